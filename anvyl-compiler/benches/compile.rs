@@ -0,0 +1,151 @@
+//! Measures time and heap usage of each front-end phase on a generated project.
+//!
+//! Run with `cargo bench -p anvyl-compiler`, optionally passing the number of components,
+//! e.g. `cargo bench -p anvyl-compiler -- 5000`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use anvyl_compiler::{
+    ast::{symbols::SymbolChecker, typechecker::TypeChecker},
+    prelude::*,
+};
+
+/// Counts the bytes allocated, so each phase's memory use can be reported.
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const DEFAULT_COMPONENTS: usize = 2000;
+
+/// A distinct, letters-only suffix for each component, e.g. `Aa`, `Ab`.
+fn suffix(mut index: usize) -> String {
+    let mut suffix = String::from("A");
+    loop {
+        suffix.push((b'a' + (index % 26) as u8) as char);
+        index /= 26;
+        if index == 0 {
+            return suffix;
+        }
+    }
+}
+
+fn generate(components: usize) -> String {
+    let mut source = String::from("const limit = 10;\n");
+    for index in 0..components {
+        let suffix = suffix(index);
+        source.push_str(&format!(
+            "component Card{suffix}(title: string, count: number) {{
+  const selected: string? = stateful(undefined);
+  const total = stateful(0);
+  let local = {index} * 2 + 1;
+
+  callback onClicked(id: string) {{
+    selected = id;
+  }}
+
+  callback reset() {{
+    total = count + local * limit;
+    for (const item of [1, 2, 3]) {{
+      total += item;
+    }}
+  }}
+}}
+"
+        ));
+    }
+    source
+}
+
+/// Runs `phase`, returning its result along with the time taken and bytes allocated.
+fn measure<T>(phase: impl FnOnce() -> T) -> (T, Duration, usize) {
+    let allocated = ALLOCATED.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let result = phase();
+    let elapsed = start.elapsed();
+    (
+        result,
+        elapsed,
+        ALLOCATED.load(Ordering::Relaxed) - allocated,
+    )
+}
+
+fn report(phase: &str, elapsed: Duration, allocated: usize) {
+    println!(
+        "{:<8} {:>10.2?} {:>10.1} KiB",
+        phase,
+        elapsed,
+        allocated as f64 / 1024.0
+    );
+}
+
+fn main() {
+    let components = std::env::args()
+        .skip(1)
+        .find_map(|argument| argument.parse().ok())
+        .unwrap_or(DEFAULT_COMPONENTS);
+    let source = generate(components);
+    let text = SourceText::new(source.as_str());
+    println!(
+        "{} components, {:.1} KiB of source",
+        components,
+        source.len() as f64 / 1024.0
+    );
+
+    let (tokens, elapsed, allocated) = measure(|| {
+        let mut lexer = Lexer::new(&source);
+        let mut tokens = Vec::new();
+        while let Some(token) = lexer.next_token() {
+            tokens.push(token);
+        }
+        tokens
+    });
+    report("lex", elapsed, allocated);
+
+    let diagnostics = DiagnosticsBag::new_ref_cell();
+    let arena = ASTArena::new();
+    let (ast, elapsed, allocated) = measure(|| {
+        let mut ast = AST::new();
+        let mut parser = Parser::new(tokens, &text, &arena, Rc::clone(&diagnostics));
+        while let Some(statement) = parser.next_statement() {
+            ast.add_statement(statement);
+        }
+        ast
+    });
+    report("parse", elapsed, allocated);
+
+    let (model, elapsed, allocated) = measure(|| {
+        let mut checker = SymbolChecker::new(Rc::clone(&diagnostics));
+        checker.check(&ast);
+        checker.into_model()
+    });
+    report("check", elapsed, allocated);
+
+    let (_, elapsed, allocated) = measure(|| {
+        let mut checker = TypeChecker::new(&model, Rc::clone(&diagnostics));
+        ast.visit(&mut checker);
+        checker.into_types()
+    });
+    report("types", elapsed, allocated);
+
+    assert!(
+        diagnostics.borrow().is_empty(),
+        "the generated project should compile without diagnostics"
+    );
+}
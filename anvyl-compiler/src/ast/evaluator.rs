@@ -1,11 +1,65 @@
 use std::collections::HashMap;
+use std::fmt::Display;
 
 use crate::ast::ASTVisitor;
+use crate::ast::iterators::{IteratorChain, IteratorConsumer, IteratorStage};
 use crate::prelude::*;
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Undefined,
+    Number(i64),
+    String(String),
+    Boolean(bool),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Undefined => false,
+            Value::Number(number) => *number != 0,
+            Value::String(string) => !string.is_empty(),
+            Value::Boolean(boolean) => *boolean,
+            Value::Array(_) => true,
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Undefined => write!(f, "undefined"),
+            Value::Number(number) => write!(f, "{}", number),
+            Value::String(string) => write!(f, "{}", string),
+            Value::Boolean(boolean) => write!(f, "{}", boolean),
+            Value::Array(values) => {
+                let values: Vec<String> = values.iter().map(Value::to_string).collect();
+                write!(f, "{}", values.join(","))
+            }
+        }
+    }
+}
+
+/// A pending `break` or `continue`, along with the label of the loop it targets.
+#[derive(Debug)]
+enum ControlFlow {
+    Break(Option<String>),
+    Continue(Option<String>),
+}
+
 pub struct ASTEvaluator {
-    pub last_value: Option<i64>,
-    pub variables: HashMap<String, i64>,
+    pub last_value: Option<Value>,
+    pub variables: HashMap<String, Value>,
+    /// Literal union types, which evaluate to an array of their members in value position.
+    types: HashMap<String, Vec<Value>>,
+    control: Option<ControlFlow>,
+}
+
+impl Default for ASTEvaluator {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ASTEvaluator {
@@ -13,49 +67,543 @@ impl ASTEvaluator {
         Self {
             last_value: None,
             variables: HashMap::new(),
+            types: HashMap::new(),
+            control: None,
+        }
+    }
+}
+
+impl ASTEvaluator {
+    /// Applies a binary operator, giving `undefined` for numbers out of the `i64` range.
+    fn apply(operator: &ASTBinaryOperatorKind, left: Value, right: Value) -> Value {
+        let number = |value: Option<i64>| value.map_or(Value::Undefined, Value::Number);
+        match (operator, left, right) {
+            (ASTBinaryOperatorKind::Add, Value::Number(left), Value::Number(right)) => {
+                number(left.checked_add(right))
+            }
+            (ASTBinaryOperatorKind::Add, left @ Value::String(_), right)
+            | (ASTBinaryOperatorKind::Add, left, right @ Value::String(_)) => {
+                Value::String(format!("{}{}", left, right))
+            }
+            (ASTBinaryOperatorKind::Subtract, Value::Number(left), Value::Number(right)) => {
+                number(left.checked_sub(right))
+            }
+            (ASTBinaryOperatorKind::Multiply, Value::Number(left), Value::Number(right)) => {
+                number(left.checked_mul(right))
+            }
+            (ASTBinaryOperatorKind::Divide, Value::Number(left), Value::Number(right)) => {
+                number(left.checked_div(right))
+            }
+            _ => Value::Undefined,
+        }
+    }
+
+    fn evaluate(&mut self, expression: &ASTExpression) -> Value {
+        self.visit_expression(expression);
+        self.last_value.clone().unwrap_or(Value::Undefined)
+    }
+
+    fn literal_members(&self, ty: &ASTType) -> Vec<Value> {
+        match ty.kind() {
+            ASTTypeKind::Literal(literal) => match &literal.kind {
+                TokenKind::Number(number) => vec![Value::Number(*number)],
+                TokenKind::String(string) => vec![Value::String(string.clone())],
+                _ => Vec::new(),
+            },
+            ASTTypeKind::Named(identifier) => self
+                .types
+                .get(identifier.text())
+                .cloned()
+                .unwrap_or_default(),
+            ASTTypeKind::Trusted(trusted) => self.literal_members(trusted.inner()),
+            ASTTypeKind::Union(union) => {
+                let mut members: Vec<Value> = Vec::new();
+                for member in union.members() {
+                    for value in self.literal_members(member) {
+                        if !members.contains(&value) {
+                            members.push(value);
+                        }
+                    }
+                }
+                members
+            }
+            ASTTypeKind::Error(_)
+            | ASTTypeKind::Optional(_)
+            | ASTTypeKind::Array(_)
+            | ASTTypeKind::Function(_)
+            | ASTTypeKind::Generic(_)
+            | ASTTypeKind::Object(_) => Vec::new(),
+        }
+    }
+
+    /// Executes statements until one of them breaks or continues a loop.
+    fn execute_block(&mut self, statements: &[ASTStatement]) {
+        for statement in statements {
+            if self.control.is_some() {
+                return;
+            }
+            self.visit_statement(statement);
+        }
+    }
+
+    /// Consumes a pending `break` or `continue` aimed at the loop with `label`.
+    ///
+    /// Returns whether the loop should stop iterating, which is also the case when
+    /// the jump targets an enclosing loop and is left pending for it.
+    fn finish_iteration(&mut self, label: Option<&str>) -> bool {
+        match self.control.take() {
+            None | Some(ControlFlow::Continue(None)) => false,
+            Some(ControlFlow::Break(None)) => true,
+            Some(ControlFlow::Continue(Some(target))) if Some(target.as_str()) == label => false,
+            Some(ControlFlow::Break(Some(target))) if Some(target.as_str()) == label => true,
+            outer => {
+                self.control = outer;
+                true
+            }
+        }
+    }
+
+    /// Calls a closure with `item`, binding its parameters for the duration of the call.
+    fn call_closure(&mut self, closure: &ASTClosureExpression, item: Value) -> Value {
+        // Adaptors pass a single item, leaving any other parameter undefined.
+        let mut item = Some(item);
+        let bindings: Vec<(String, Value)> = closure
+            .parameters
+            .iter()
+            .flat_map(|parameter| {
+                let argument = item.take().unwrap_or(Value::Undefined);
+                match parameter {
+                    ASTClosureParameter::Binding(parameter) => {
+                        vec![(parameter.identifier.text().to_string(), argument)]
+                    }
+                    ASTClosureParameter::Tuple(items) => {
+                        let values = match argument {
+                            Value::Array(values) => values,
+                            _ => Vec::new(),
+                        };
+                        items
+                            .iter()
+                            .enumerate()
+                            .map(|(index, item)| {
+                                let value = values.get(index).cloned().unwrap_or(Value::Undefined);
+                                (item.text().to_string(), value)
+                            })
+                            .collect()
+                    }
+                }
+            })
+            .collect();
+
+        let shadowed: Vec<(String, Option<Value>)> = bindings
+            .into_iter()
+            .map(|(name, value)| {
+                let previous = self.variables.insert(name.clone(), value);
+                (name, previous)
+            })
+            .collect();
+        let result = self.evaluate(&closure.body);
+        for (name, previous) in shadowed.into_iter().rev() {
+            match previous {
+                Some(value) => self.variables.insert(name, value),
+                None => self.variables.remove(&name),
+            };
+        }
+        result
+    }
+
+    /// Runs an iterator chain the way its fused loop does, taking each item through every
+    /// adaptor before moving on to the next one.
+    fn evaluate_chain(&mut self, chain: &IteratorChain) -> Value {
+        let items: Vec<Value> = match self.evaluate(chain.source) {
+            Value::Array(values) => values,
+            Value::String(string) => string
+                .chars()
+                .map(|character| Value::String(character.to_string()))
+                .collect(),
+            _ => Vec::new(),
+        };
+        // The limit of each `take`, and how many items went through it or `enumerate`.
+        let limits: Vec<Option<Value>> = chain
+            .stages
+            .iter()
+            .map(|stage| match stage {
+                IteratorStage::Take(count) => Some(self.evaluate(count)),
+                _ => None,
+            })
+            .collect();
+        let mut counts = vec![0; chain.stages.len()];
+        let mut sum = Value::Number(0);
+        let mut collected = Vec::new();
+
+        'items: for item in items {
+            let exhausted = limits.iter().zip(&counts).any(
+                |(limit, count)| matches!(limit, Some(Value::Number(limit)) if count >= limit),
+            );
+            if exhausted {
+                break;
+            }
+
+            let mut item = item;
+            for (index, stage) in chain.stages.iter().enumerate() {
+                match stage {
+                    IteratorStage::Map(closure) => item = self.call_closure(closure, item),
+                    IteratorStage::Filter(closure) => {
+                        if !self.call_closure(closure, item.clone()).is_truthy() {
+                            continue 'items;
+                        }
+                    }
+                    IteratorStage::Take(_) => counts[index] += 1,
+                    IteratorStage::Enumerate => {
+                        item = Value::Array(vec![Value::Number(counts[index]), item]);
+                        counts[index] += 1;
+                    }
+                }
+            }
+
+            match &chain.consumer {
+                IteratorConsumer::Sum => {
+                    sum = Self::apply(&ASTBinaryOperatorKind::Add, sum, item);
+                }
+                IteratorConsumer::Collect => collected.push(item),
+                IteratorConsumer::Find(closure) => {
+                    if self.call_closure(closure, item.clone()).is_truthy() {
+                        return item;
+                    }
+                }
+                IteratorConsumer::Any(closure) => {
+                    if self.call_closure(closure, item).is_truthy() {
+                        return Value::Boolean(true);
+                    }
+                }
+                IteratorConsumer::All(closure) => {
+                    if !self.call_closure(closure, item).is_truthy() {
+                        return Value::Boolean(false);
+                    }
+                }
+            }
+        }
+
+        match chain.consumer {
+            IteratorConsumer::Sum => sum,
+            IteratorConsumer::Collect => Value::Array(collected),
+            IteratorConsumer::Find(_) => Value::Undefined,
+            IteratorConsumer::Any(_) => Value::Boolean(false),
+            IteratorConsumer::All(_) => Value::Boolean(true),
+        }
+    }
+
+    fn assign_index(&mut self, expression: &ASTIndexExpression, value: Value) {
+        let ASTExpressionKind::Variable(variable) = expression.object.kind() else {
+            return;
+        };
+        let Value::Number(index) = self.evaluate(&expression.index) else {
+            return;
+        };
+
+        if let Some(Value::Array(values)) = self.variables.get_mut(variable.identifier())
+            && let Some(element) = usize::try_from(index)
+                .ok()
+                .and_then(|index| values.get_mut(index))
+        {
+            *element = value;
         }
     }
 }
 
 impl ASTVisitor for ASTEvaluator {
-    fn visit_variable_expression(&mut self, expression: &crate::prelude::ASTVariableExpression) {
-        self.last_value = Some(
-            *self
-                .variables
-                .get(&expression.identifier.span.clone_text())
-                .unwrap(),
-        );
+    fn visit_variable_expression(&mut self, expression: &ASTVariableExpression) {
+        let identifier = expression.identifier();
+        let value = match self.variables.get(identifier) {
+            Some(value) => value.clone(),
+            None => match self.types.get(identifier) {
+                Some(members) => Value::Array(members.clone()),
+                None => Value::Undefined,
+            },
+        };
+        self.last_value = Some(value);
     }
 
-    fn visit_let_statement(&mut self, let_statement: &crate::prelude::ASTLetStatement) {
-        self.visit_expression(&let_statement.initializer);
-        self.variables.insert(
-            let_statement.identifier.span.clone_text(),
-            self.last_value.unwrap(),
-        );
+    fn visit_let_statement(&mut self, let_statement: &ASTLetStatement) {
+        let value = self.evaluate(&let_statement.initializer);
+        match &let_statement.pattern {
+            ASTPattern::Identifier(identifier) => {
+                self.variables.insert(identifier.text().to_string(), value);
+            }
+            // There are no object values, so destructured properties are always undefined.
+            ASTPattern::Object(properties) => {
+                for property in properties {
+                    self.variables
+                        .insert(property.text().to_string(), Value::Undefined);
+                }
+            }
+        }
     }
 
-    fn visit_number_expression(&mut self, expression: &crate::prelude::ASTNumberExpression) {
-        self.last_value = Some(expression.number());
+    fn visit_type_alias_statement(&mut self, type_alias: &ASTTypeAliasStatement) {
+        let members = self.literal_members(&type_alias.ty);
+        self.types
+            .insert(type_alias.identifier.text().to_string(), members);
     }
 
-    fn visit_binary_expression(&mut self, expression: &crate::prelude::ASTBinaryExpression) {
-        self.visit_expression(&expression.left);
-        let left = self.last_value.unwrap();
-        self.visit_expression(&expression.right);
-        let right = self.last_value.unwrap();
-        self.last_value = Some(match expression.operator.kind {
-            ASTBinaryOperatorKind::Add => left + right,
-            ASTBinaryOperatorKind::Subtract => left - right,
-            ASTBinaryOperatorKind::Multiply => left * right,
-            ASTBinaryOperatorKind::Divide => left / right,
-        });
+    fn visit_number_expression(&mut self, expression: &ASTNumberExpression) {
+        self.last_value = Some(Value::Number(expression.number()));
+    }
+
+    fn visit_string_expression(&mut self, expression: &ASTStringExpression) {
+        self.last_value = Some(Value::String(expression.value().to_string()));
+    }
+
+    fn visit_array_expression(&mut self, expression: &ASTArrayExpression) {
+        let values = expression
+            .elements
+            .iter()
+            .map(|element| self.evaluate(element))
+            .collect();
+        self.last_value = Some(Value::Array(values));
     }
 
-    fn visit_parenthesized_expression(
-        &mut self,
-        expression: &crate::prelude::ASTParenthesizedExpression,
-    ) {
+    fn visit_binary_expression(&mut self, expression: &ASTBinaryExpression) {
+        let left = self.evaluate(&expression.left);
+        let right = self.evaluate(&expression.right);
+        self.last_value = Some(Self::apply(&expression.operator.kind, left, right));
+    }
+
+    fn visit_parenthesized_expression(&mut self, expression: &ASTParenthesizedExpression) {
         self.visit_expression(&expression.inner);
     }
+
+    fn visit_member_expression(&mut self, expression: &ASTMemberExpression) {
+        let object = self.evaluate(&expression.object);
+        self.last_value = Some(match (object, expression.property()) {
+            (Value::Array(values), "length") => Value::Number(values.len() as i64),
+            (Value::String(string), "length") => Value::Number(string.chars().count() as i64),
+            _ => Value::Undefined,
+        });
+    }
+
+    fn visit_index_expression(&mut self, expression: &ASTIndexExpression) {
+        let object = self.evaluate(&expression.object);
+        let index = self.evaluate(&expression.index);
+
+        let element = match (object, index) {
+            (Value::Array(values), Value::Number(index)) => usize::try_from(index)
+                .ok()
+                .and_then(|index| values.get(index).cloned()),
+            (Value::String(string), Value::Number(index)) => usize::try_from(index)
+                .ok()
+                .and_then(|index| string.chars().nth(index))
+                .map(|character| Value::String(character.to_string())),
+            _ => None,
+        };
+        self.last_value = Some(element.unwrap_or(Value::Undefined));
+    }
+
+    fn visit_call_expression(&mut self, expression: &ASTCallExpression) {
+        if let Some(chain) = IteratorChain::of(expression) {
+            self.last_value = Some(self.evaluate_chain(&chain));
+            return;
+        }
+
+        self.visit_expression(&expression.callee);
+        for argument in &expression.arguments {
+            self.visit_expression(argument);
+        }
+    }
+
+    /// Closures only run when passed to an iterator adaptor, see `evaluate_chain`.
+    fn visit_closure_expression(&mut self, _expression: &ASTClosureExpression) {
+        self.last_value = Some(Value::Undefined);
+    }
+
+    fn visit_component_statement(&mut self, _component: &ASTComponentStatement) {}
+
+    fn visit_element_expression(&mut self, _expression: &ASTElementExpression) {
+        self.last_value = Some(Value::Undefined);
+    }
+
+    fn visit_callback_statement(&mut self, _callback: &ASTCallbackStatement) {}
+
+    fn visit_assignment_expression(&mut self, expression: &ASTAssignmentExpression) {
+        let mut value = self.evaluate(&expression.value);
+
+        if let Some(operator) = expression.operator.binary_operator() {
+            let current = self.evaluate(&expression.target);
+            value = Self::apply(&operator, current, value);
+        }
+
+        match expression.target.kind() {
+            ASTExpressionKind::Variable(variable) => {
+                self.variables
+                    .insert(variable.identifier.text().to_string(), value.clone());
+            }
+            ASTExpressionKind::Index(index) => self.assign_index(index, value.clone()),
+            // Members can't be assigned, since the evaluator has no objects.
+            _ => {}
+        }
+
+        self.last_value = Some(value);
+    }
+
+    fn visit_for_statement(&mut self, for_statement: &ASTForStatement) {
+        let iterable = self.evaluate(&for_statement.iterable);
+        let items: Vec<Value> = match (&for_statement.kind, iterable) {
+            (ASTForKind::Of, Value::Array(values)) => values,
+            (ASTForKind::Of, Value::String(string)) => string
+                .chars()
+                .map(|character| Value::String(character.to_string()))
+                .collect(),
+            (ASTForKind::In, Value::Array(values)) => {
+                (0..values.len() as i64).map(Value::Number).collect()
+            }
+            (ASTForKind::In, Value::String(string)) => (0..string.chars().count() as i64)
+                .map(Value::Number)
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        let identifier = for_statement.identifier.text().to_string();
+        let shadowed = self.variables.get(&identifier).cloned();
+
+        for item in items {
+            self.variables.insert(identifier.clone(), item);
+            self.execute_block(&for_statement.body);
+            if self.finish_iteration(for_statement.label()) {
+                break;
+            }
+        }
+
+        match shadowed {
+            Some(value) => self.variables.insert(identifier, value),
+            None => self.variables.remove(&identifier),
+        };
+    }
+
+    fn visit_while_statement(&mut self, while_statement: &ASTWhileStatement) {
+        while self.evaluate(&while_statement.condition).is_truthy() {
+            self.execute_block(&while_statement.body);
+            if self.finish_iteration(while_statement.label()) {
+                break;
+            }
+        }
+    }
+
+    fn visit_break_statement(&mut self, break_statement: &ASTBreakStatement) {
+        let label = break_statement.label().map(str::to_string);
+        self.control = Some(ControlFlow::Break(label));
+    }
+
+    fn visit_continue_statement(&mut self, continue_statement: &ASTContinueStatement) {
+        let label = continue_statement.label().map(str::to_string);
+        self.control = Some(ControlFlow::Continue(label));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Value;
+    use crate::testing::evaluate;
+
+    #[test]
+    fn breaks_and_continues_the_labelled_loop() {
+        let source = "let total = 0;
+outer: for x of [1, 2, 3] {
+    for y of [10, 20] {
+        total += x * y;
+        break outer;
+    }
+}
+total";
+        assert_eq!(evaluate(source), Value::Number(10));
+
+        let source = "let total = 0;
+outer: for x of [1, 2, 3] {
+    for y of [10, 20] {
+        total += x * y;
+        continue outer;
+    }
+    total += 100;
+}
+total";
+        assert_eq!(evaluate(source), Value::Number(60));
+    }
+
+    #[test]
+    fn breaks_the_innermost_loop_without_a_label() {
+        let source = "let total = 0;
+for x of [1, 2, 3] {
+    for y of [10, 20] {
+        total += y;
+        break;
+    }
+    total += x;
+}
+total";
+        assert_eq!(evaluate(source), Value::Number(36));
+    }
+
+    #[test]
+    fn iterates_over_indices_and_characters() {
+        let source = "let total = 0;
+for i in [5, 6, 7] {
+    total += i;
+}
+for (const i in \"ab\") {
+    total += i * 10;
+}
+total";
+        assert_eq!(evaluate(source), Value::Number(13));
+
+        let source = "let reversed = \"\";
+for c of \"abc\" {
+    reversed = c + reversed;
+}
+reversed";
+        assert_eq!(evaluate(source), Value::String("cba".to_string()));
+    }
+
+    #[test]
+    fn restores_the_variable_shadowed_by_a_loop() {
+        assert_eq!(
+            evaluate("let x = 7;\nfor x of [1, 2] {}\nx"),
+            Value::Number(7)
+        );
+        assert_eq!(evaluate("for y of [1, 2] {}\ny"), Value::Undefined);
+    }
+
+    #[test]
+    fn loops_until_the_condition_is_falsy() {
+        let source = "let n = 3;
+let total = 0;
+while n {
+    total += n;
+    n -= 1;
+}
+total";
+        assert_eq!(evaluate(source), Value::Number(6));
+
+        let source = "let n = 0;
+while 1 {
+    n += 1;
+    break;
+}
+n";
+        assert_eq!(evaluate(source), Value::Number(1));
+    }
+
+    #[test]
+    fn applies_assignments_and_compound_assignments() {
+        assert_eq!(
+            evaluate("let x = 1;\nx += 2;\nx = x * 3;\nx"),
+            Value::Number(9)
+        );
+        // An assignment gives the assigned value.
+        assert_eq!(
+            evaluate("let x = 12;\nx /= 4;\nx -= 1;\nx *= 5"),
+            Value::Number(10)
+        );
+        assert_eq!(
+            evaluate("let xs = [1, 2];\nxs[1] += 5;\nxs[0] -= 3;\nxs"),
+            Value::Array(vec![Value::Number(-2), Value::Number(7)])
+        );
+    }
 }
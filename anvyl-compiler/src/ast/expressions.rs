@@ -1,57 +1,258 @@
-use crate::prelude::{TextSpan, Token};
+use bumpalo::boxed::Box;
+
+use crate::prelude::{ASTArena, ASTParameter, ASTType, NodeId, TextSpan, Token, TokenKind};
 
 #[derive(Debug)]
-pub enum ASTExpressionKind {
+pub enum ASTExpressionKind<'a> {
     Error(TextSpan),
     Number(ASTNumberExpression),
-    Binary(ASTBinaryExpression),
-    Parenthesized(ASTParenthesizedExpression),
+    String(ASTStringExpression),
+    Array(ASTArrayExpression<'a>),
+    Binary(ASTBinaryExpression<'a>),
+    Parenthesized(ASTParenthesizedExpression<'a>),
     Variable(ASTVariableExpression),
+    Call(ASTCallExpression<'a>),
+    Member(ASTMemberExpression<'a>),
+    Index(ASTIndexExpression<'a>),
+    Assignment(ASTAssignmentExpression<'a>),
+    /// A JSX element, e.g. `<Column gap=2>...</Column>`.
+    Element(ASTElementExpression<'a>),
+    /// Text within a JSX element, e.g. `Hello world` in `<p>Hello world</p>`.
+    Text(ASTTextExpression),
+    /// A closure, e.g. `|message| message.text`.
+    Closure(ASTClosureExpression<'a>),
 }
 
 #[derive(Debug)]
-pub struct ASTExpression {
-    kind: ASTExpressionKind,
+pub struct ASTExpression<'a> {
+    pub(crate) id: NodeId,
+    pub(crate) kind: ASTExpressionKind<'a>,
+    pub(crate) span: TextSpan,
 }
 
-impl ASTExpression {
-    pub fn new(kind: ASTExpressionKind) -> Self {
-        Self { kind }
+impl<'a> ASTExpression<'a> {
+    pub fn new(kind: ASTExpressionKind<'a>, id: NodeId, span: TextSpan) -> Self {
+        Self { id, kind, span }
+    }
+
+    pub fn number(value: i64, id: NodeId, span: TextSpan) -> Self {
+        ASTExpression::new(
+            ASTExpressionKind::Number(ASTNumberExpression::new(value)),
+            id,
+            span,
+        )
+    }
+
+    pub fn string(literal: Token, id: NodeId, span: TextSpan) -> Self {
+        ASTExpression::new(
+            ASTExpressionKind::String(ASTStringExpression::new(literal)),
+            id,
+            span,
+        )
+    }
+
+    pub fn array(elements: Vec<ASTExpression<'a>>, id: NodeId, span: TextSpan) -> Self {
+        ASTExpression::new(
+            ASTExpressionKind::Array(ASTArrayExpression { elements }),
+            id,
+            span,
+        )
+    }
+
+    pub fn binary(
+        left: ASTExpression<'a>,
+        operator: ASTBinaryOperator,
+        right: ASTExpression<'a>,
+        id: NodeId,
+        span: TextSpan,
+        arena: &'a ASTArena,
+    ) -> Self {
+        ASTExpression::new(
+            ASTExpressionKind::Binary(ASTBinaryExpression {
+                left: Box::new_in(left, arena),
+                operator,
+                right: Box::new_in(right, arena),
+            }),
+            id,
+            span,
+        )
     }
 
-    pub fn number(value: i64) -> Self {
-        ASTExpression::new(ASTExpressionKind::Number(ASTNumberExpression::new(value)))
+    pub fn parenthesized(
+        expression: ASTExpression<'a>,
+        id: NodeId,
+        span: TextSpan,
+        arena: &'a ASTArena,
+    ) -> Self {
+        ASTExpression::new(
+            ASTExpressionKind::Parenthesized(ASTParenthesizedExpression {
+                inner: Box::new_in(expression, arena),
+            }),
+            id,
+            span,
+        )
     }
 
-    pub fn binary(left: ASTExpression, operator: ASTBinaryOperator, right: ASTExpression) -> Self {
-        ASTExpression::new(ASTExpressionKind::Binary(ASTBinaryExpression {
-            left: Box::new(left),
-            operator,
-            right: Box::new(right),
-        }))
+    pub fn identifier(identifier: Token, id: NodeId, span: TextSpan) -> Self {
+        ASTExpression::new(
+            ASTExpressionKind::Variable(ASTVariableExpression::new(identifier)),
+            id,
+            span,
+        )
     }
 
-    pub fn parenthesized(expression: ASTExpression) -> Self {
-        ASTExpression::new(ASTExpressionKind::Parenthesized(
-            ASTParenthesizedExpression {
-                inner: Box::new(expression),
-            },
-        ))
+    pub fn call(
+        callee: ASTExpression<'a>,
+        arguments: Vec<ASTExpression<'a>>,
+        id: NodeId,
+        span: TextSpan,
+        arena: &'a ASTArena,
+    ) -> Self {
+        ASTExpression::generic_call(callee, Vec::new(), arguments, id, span, arena)
     }
 
-    pub fn identifier(identifier: Token) -> Self {
-        ASTExpression::new(ASTExpressionKind::Variable(ASTVariableExpression::new(
-            identifier,
-        )))
+    /// A call with explicit type arguments, e.g. `validate<Message>(data)`.
+    pub fn generic_call(
+        callee: ASTExpression<'a>,
+        type_arguments: Vec<ASTType<'a>>,
+        arguments: Vec<ASTExpression<'a>>,
+        id: NodeId,
+        span: TextSpan,
+        arena: &'a ASTArena,
+    ) -> Self {
+        ASTExpression::new(
+            ASTExpressionKind::Call(ASTCallExpression {
+                callee: Box::new_in(callee, arena),
+                type_arguments,
+                arguments,
+            }),
+            id,
+            span,
+        )
     }
 
-    pub fn error(span: TextSpan) -> Self {
-        ASTExpression::new(ASTExpressionKind::Error(span))
+    pub fn member(
+        object: ASTExpression<'a>,
+        property: Token,
+        id: NodeId,
+        span: TextSpan,
+        arena: &'a ASTArena,
+    ) -> Self {
+        ASTExpression::new(
+            ASTExpressionKind::Member(ASTMemberExpression {
+                object: Box::new_in(object, arena),
+                property,
+            }),
+            id,
+            span,
+        )
     }
 
-    pub fn kind(&self) -> &ASTExpressionKind {
+    pub fn index(
+        object: ASTExpression<'a>,
+        index: ASTExpression<'a>,
+        id: NodeId,
+        span: TextSpan,
+        arena: &'a ASTArena,
+    ) -> Self {
+        ASTExpression::new(
+            ASTExpressionKind::Index(ASTIndexExpression {
+                object: Box::new_in(object, arena),
+                index: Box::new_in(index, arena),
+            }),
+            id,
+            span,
+        )
+    }
+
+    pub fn assignment(
+        target: ASTExpression<'a>,
+        operator: ASTAssignmentOperator,
+        value: ASTExpression<'a>,
+        id: NodeId,
+        span: TextSpan,
+        arena: &'a ASTArena,
+    ) -> Self {
+        ASTExpression::new(
+            ASTExpressionKind::Assignment(ASTAssignmentExpression {
+                target: Box::new_in(target, arena),
+                operator,
+                value: Box::new_in(value, arena),
+            }),
+            id,
+            span,
+        )
+    }
+
+    pub fn element(
+        name: Token,
+        attributes: Vec<ASTAttribute<'a>>,
+        children: Option<Vec<ASTExpression<'a>>>,
+        id: NodeId,
+        span: TextSpan,
+    ) -> Self {
+        ASTExpression::new(
+            ASTExpressionKind::Element(ASTElementExpression {
+                name,
+                attributes,
+                children,
+            }),
+            id,
+            span,
+        )
+    }
+
+    pub fn text(value: String, id: NodeId, span: TextSpan) -> Self {
+        ASTExpression::new(
+            ASTExpressionKind::Text(ASTTextExpression { value }),
+            id,
+            span,
+        )
+    }
+
+    pub fn closure(
+        parameters: Vec<ASTClosureParameter<'a>>,
+        body: ASTExpression<'a>,
+        id: NodeId,
+        span: TextSpan,
+        arena: &'a ASTArena,
+    ) -> Self {
+        ASTExpression::new(
+            ASTExpressionKind::Closure(ASTClosureExpression {
+                parameters,
+                body: Box::new_in(body, arena),
+            }),
+            id,
+            span,
+        )
+    }
+
+    pub fn error(id: NodeId, span: TextSpan) -> Self {
+        ASTExpression::new(ASTExpressionKind::Error(span), id, span)
+    }
+
+    pub fn kind(&self) -> &ASTExpressionKind<'a> {
         &self.kind
     }
+
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    /// The source range of the whole node.
+    pub fn span(&self) -> &TextSpan {
+        &self.span
+    }
+
+    /// Whether the expression can be the target of an assignment.
+    pub fn is_assignable(&self) -> bool {
+        matches!(
+            self.kind,
+            ASTExpressionKind::Variable(_)
+                | ASTExpressionKind::Member(_)
+                | ASTExpressionKind::Index(_)
+        )
+    }
 }
 
 #[derive(Debug)]
@@ -70,10 +271,40 @@ impl ASTNumberExpression {
 }
 
 #[derive(Debug)]
-pub struct ASTBinaryExpression {
-    pub(crate) left: Box<ASTExpression>,
+pub struct ASTStringExpression {
+    pub(crate) literal: Token,
+}
+
+impl ASTStringExpression {
+    pub fn new(literal: Token) -> Self {
+        Self { literal }
+    }
+
+    pub fn value(&self) -> &str {
+        match &self.literal.kind {
+            TokenKind::String(value) => value,
+            _ => unreachable!("string expression without a string token"),
+        }
+    }
+}
+
+/// An `[<element>, ...]` expression.
+#[derive(Debug)]
+pub struct ASTArrayExpression<'a> {
+    pub(crate) elements: Vec<ASTExpression<'a>>,
+}
+
+impl<'a> ASTArrayExpression<'a> {
+    pub fn elements(&self) -> &[ASTExpression<'a>] {
+        &self.elements
+    }
+}
+
+#[derive(Debug)]
+pub struct ASTBinaryExpression<'a> {
+    pub(crate) left: Box<'a, ASTExpression<'a>>,
     pub(crate) operator: ASTBinaryOperator,
-    pub(crate) right: Box<ASTExpression>,
+    pub(crate) right: Box<'a, ASTExpression<'a>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -104,8 +335,8 @@ impl ASTBinaryOperator {
 }
 
 #[derive(Debug)]
-pub struct ASTParenthesizedExpression {
-    pub(crate) inner: Box<ASTExpression>,
+pub struct ASTParenthesizedExpression<'a> {
+    pub(crate) inner: Box<'a, ASTExpression<'a>>,
 }
 
 #[derive(Debug)]
@@ -119,6 +350,266 @@ impl ASTVariableExpression {
     }
 
     pub fn identifier(&self) -> &str {
-        &self.identifier.span.literal
+        &self.identifier.text
+    }
+}
+
+#[derive(Debug)]
+pub struct ASTCallExpression<'a> {
+    pub(crate) callee: Box<'a, ASTExpression<'a>>,
+    pub(crate) type_arguments: Vec<ASTType<'a>>,
+    pub(crate) arguments: Vec<ASTExpression<'a>>,
+}
+
+impl<'a> ASTCallExpression<'a> {
+    /// Returns the name of the called function when the callee is a plain identifier.
+    pub fn callee_identifier(&self) -> Option<&str> {
+        match self.callee.kind() {
+            ASTExpressionKind::Variable(variable) => Some(variable.identifier()),
+            _ => None,
+        }
     }
+
+    /// Returns the receiver and name of the called method when the callee is a member, e.g.
+    /// `items` and `iter` for `items.iter()`.
+    pub fn method(&self) -> Option<(&ASTExpression<'a>, &Token)> {
+        match self.callee.kind() {
+            ASTExpressionKind::Member(member) => Some((&member.object, &member.property)),
+            _ => None,
+        }
+    }
+
+    pub fn type_arguments(&self) -> &[ASTType<'a>] {
+        &self.type_arguments
+    }
+
+    pub fn arguments(&self) -> &[ASTExpression<'a>] {
+        &self.arguments
+    }
+}
+
+/// An `<object>.<property>` expression.
+#[derive(Debug)]
+pub struct ASTMemberExpression<'a> {
+    pub(crate) object: Box<'a, ASTExpression<'a>>,
+    pub(crate) property: Token,
+}
+
+impl<'a> ASTMemberExpression<'a> {
+    pub fn property(&self) -> &str {
+        &self.property.text
+    }
+}
+
+/// An `<object>[<index>]` expression.
+#[derive(Debug)]
+pub struct ASTIndexExpression<'a> {
+    pub(crate) object: Box<'a, ASTExpression<'a>>,
+    pub(crate) index: Box<'a, ASTExpression<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ASTAssignmentOperatorKind {
+    Assign,
+    AddAssign,
+    SubtractAssign,
+    MultiplyAssign,
+    DivideAssign,
+}
+
+#[derive(Debug, Clone)]
+pub struct ASTAssignmentOperator {
+    pub(crate) kind: ASTAssignmentOperatorKind,
+    pub(crate) token: Token,
+}
+
+impl ASTAssignmentOperator {
+    pub fn new(kind: ASTAssignmentOperatorKind, token: Token) -> Self {
+        Self { kind, token }
+    }
+
+    /// The binary operator a compound assignment applies, e.g. `Add` for `+=`.
+    pub fn binary_operator(&self) -> Option<ASTBinaryOperatorKind> {
+        match self.kind {
+            ASTAssignmentOperatorKind::Assign => None,
+            ASTAssignmentOperatorKind::AddAssign => Some(ASTBinaryOperatorKind::Add),
+            ASTAssignmentOperatorKind::SubtractAssign => Some(ASTBinaryOperatorKind::Subtract),
+            ASTAssignmentOperatorKind::MultiplyAssign => Some(ASTBinaryOperatorKind::Multiply),
+            ASTAssignmentOperatorKind::DivideAssign => Some(ASTBinaryOperatorKind::Divide),
+        }
+    }
+
+    pub fn is_compound(&self) -> bool {
+        self.kind != ASTAssignmentOperatorKind::Assign
+    }
+}
+
+/// An `<target> = <value>` or compound assignment expression, e.g. `sum += value`.
+///
+/// The parser only produces assignable targets: variables, members and indexes.
+#[derive(Debug)]
+pub struct ASTAssignmentExpression<'a> {
+    pub(crate) target: Box<'a, ASTExpression<'a>>,
+    pub(crate) operator: ASTAssignmentOperator,
+    pub(crate) value: Box<'a, ASTExpression<'a>>,
+}
+
+impl<'a> ASTAssignmentExpression<'a> {
+    /// Returns the assigned variable when the target is a plain identifier.
+    pub fn target_variable(&self) -> Option<&ASTVariableExpression> {
+        match self.target.kind() {
+            ASTExpressionKind::Variable(variable) => Some(variable),
+            _ => None,
+        }
+    }
+
+    /// Returns the variable expression at the root of the target, e.g. `a` for `a.b[0] = c`.
+    pub fn root_variable(&self) -> Option<&ASTExpression<'a>> {
+        let mut target = self.target.as_ref();
+        loop {
+            match target.kind() {
+                ASTExpressionKind::Variable(_) => return Some(target),
+                ASTExpressionKind::Member(member) => target = &member.object,
+                ASTExpressionKind::Index(index) => target = &index.object,
+                _ => return None,
+            }
+        }
+    }
+}
+
+/// A JSX element, e.g. `<ChatMessages #messages selected={id} />`.
+///
+/// Children are either nested elements or `{<expression>}` containers.
+#[derive(Debug)]
+pub struct ASTElementExpression<'a> {
+    /// Empty for a fragment, `<>...</>`.
+    pub(crate) name: Token,
+    pub(crate) attributes: Vec<ASTAttribute<'a>>,
+    /// `None` for a self-closing element.
+    pub(crate) children: Option<Vec<ASTExpression<'a>>>,
+}
+
+impl<'a> ASTElementExpression<'a> {
+    pub fn name(&self) -> &str {
+        self.name.text()
+    }
+
+    pub fn is_fragment(&self) -> bool {
+        self.name().is_empty()
+    }
+
+    pub fn attributes(&self) -> &[ASTAttribute<'a>] {
+        &self.attributes
+    }
+
+    pub fn children(&self) -> Option<&[ASTExpression<'a>]> {
+        self.children.as_deref()
+    }
+}
+
+/// Text within a JSX element, with its whitespace collapsed the way JSX does: lines are
+/// trimmed, and joined by a space unless empty.
+#[derive(Debug)]
+pub struct ASTTextExpression {
+    pub(crate) value: String,
+}
+
+impl ASTTextExpression {
+    /// Collapses the whitespace of raw JSX text, giving an empty string for text which is
+    /// only whitespace spanning several lines, e.g. the indentation between two elements.
+    pub fn collapse(raw: &str) -> String {
+        let lines: Vec<&str> = raw.lines().collect();
+        let last = lines.iter().rposition(|line| !line.trim().is_empty());
+        let mut value = String::new();
+        for (index, line) in lines.iter().enumerate() {
+            let mut line = *line;
+            if index > 0 {
+                line = line.trim_start();
+            }
+            if index + 1 < lines.len() || raw.ends_with('\n') {
+                line = line.trim_end();
+            }
+            if line.is_empty() {
+                continue;
+            }
+            value.push_str(line);
+            if last.is_some_and(|last| index < last) {
+                value.push(' ');
+            }
+        }
+        value
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+/// An element attribute: `name=<literal>`, `name={<expression>}` or the `#name`
+/// shorthand, which passes the variable of the same name.
+#[derive(Debug)]
+pub struct ASTAttribute<'a> {
+    pub(crate) name: Token,
+    pub(crate) value: ASTExpression<'a>,
+    pub(crate) shorthand: bool,
+}
+
+impl<'a> ASTAttribute<'a> {
+    pub fn new(name: Token, value: ASTExpression<'a>, shorthand: bool) -> Self {
+        Self {
+            name,
+            value,
+            shorthand,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.text()
+    }
+
+    pub fn value(&self) -> &ASTExpression<'a> {
+        &self.value
+    }
+
+    pub fn is_shorthand(&self) -> bool {
+        self.shorthand
+    }
+}
+
+/// A `|<parameters>| <body>` closure, e.g. `|message| message.text`.
+#[derive(Debug)]
+pub struct ASTClosureExpression<'a> {
+    pub(crate) parameters: Vec<ASTClosureParameter<'a>>,
+    pub(crate) body: Box<'a, ASTExpression<'a>>,
+}
+
+impl<'a> ASTClosureExpression<'a> {
+    pub fn parameters(&self) -> &[ASTClosureParameter<'a>] {
+        &self.parameters
+    }
+
+    pub fn body(&self) -> &ASTExpression<'a> {
+        &self.body
+    }
+
+    /// Every identifier the parameters declare, in source order.
+    pub fn identifiers(&self) -> impl Iterator<Item = &Token> {
+        self.parameters
+            .iter()
+            .flat_map(|parameter| match parameter {
+                ASTClosureParameter::Binding(parameter) => {
+                    std::slice::from_ref(&parameter.identifier)
+                }
+                ASTClosureParameter::Tuple(items) => items.as_slice(),
+            })
+    }
+}
+
+/// A parameter of a closure.
+#[derive(Debug)]
+pub enum ASTClosureParameter<'a> {
+    /// A single binding, which may be annotated, e.g. `message: Message`.
+    Binding(Box<'a, ASTParameter<'a>>),
+    /// The items of a tuple, e.g. the `(index, message)` pairs of `enumerate()`.
+    Tuple(Vec<Token>),
 }
@@ -0,0 +1,541 @@
+use std::collections::HashMap;
+
+use crate::ast::ASTVisitor;
+use crate::prelude::*;
+
+/// The results of lowering `stateful` bindings and `callback` declarations to React hooks.
+#[derive(Debug, Default)]
+pub struct ReactHooks {
+    /// Setter names of `stateful` bindings, keyed by their `stateful(...)` initializer.
+    stateful: SideTable<String>,
+    /// Setter calls replacing assignments, keyed by the assignment target.
+    setters: SideTable<Setter>,
+    /// `useCallback` dependency lists, keyed by the callback statement.
+    dependencies: SideTable<Vec<String>>,
+}
+
+impl ReactHooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stateful_setter(&self, initializer: NodeId) -> Option<&str> {
+        self.stateful.get(initializer).map(String::as_str)
+    }
+
+    pub fn assignment_setter(&self, target: NodeId) -> Option<&Setter> {
+        self.setters.get(target)
+    }
+
+    pub fn callback_dependencies(&self, callback: NodeId) -> Option<&[String]> {
+        self.dependencies.get(callback).map(Vec::as_slice)
+    }
+}
+
+/// The setter call an assignment to a stateful binding is lowered to.
+#[derive(Debug)]
+pub struct Setter {
+    name: String,
+    /// Whether the new value is computed from the current one, e.g. `count += 1`, so it's
+    /// passed as an update of the latest state rather than of the value the render saw.
+    functional: bool,
+}
+
+impl Setter {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_functional(&self) -> bool {
+        self.functional
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Binding {
+    Value,
+    Stateful {
+        setter: String,
+        /// Whether the setter also takes a function of the current value, as `useState`
+        /// setters do, unlike the change handlers of controllable props.
+        updates: bool,
+    },
+}
+
+enum ScopeKind {
+    Component,
+    Callback {
+        dependencies: Vec<String>,
+    },
+    /// A closure, whose parameters shadow the component's bindings.
+    Closure,
+    /// A loop, whose variable and body bindings shadow the component's until it ends.
+    Loop,
+}
+
+struct Scope {
+    kind: ScopeKind,
+    bindings: HashMap<SymbolId, Binding>,
+}
+
+impl Scope {
+    fn new(kind: ScopeKind) -> Self {
+        Self {
+            kind,
+            bindings: HashMap::new(),
+        }
+    }
+}
+
+/// Uppercases the first character of an identifier, e.g. `value` -> `Value`.
+pub(crate) fn capitalized(identifier: &str) -> String {
+    let mut characters = identifier.chars();
+    match characters.next() {
+        Some(first) => format!("{}{}", first.to_uppercase(), characters.as_str()),
+        None => String::new(),
+    }
+}
+
+/// Derives the prop notified of changes to a controllable prop, e.g. `value` ->
+/// `onValueChanged`, which is also the setter assignments to the prop are lowered to.
+pub(crate) fn change_handler_name(prop: &str) -> String {
+    format!("on{}Changed", capitalized(prop))
+}
+
+/// Derives the prop holding the initial value of an uncontrolled prop, e.g. `value` ->
+/// `defaultValue`.
+pub(crate) fn default_prop_name(prop: &str) -> String {
+    format!("default{}", capitalized(prop))
+}
+
+/// Tracks `stateful` bindings and `callback` declarations within components.
+///
+/// Assignments to stateful bindings are recorded as setter calls, and each callback
+/// records the component values it captures as its `useCallback` dependencies.
+///
+/// Runs after the `SymbolChecker`, relying on the names it resolved.
+pub struct HookLowering<'a> {
+    model: &'a SemanticModel,
+    hooks: ReactHooks,
+    scopes: Vec<Scope>,
+    /// The statement being visited, whose declarations are looked up in the model.
+    statement: Option<NodeId>,
+    /// `useState` bindings whose assignment is being visited, innermost last, along with
+    /// whether the assigned value reads them, which makes it a functional update.
+    assignments: Vec<(SymbolId, bool)>,
+    diagnostics: DiagnosticsBagCell,
+}
+
+impl<'a> HookLowering<'a> {
+    pub fn new(model: &'a SemanticModel, diagnostics: DiagnosticsBagCell) -> Self {
+        Self {
+            model,
+            hooks: ReactHooks::new(),
+            scopes: Vec::new(),
+            statement: None,
+            assignments: Vec::new(),
+            diagnostics,
+        }
+    }
+
+    pub fn hooks(&self) -> &ReactHooks {
+        &self.hooks
+    }
+
+    pub fn into_hooks(self) -> ReactHooks {
+        self.hooks
+    }
+
+    /// Derives the setter name of a stateful binding, e.g. `selected` -> `setSelected`.
+    fn setter_name(identifier: &str) -> String {
+        format!("set{}", capitalized(identifier))
+    }
+
+    /// The statement being visited.
+    fn declaring_statement(&self) -> NodeId {
+        self.statement
+            .expect("declarations are only visited through their statement")
+    }
+
+    /// Declares the symbol `identifier` declared by the node `declaration`.
+    fn declare(&mut self, declaration: NodeId, identifier: &str, binding: Binding) {
+        let symbol = self.model.declared_symbol(declaration, identifier);
+        if let (Some(symbol), Some(scope)) = (symbol, self.scopes.last_mut()) {
+            scope.bindings.insert(symbol, binding);
+        }
+    }
+
+    /// Looks up the binding a variable expression refers to, recording it as a dependency
+    /// of every callback capturing it from outside of the callback.
+    ///
+    /// `dependency` is the name the callback depends on given the binding's name, which for
+    /// assignments to a stateful binding is its setter rather than the binding itself.
+    fn resolve(
+        &mut self,
+        reference: NodeId,
+        dependency: impl Fn(&str, &Binding) -> String,
+    ) -> Option<Binding> {
+        let symbol = *self.model.resolved.get(reference)?;
+        let index = self
+            .scopes
+            .iter()
+            .rposition(|scope| scope.bindings.contains_key(&symbol))?;
+        let binding = self.scopes[index].bindings[&symbol].clone();
+
+        if !matches!(self.scopes[index].kind, ScopeKind::Callback { .. }) {
+            let name = dependency(self.model.symbol(symbol).name(), &binding);
+            for scope in &mut self.scopes[index + 1..] {
+                if let ScopeKind::Callback { dependencies } = &mut scope.kind
+                    && !dependencies.contains(&name)
+                {
+                    dependencies.push(name.clone());
+                }
+            }
+        }
+
+        Some(binding)
+    }
+
+    fn lookup(&self, reference: NodeId) -> Option<&Binding> {
+        let symbol = self.model.resolved.get(reference)?;
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.bindings.get(symbol))
+    }
+
+    fn in_component(&self) -> bool {
+        !self.scopes.is_empty()
+    }
+
+    /// Whether the statements being visited are directly in a component's body, where hooks
+    /// run once per render rather than as often as a loop or callback happens to.
+    fn in_component_body(&self) -> bool {
+        matches!(
+            self.scopes.last(),
+            Some(Scope {
+                kind: ScopeKind::Component,
+                ..
+            })
+        )
+    }
+
+    fn lower_callback(&mut self, id: NodeId, callback: &ASTCallbackStatement) {
+        if !self.in_component() {
+            self.diagnostics
+                .borrow_mut()
+                .report_callback_outside_component(callback.identifier.span);
+        } else if !self.in_component_body() {
+            self.diagnostics
+                .borrow_mut()
+                .report_nested_callback(callback.identifier.span);
+        }
+
+        self.scopes.push(Scope::new(ScopeKind::Callback {
+            dependencies: Vec::new(),
+        }));
+        for parameter in &callback.parameters {
+            self.declare(id, parameter.identifier(), Binding::Value);
+        }
+        for statement in &callback.body {
+            self.visit_statement(statement);
+        }
+
+        if let Some(Scope {
+            kind: ScopeKind::Callback { dependencies },
+            ..
+        }) = self.scopes.pop()
+        {
+            self.hooks.dependencies.insert(id, dependencies);
+        }
+
+        // Declared afterwards, a callback can't list itself as one of its own dependencies.
+        if let (Some(symbol), Some(scope)) = (
+            self.model.declared_symbols(id).first(),
+            self.scopes.last_mut(),
+        ) {
+            scope.bindings.insert(*symbol, Binding::Value);
+        }
+    }
+
+    /// Visits the body of a loop in a scope of its own, declaring its loop variable there.
+    fn visit_loop(&mut self, variable: Option<(NodeId, &str)>, body: &[ASTStatement]) {
+        // Outside of components, there are no bindings to shadow.
+        let shadowing = self.in_component();
+        if shadowing {
+            self.scopes.push(Scope::new(ScopeKind::Loop));
+        }
+        if let Some((declaration, variable)) = variable {
+            self.declare(declaration, variable, Binding::Value);
+        }
+        for statement in body {
+            self.visit_statement(statement);
+        }
+        if shadowing {
+            self.scopes.pop();
+        }
+    }
+}
+
+impl ASTVisitor for HookLowering<'_> {
+    fn visit_statement(&mut self, statement: &ASTStatement) {
+        self.statement = Some(statement.id());
+        match statement.kind() {
+            ASTStatementKind::Callback(callback) => self.lower_callback(statement.id(), callback),
+            _ => self.default_visit_statement(statement),
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &ASTExpression) {
+        match expression.kind() {
+            ASTExpressionKind::Variable(_) => {
+                let symbol = self.model.resolved.get(expression.id());
+                let assigned = self
+                    .assignments
+                    .iter_mut()
+                    .rev()
+                    .find(|(assigned, _)| symbol == Some(assigned));
+                match assigned {
+                    // Within a functional update, the state is the updater's parameter rather
+                    // than something the callback captures.
+                    Some((_, read)) => *read = true,
+                    None => {
+                        self.resolve(expression.id(), |name, _| name.to_string());
+                    }
+                }
+            }
+            _ => self.default_visit_expression(expression),
+        }
+    }
+
+    fn visit_component_statement(&mut self, component: &ASTComponentStatement) {
+        let statement = self.declaring_statement();
+        self.scopes.push(Scope::new(ScopeKind::Component));
+        let controlled = component.controlled_props();
+        for parameter in &component.parameters {
+            let binding = match controlled
+                .iter()
+                .any(|prop| prop.text() == parameter.identifier())
+            {
+                true => Binding::Stateful {
+                    setter: change_handler_name(parameter.identifier()),
+                    updates: false,
+                },
+                false => Binding::Value,
+            };
+            self.declare(statement, parameter.identifier(), binding);
+        }
+        for statement in &component.body {
+            self.visit_statement(statement);
+        }
+        self.scopes.pop();
+    }
+
+    fn visit_for_statement(&mut self, for_statement: &ASTForStatement) {
+        let statement = self.declaring_statement();
+        self.visit_expression(&for_statement.iterable);
+        self.visit_loop(
+            Some((statement, for_statement.identifier())),
+            &for_statement.body,
+        );
+    }
+
+    fn visit_while_statement(&mut self, while_statement: &ASTWhileStatement) {
+        self.visit_expression(&while_statement.condition);
+        self.visit_loop(None, &while_statement.body);
+    }
+
+    fn visit_closure_expression(&mut self, expression: &ASTClosureExpression) {
+        // Outside of components, there are no bindings to shadow.
+        let shadowing = self.in_component();
+        if shadowing {
+            self.scopes.push(Scope::new(ScopeKind::Closure));
+            for identifier in expression.identifiers() {
+                self.declare(expression.body.id(), identifier.text(), Binding::Value);
+            }
+        }
+        self.visit_expression(&expression.body);
+        if shadowing {
+            self.scopes.pop();
+        }
+    }
+
+    fn visit_let_statement(&mut self, let_statement: &ASTLetStatement) {
+        let statement = self.declaring_statement();
+        let (stateful, identifier) = match (
+            let_statement.stateful_initializer(),
+            let_statement.identifier(),
+        ) {
+            (Some(call), Some(identifier))
+                if self.in_component_body() && let_statement.is_const() =>
+            {
+                (call, identifier)
+            }
+            _ => {
+                self.visit_expression(&let_statement.initializer);
+                for identifier in let_statement.pattern.identifiers() {
+                    self.declare(statement, identifier.text(), Binding::Value);
+                }
+                return;
+            }
+        };
+
+        if stateful.arguments.len() > 1 {
+            self.diagnostics
+                .borrow_mut()
+                .report_stateful_arguments(stateful.arguments.len(), identifier.span);
+        }
+        for argument in &stateful.arguments {
+            self.visit_expression(argument);
+        }
+
+        let setter = Self::setter_name(identifier.text());
+        self.hooks
+            .stateful
+            .insert(let_statement.initializer.id(), setter.clone());
+        let binding = Binding::Stateful {
+            setter,
+            updates: true,
+        };
+        self.declare(statement, identifier.text(), binding);
+    }
+
+    fn visit_call_expression(&mut self, expression: &ASTCallExpression) {
+        // Valid `stateful(...)` initialisers never reach this point, see `visit_let_statement`.
+        if let ASTExpressionKind::Variable(callee) = expression.callee.kind()
+            && callee.identifier() == "stateful"
+        {
+            let mut diagnostics = self.diagnostics.borrow_mut();
+            match self.in_component() && !self.in_component_body() {
+                true => diagnostics.report_nested_stateful(callee.identifier.span),
+                false => diagnostics.report_misplaced_stateful(callee.identifier.span),
+            }
+        }
+
+        self.visit_expression(&expression.callee);
+        for argument in &expression.arguments {
+            self.visit_expression(argument);
+        }
+    }
+
+    fn visit_assignment_expression(&mut self, expression: &ASTAssignmentExpression) {
+        let target = expression.target.id();
+        let updated = match self.lookup(target) {
+            Some(Binding::Stateful { updates: true, .. }) => self.model.resolved.get(target),
+            _ => None,
+        };
+        let compound = expression.operator.is_compound();
+        self.assignments
+            .extend(updated.map(|symbol| (*symbol, compound)));
+        self.visit_expression(&expression.value);
+        let functional = updated.is_some() && self.assignments.pop().is_some_and(|(_, read)| read);
+
+        if expression.target_variable().is_none() {
+            self.visit_expression(&expression.target);
+
+            if let Some(root) = expression.root_variable()
+                && let Some(Binding::Stateful { .. }) = self.lookup(root.id())
+                && let Some(symbol) = self.model.resolved_symbol(root.id())
+            {
+                self.diagnostics
+                    .borrow_mut()
+                    .report_stateful_mutation(symbol.name().to_string(), *root.span());
+            }
+            return;
+        }
+
+        // Compound assignments read the current value before replacing it, unless they're
+        // lowered to a function of it.
+        if compound && !functional {
+            self.resolve(target, |name, _| name.to_string());
+        }
+
+        let binding = self.resolve(target, |name, binding| match binding {
+            Binding::Stateful { setter, .. } => setter.clone(),
+            Binding::Value => name.to_string(),
+        });
+
+        if let Some(Binding::Stateful { setter, .. }) = binding {
+            let setter = Setter {
+                name: setter,
+                functional,
+            };
+            self.hooks.setters.insert(target, setter);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::compile;
+
+    #[test]
+    fn passes_updates_reading_the_state_as_functions_of_it() {
+        let compilation = compile(
+            "component Counter(by: number, step: number) {
+    const count = stateful(0)
+    callback increment() {
+        count += by + step;
+        count = count + 1;
+        count = by
+    }
+    return <button onClick={increment}>{count}</button>
+}",
+        );
+        assert_eq!(compilation.diagnostics, Vec::<String>::new());
+        assert_eq!(
+            compilation.tsx.unwrap(),
+            "import { useState, useCallback } from \"react\";
+
+interface CounterProps {
+  by: number;
+  step: number;
+}
+
+function Counter({ by, step }: CounterProps) {
+  const [count, setCount] = useState(0);
+  const increment = useCallback(() => {
+    setCount(count => count + (by + step));
+    setCount(count => count + 1);
+    setCount(by);
+  }, [by, step, setCount]);
+  return (
+    <button onClick={increment}>
+      {count}
+    </button>
+  );
+}
+"
+        );
+    }
+
+    #[test]
+    fn reports_hooks_outside_of_the_component_body() {
+        let compilation = compile(
+            "component List(items: string[]) {
+    for item of items {
+        const selected = stateful(item)
+        callback select() {
+            selected = item
+        }
+    }
+    callback save() {
+        const saving = stateful(0)
+        return saving
+    }
+    return <ul onClick={save}></ul>
+}",
+        );
+        let nested_stateful = "`stateful(...)` can't be used inside a loop or callback, as React \
+                               needs hooks called in the same order on every render";
+        assert_eq!(
+            compilation.diagnostics,
+            vec![
+                nested_stateful,
+                "Callbacks can't be declared inside a loop or callback, as React needs hooks \
+                 called in the same order on every render",
+                nested_stateful,
+            ]
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use crate::prelude::*;
+
+/// An adaptor between the source of an iterator chain and the call consuming it.
+#[derive(Debug)]
+pub enum IteratorStage<'t, 'a> {
+    /// `map(|item| ...)`, replacing each item by the closure's result.
+    Map(&'t ASTClosureExpression<'a>),
+    /// `filter(|item| ...)`, skipping the items the closure rejects.
+    Filter(&'t ASTClosureExpression<'a>),
+    /// `take(n)`, stopping once `n` items went through.
+    Take(&'t ASTExpression<'a>),
+    /// `enumerate()`, pairing each item with its position, e.g. `(0, item)`.
+    Enumerate,
+}
+
+/// The call ending an iterator chain, which runs it.
+#[derive(Debug)]
+pub enum IteratorConsumer<'t, 'a> {
+    /// `sum()`, adding up every item.
+    Sum,
+    /// `collect()`, gathering every item into an array.
+    Collect,
+    /// `find(|item| ...)`, the first item the closure accepts, or `undefined`.
+    Find(&'t ASTClosureExpression<'a>),
+    /// `any(|item| ...)`, whether the closure accepts some item.
+    Any(&'t ASTClosureExpression<'a>),
+    /// `all(|item| ...)`, whether the closure accepts every item.
+    All(&'t ASTClosureExpression<'a>),
+}
+
+/// A chain of iterator adaptors, e.g. `messages.iter().filter(|m| m.unread).take(3).collect()`,
+/// which runs as a single loop over its source rather than one pass per adaptor.
+#[derive(Debug)]
+pub struct IteratorChain<'t, 'a> {
+    /// What `iter()` was called on.
+    pub source: &'t ASTExpression<'a>,
+    /// The adaptors in the order items go through them.
+    pub stages: Vec<IteratorStage<'t, 'a>>,
+    pub consumer: IteratorConsumer<'t, 'a>,
+}
+
+impl<'t, 'a> IteratorChain<'t, 'a> {
+    /// The chain ended by `call`, e.g. the `sum()` of `items.iter().map(|v| v * 2).sum()`.
+    ///
+    /// Returns `None` unless every call up to `iter()` is an adaptor with the arguments it
+    /// expects, closures being written out where they're passed so they can be inlined.
+    pub fn of(call: &'t ASTCallExpression<'a>) -> Option<Self> {
+        let (mut receiver, method) = call.method()?;
+        let consumer = match (method.text(), Self::arguments(call)?) {
+            ("sum", None) => IteratorConsumer::Sum,
+            ("collect", None) => IteratorConsumer::Collect,
+            ("find", Some(argument)) => IteratorConsumer::Find(Self::closure(argument)?),
+            ("any", Some(argument)) => IteratorConsumer::Any(Self::closure(argument)?),
+            ("all", Some(argument)) => IteratorConsumer::All(Self::closure(argument)?),
+            _ => return None,
+        };
+
+        let mut stages = Vec::new();
+        loop {
+            let ASTExpressionKind::Call(call) = receiver.kind() else {
+                return None;
+            };
+            let (object, method) = call.method()?;
+            let stage = match (method.text(), Self::arguments(call)?) {
+                ("iter", None) => break,
+                ("map", Some(argument)) => IteratorStage::Map(Self::closure(argument)?),
+                ("filter", Some(argument)) => IteratorStage::Filter(Self::closure(argument)?),
+                ("take", Some(argument)) => IteratorStage::Take(argument),
+                ("enumerate", None) => IteratorStage::Enumerate,
+                _ => return None,
+            };
+            stages.push(stage);
+            receiver = object;
+        }
+        stages.reverse();
+
+        let ASTExpressionKind::Call(iter) = receiver.kind() else {
+            return None;
+        };
+        Some(Self {
+            source: iter.method()?.0,
+            stages,
+            consumer,
+        })
+    }
+
+    /// The single argument of an adaptor call, `Some(None)` when it has none and `None`
+    /// when it has several, or type arguments.
+    fn arguments(call: &'t ASTCallExpression<'a>) -> Option<Option<&'t ASTExpression<'a>>> {
+        match (call.type_arguments(), call.arguments()) {
+            ([], []) => Some(None),
+            ([], [argument]) => Some(Some(argument)),
+            _ => None,
+        }
+    }
+
+    fn closure(argument: &'t ASTExpression<'a>) -> Option<&'t ASTClosureExpression<'a>> {
+        match argument.kind() {
+            ASTExpressionKind::Closure(closure) => Some(closure),
+            _ => None,
+        }
+    }
+
+    /// Every closure of the chain, in the order items go through them.
+    pub fn closures(&self) -> impl Iterator<Item = &'t ASTClosureExpression<'a>> {
+        let stages = self.stages.iter().filter_map(|stage| match stage {
+            IteratorStage::Map(closure) | IteratorStage::Filter(closure) => Some(*closure),
+            IteratorStage::Take(_) | IteratorStage::Enumerate => None,
+        });
+        stages.chain(self.consumer.closure())
+    }
+}
+
+impl<'t, 'a> IteratorConsumer<'t, 'a> {
+    /// The closure testing items, for the consumers taking one.
+    pub fn closure(&self) -> Option<&'t ASTClosureExpression<'a>> {
+        match self {
+            IteratorConsumer::Find(closure)
+            | IteratorConsumer::Any(closure)
+            | IteratorConsumer::All(closure) => Some(closure),
+            IteratorConsumer::Sum | IteratorConsumer::Collect => None,
+        }
+    }
+}
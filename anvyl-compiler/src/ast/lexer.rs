@@ -1,4 +1,6 @@
+use std::collections::HashMap;
 use std::fmt::Display;
+use std::rc::Rc;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TokenKind {
@@ -7,11 +9,15 @@ pub enum TokenKind {
 
     // Punctuation
     Colon,
+    /// `::`
+    ColonColon,
     Semicolon,
     LeftBrace,
     RightBrace,
     LeftParen,
     RightParen,
+    LeftBracket,
+    RightBracket,
     LeftChevron,
     RightChevron,
     Amperstand,
@@ -27,6 +33,8 @@ pub enum TokenKind {
     SingleQuote,
     DoubleQuote,
     Equals,
+    /// `=>`
+    FatArrow,
 
     // Arithmetic Operators
     Plus,
@@ -34,13 +42,36 @@ pub enum TokenKind {
     Slash,
     Asterisk,
 
+    // Assignment Operators
+    PlusEquals,
+    MinusEquals,
+    AsteriskEquals,
+    SlashEquals,
+
     // Keywords
     Let,
     If,
     Else,
+    Type,
+    Const,
+    Component,
+    Callback,
+    For,
+    Of,
+    In,
+    While,
+    Break,
+    Continue,
+    Return,
+    Impl,
+    Extends,
+    Excludes,
+    Import,
+    Pub,
 
     // Literals
     Number(i64),
+    String(String),
     Identifier,
 
     // Hidden tokens
@@ -53,75 +84,67 @@ pub enum TokenKind {
 }
 
 impl TokenKind {
-    pub fn to_string(&self) -> String {
-        let str = match self {
-            // Error token
-            TokenKind::Bad => "Bad",
-
-            // Punctuation
-            TokenKind::Colon => ":",
-            TokenKind::Semicolon => ";",
-            TokenKind::LeftBrace => "{",
-            TokenKind::RightBrace => "}",
-            TokenKind::LeftParen => "(",
-            TokenKind::RightParen => ")",
-            TokenKind::LeftChevron => "<",
-            TokenKind::RightChevron => ">",
-            TokenKind::Amperstand => "&",
-            TokenKind::Percent => "%",
-            TokenKind::Comma => ",",
-            TokenKind::Period => ".",
-            TokenKind::Hashtag => "#",
-            TokenKind::Exclamation => "!",
-            TokenKind::Question => "?",
-            TokenKind::Tilde => "~",
-            TokenKind::Pipe => "|",
-            TokenKind::Backslash => "\\",
-            TokenKind::SingleQuote => "'",
-            TokenKind::DoubleQuote => "\"",
-            TokenKind::Equals => "=",
-
-            // Arithmetic Operators
-            TokenKind::Plus => "+",
-            TokenKind::Minus => "-",
-            TokenKind::Asterisk => "*",
-            TokenKind::Slash => "/",
-
-            // Keywords
-            TokenKind::Let => "let",
-            TokenKind::If => "if",
-            TokenKind::Else => "else",
-
-            // Literals
-            TokenKind::Number(n) => &n.to_string(),
-            TokenKind::Identifier => "Identifier",
-
-            // Hidden tokens
-            TokenKind::Whitespace => "Whitespace",
-            TokenKind::Newline => "Newline",
-            TokenKind::EOF => "EOF",
-        };
-
-        str.to_string()
-    }
-
     pub fn is_bad(&self) -> bool {
         matches!(self, TokenKind::Bad)
     }
 
     pub fn is_keyword(&self) -> bool {
-        matches!(self, TokenKind::Let | TokenKind::If | TokenKind::Else)
+        matches!(
+            self,
+            TokenKind::Let
+                | TokenKind::If
+                | TokenKind::Else
+                | TokenKind::Type
+                | TokenKind::Const
+                | TokenKind::Component
+                | TokenKind::Callback
+                | TokenKind::For
+                | TokenKind::Of
+                | TokenKind::In
+                | TokenKind::While
+                | TokenKind::Break
+                | TokenKind::Continue
+                | TokenKind::Impl
+                | TokenKind::Extends
+                | TokenKind::Excludes
+                | TokenKind::Import
+                | TokenKind::Pub
+        )
+    }
+
+    /// Whether the keyword begins a statement, which the parser can resynchronise at.
+    pub fn is_statement_keyword(&self) -> bool {
+        matches!(
+            self,
+            TokenKind::Let
+                | TokenKind::If
+                | TokenKind::Type
+                | TokenKind::Const
+                | TokenKind::Component
+                | TokenKind::Callback
+                | TokenKind::For
+                | TokenKind::While
+                | TokenKind::Break
+                | TokenKind::Continue
+                | TokenKind::Return
+                | TokenKind::Impl
+                | TokenKind::Import
+                | TokenKind::Pub
+        )
     }
 
     pub fn is_punctuation(&self) -> bool {
         matches!(
             self,
             TokenKind::Colon
+                | TokenKind::ColonColon
                 | TokenKind::Semicolon
                 | TokenKind::LeftBrace
                 | TokenKind::RightBrace
                 | TokenKind::LeftParen
                 | TokenKind::RightParen
+                | TokenKind::LeftBracket
+                | TokenKind::RightBracket
                 | TokenKind::LeftChevron
                 | TokenKind::RightChevron
                 | TokenKind::Amperstand
@@ -137,6 +160,7 @@ impl TokenKind {
                 | TokenKind::SingleQuote
                 | TokenKind::DoubleQuote
                 | TokenKind::Equals
+                | TokenKind::FatArrow
         )
     }
 
@@ -147,6 +171,17 @@ impl TokenKind {
         )
     }
 
+    pub fn is_assignment_operator(&self) -> bool {
+        matches!(
+            self,
+            TokenKind::Equals
+                | TokenKind::PlusEquals
+                | TokenKind::MinusEquals
+                | TokenKind::AsteriskEquals
+                | TokenKind::SlashEquals
+        )
+    }
+
     pub fn is_whitespace(&self) -> bool {
         matches!(
             self,
@@ -157,7 +192,82 @@ impl TokenKind {
 
 impl Display for TokenKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_string())
+        let str = match self {
+            // Error token
+            TokenKind::Bad => "Bad",
+
+            // Punctuation
+            TokenKind::Colon => ":",
+            TokenKind::ColonColon => "::",
+            TokenKind::Semicolon => ";",
+            TokenKind::LeftBrace => "{",
+            TokenKind::RightBrace => "}",
+            TokenKind::LeftParen => "(",
+            TokenKind::RightParen => ")",
+            TokenKind::LeftBracket => "[",
+            TokenKind::RightBracket => "]",
+            TokenKind::LeftChevron => "<",
+            TokenKind::RightChevron => ">",
+            TokenKind::Amperstand => "&",
+            TokenKind::Percent => "%",
+            TokenKind::Comma => ",",
+            TokenKind::Period => ".",
+            TokenKind::Hashtag => "#",
+            TokenKind::Exclamation => "!",
+            TokenKind::Question => "?",
+            TokenKind::Tilde => "~",
+            TokenKind::Pipe => "|",
+            TokenKind::Backslash => "\\",
+            TokenKind::SingleQuote => "'",
+            TokenKind::DoubleQuote => "\"",
+            TokenKind::Equals => "=",
+            TokenKind::FatArrow => "=>",
+
+            // Arithmetic Operators
+            TokenKind::Plus => "+",
+            TokenKind::Minus => "-",
+            TokenKind::Asterisk => "*",
+            TokenKind::Slash => "/",
+
+            // Assignment Operators
+            TokenKind::PlusEquals => "+=",
+            TokenKind::MinusEquals => "-=",
+            TokenKind::AsteriskEquals => "*=",
+            TokenKind::SlashEquals => "/=",
+
+            // Keywords
+            TokenKind::Let => "let",
+            TokenKind::If => "if",
+            TokenKind::Else => "else",
+            TokenKind::Type => "type",
+            TokenKind::Const => "const",
+            TokenKind::Component => "component",
+            TokenKind::Callback => "callback",
+            TokenKind::For => "for",
+            TokenKind::Of => "of",
+            TokenKind::In => "in",
+            TokenKind::While => "while",
+            TokenKind::Break => "break",
+            TokenKind::Continue => "continue",
+            TokenKind::Return => "return",
+            TokenKind::Impl => "impl",
+            TokenKind::Extends => "extends",
+            TokenKind::Excludes => "excludes",
+            TokenKind::Import => "import",
+            TokenKind::Pub => "pub",
+
+            // Literals
+            TokenKind::Number(n) => return write!(f, "{}", n),
+            TokenKind::String(s) => return write!(f, "\"{}\"", s),
+            TokenKind::Identifier => "Identifier",
+
+            // Hidden tokens
+            TokenKind::Whitespace => "Whitespace",
+            TokenKind::Newline => "Newline",
+            TokenKind::EOF => "EOF",
+        };
+
+        write!(f, "{}", str)
     }
 }
 
@@ -185,6 +295,8 @@ impl From<char> for TokenKind {
             '<' => TokenKind::LeftChevron,
             '(' => TokenKind::LeftParen,
             ')' => TokenKind::RightParen,
+            '[' => TokenKind::LeftBracket,
+            ']' => TokenKind::RightBracket,
             '{' => TokenKind::LeftBrace,
             '}' => TokenKind::RightBrace,
             '\\' => TokenKind::Backslash,
@@ -195,20 +307,16 @@ impl From<char> for TokenKind {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// A range of byte offsets into the source, whose text is read from the `SourceText`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct TextSpan {
     pub(crate) start: usize,
     pub(crate) end: usize,
-    pub(crate) literal: String,
 }
 
 impl TextSpan {
-    pub fn new(start: usize, end: usize, literal: String) -> Self {
-        Self {
-            start,
-            end,
-            literal,
-        }
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
     }
 
     pub fn start(&self) -> usize {
@@ -219,14 +327,6 @@ impl TextSpan {
         self.end
     }
 
-    pub fn get_text(&self) -> &str {
-        &self.literal
-    }
-
-    pub fn clone_text(&self) -> String {
-        self.literal.clone()
-    }
-
     pub fn length(&self) -> usize {
         self.end - self.start
     }
@@ -236,29 +336,45 @@ impl TextSpan {
 pub struct Token {
     pub(crate) kind: TokenKind,
     pub(crate) span: TextSpan,
+    /// The text of the token, shared by the tokens with the same text. Tokens created by
+    /// tree transforms don't take it from the source.
+    pub(crate) text: Rc<str>,
 }
 
 impl Token {
-    pub fn new(kind: TokenKind, span: TextSpan) -> Self {
-        Self { kind, span }
+    pub fn new(kind: TokenKind, span: TextSpan, text: Rc<str>) -> Self {
+        Self { kind, span, text }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
     }
 }
 
 pub struct Lexer<'a> {
     input: &'a str,
+    /// Byte offset of the next character.
     current_pos: usize,
+    /// The text of every distinct token lexed so far, which tokens with the same text share.
+    texts: HashMap<&'a str, Rc<str>>,
 }
 
 impl<'a> Lexer<'a> {
     pub fn new(input: &'a str) -> Self {
+        Self::starting_at(input, 0)
+    }
+
+    /// Lexes `input` from the byte offset `position`, giving tokens spanning `input` as a whole.
+    pub fn starting_at(input: &'a str, position: usize) -> Self {
         Self {
             input,
-            current_pos: 0,
+            current_pos: position,
+            texts: HashMap::new(),
         }
     }
 
     pub fn next_token(&mut self) -> Option<Token> {
-        if self.current_pos >= self.input.len() {
+        if self.current_pos > self.input.len() {
             return None;
         }
 
@@ -266,7 +382,8 @@ impl<'a> Lexer<'a> {
             self.current_pos += 1;
             return Some(Token::new(
                 TokenKind::EOF,
-                TextSpan::new(0, 0, '\0'.to_string()),
+                TextSpan::new(self.input.len(), self.input.len()),
+                Rc::from("\0"),
             ));
         }
 
@@ -275,10 +392,13 @@ impl<'a> Lexer<'a> {
         let kind = self.read_token();
 
         let end = self.current_pos;
-        let literal = self.input[start..end].to_string();
-        let span = TextSpan::new(start, end, literal);
-
-        Some(Token::new(kind, span))
+        let text = &self.input[start..end];
+        let text = self
+            .texts
+            .entry(text)
+            .or_insert_with(|| Rc::from(text))
+            .clone();
+        Some(Token::new(kind, TextSpan::new(start, end), text))
     }
 
     fn read_token(&mut self) -> TokenKind {
@@ -294,6 +414,10 @@ impl<'a> Lexer<'a> {
             return TokenKind::Newline;
         }
 
+        if Lexer::is_quote(c) {
+            return self.consume_string();
+        }
+
         if Lexer::is_whitespace(c) {
             self.consume_whitespace();
             return TokenKind::Whitespace;
@@ -301,10 +425,26 @@ impl<'a> Lexer<'a> {
 
         if Lexer::is_identifier(c) {
             let identifier = self.consume_identifier();
-            return match identifier.as_str() {
+            return match identifier {
                 "let" => TokenKind::Let,
                 "if" => TokenKind::If,
                 "else" => TokenKind::Else,
+                "type" => TokenKind::Type,
+                "const" => TokenKind::Const,
+                "component" => TokenKind::Component,
+                "callback" => TokenKind::Callback,
+                "for" => TokenKind::For,
+                "of" => TokenKind::Of,
+                "in" => TokenKind::In,
+                "while" => TokenKind::While,
+                "break" => TokenKind::Break,
+                "continue" => TokenKind::Continue,
+                "return" => TokenKind::Return,
+                "impl" => TokenKind::Impl,
+                "extends" => TokenKind::Extends,
+                "excludes" => TokenKind::Excludes,
+                "import" => TokenKind::Import,
+                "pub" => TokenKind::Pub,
                 _ => TokenKind::Identifier,
             };
         }
@@ -321,7 +461,7 @@ impl<'a> Lexer<'a> {
     }
 
     fn peek_unchecked(&self) -> char {
-        self.input.chars().nth(self.current_pos).unwrap()
+        self.input[self.current_pos..].chars().next().unwrap()
     }
 
     fn is_whitespace(c: char) -> bool {
@@ -329,25 +469,53 @@ impl<'a> Lexer<'a> {
     }
 
     fn is_digit(c: char) -> bool {
-        c.is_digit(10)
+        c.is_ascii_digit()
+    }
+
+    fn is_quote(c: char) -> bool {
+        c == '\'' || c == '"'
     }
 
     fn is_identifier(c: char) -> bool {
         c.is_alphabetic() || c == '_'
     }
 
-    fn consume_whitespace(&mut self) -> String {
+    fn consume_whitespace(&mut self) -> &'a str {
         self.consume_while(Lexer::is_whitespace)
     }
 
-    fn consume_identifier(&mut self) -> String {
+    fn consume_identifier(&mut self) -> &'a str {
         self.consume_while(Lexer::is_identifier)
     }
 
     fn consume_punctuation(&mut self) -> TokenKind {
         let c = self.consume().unwrap();
 
-        TokenKind::from(c)
+        if c == '=' && self.peek() == Some('>') {
+            self.consume();
+            return TokenKind::FatArrow;
+        }
+
+        if c == ':' && self.peek() == Some(':') {
+            self.consume();
+            return TokenKind::ColonColon;
+        }
+
+        let compound = match c {
+            '+' => Some(TokenKind::PlusEquals),
+            '-' => Some(TokenKind::MinusEquals),
+            '*' => Some(TokenKind::AsteriskEquals),
+            '/' => Some(TokenKind::SlashEquals),
+            _ => None,
+        };
+
+        match compound {
+            Some(kind) if self.peek() == Some('=') => {
+                self.consume();
+                kind
+            }
+            _ => TokenKind::from(c),
+        }
     }
 
     fn consume(&mut self) -> Option<char> {
@@ -356,16 +524,40 @@ impl<'a> Lexer<'a> {
         }
 
         let c = self.peek_unchecked();
-        self.current_pos += 1;
+        self.current_pos += c.len_utf8();
 
         Some(c)
     }
 
+    /// Consumes a single or double quoted string literal, resolving escape sequences.
+    ///
+    /// Returns a `Bad` token when the closing quote is missing.
+    fn consume_string(&mut self) -> TokenKind {
+        let quote = self.consume().unwrap();
+        let mut value = String::new();
+
+        while let Some(c) = self.consume() {
+            match c {
+                '\\' => match self.consume() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some(escaped) => value.push(escaped),
+                    None => break,
+                },
+                '\n' => break,
+                c if c == quote => return TokenKind::String(value),
+                c => value.push(c),
+            }
+        }
+
+        TokenKind::Bad
+    }
+
     fn consume_number(&mut self) -> i64 {
         let mut number: i64 = 0;
 
         while let Some(c) = self.peek() {
-            if c.is_digit(10) {
+            if c.is_ascii_digit() {
                 number = number * 10 + (c.to_digit(10).unwrap() as i64);
                 self.current_pos += 1;
                 continue;
@@ -377,19 +569,58 @@ impl<'a> Lexer<'a> {
         number
     }
 
-    fn consume_while<F>(&mut self, test: F) -> String
+    fn consume_while<F>(&mut self, test: F) -> &'a str
     where
         F: Fn(char) -> bool,
     {
         let start = self.current_pos;
         while let Some(c) = self.peek() {
             if test(c) {
-                self.current_pos += 1;
+                self.current_pos += c.len_utf8();
                 continue;
             }
 
             break;
         }
-        self.input[start..self.current_pos].to_string()
+        &self.input[start..self.current_pos]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spans_bytes_after_non_ascii_characters() {
+        let input = "let greeting = \"héllo 👋\"; let n = 1;";
+        let mut lexer = Lexer::new(input);
+        let mut tokens = Vec::new();
+        while let Some(token) = lexer.next_token() {
+            tokens.push(token);
+        }
+
+        for token in &tokens[..tokens.len() - 1] {
+            assert_eq!(&input[token.span.start..token.span.end], token.text());
+        }
+        let literals: Vec<&str> = tokens
+            .iter()
+            .filter(|token| !matches!(token.kind, TokenKind::Whitespace | TokenKind::EOF))
+            .map(|token| token.text())
+            .collect();
+        assert_eq!(
+            literals,
+            [
+                "let",
+                "greeting",
+                "=",
+                "\"héllo 👋\"",
+                ";",
+                "let",
+                "n",
+                "=",
+                "1",
+                ";"
+            ]
+        );
     }
 }
@@ -2,36 +2,56 @@ use crate::prelude::*;
 
 pub mod evaluator;
 pub mod expressions;
+pub mod hooks;
+pub mod iterators;
 pub mod lexer;
 pub mod parser;
 pub mod printer;
+pub mod semantics;
 pub mod statements;
 pub mod symbols;
 pub mod syntax;
+pub mod transform;
+pub mod typechecker;
+pub mod types;
+pub mod visibility;
 
-pub struct AST {
-    pub statements: Vec<ASTStatement>,
+/// The arena the nodes of a tree are allocated in, which must outlive the tree.
+pub type ASTArena = bumpalo::Bump;
+
+pub struct AST<'a> {
+    pub statements: Vec<ASTStatement<'a>>,
+}
+
+impl Default for AST<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-impl AST {
+impl<'a> AST<'a> {
     pub fn new() -> Self {
         Self {
             statements: Vec::new(),
         }
     }
 
-    pub fn add_statement(&mut self, statement: ASTStatement) {
+    pub fn add_statement(&mut self, statement: ASTStatement<'a>) {
         self.statements.push(statement);
     }
 
-    pub fn visit(&mut self, visitor: &mut dyn ASTVisitor) {
+    pub fn visit(&self, visitor: &mut dyn ASTVisitor) {
         for statement in &self.statements {
             visitor.visit_statement(statement);
         }
     }
 
-    pub fn visualize(&mut self) {
-        let mut printer = ASTPrinter::new();
+    pub fn visit_mut(&mut self, visitor: &mut dyn ASTVisitorMut<'a>) {
+        visitor.visit_block(&mut self.statements);
+    }
+
+    pub fn visualize(&self, source: &SourceText) {
+        let mut printer = ASTPrinter::new(source);
         self.visit(&mut printer);
         println!("{}", printer.result());
     }
@@ -42,6 +62,21 @@ pub trait ASTVisitor {
         match statement.kind() {
             ASTStatementKind::Expression(expr) => self.visit_expression(expr),
             ASTStatementKind::LetStatement(let_stmt) => self.visit_let_statement(let_stmt),
+            ASTStatementKind::TypeAlias(type_alias) => self.visit_type_alias_statement(type_alias),
+            ASTStatementKind::Component(component) => self.visit_component_statement(component),
+            ASTStatementKind::Callback(callback) => self.visit_callback_statement(callback),
+            ASTStatementKind::For(for_statement) => self.visit_for_statement(for_statement),
+            ASTStatementKind::While(while_statement) => self.visit_while_statement(while_statement),
+            ASTStatementKind::Break(break_statement) => self.visit_break_statement(break_statement),
+            ASTStatementKind::Continue(continue_statement) => {
+                self.visit_continue_statement(continue_statement)
+            }
+            ASTStatementKind::Return(return_statement) => {
+                self.visit_return_statement(return_statement)
+            }
+            ASTStatementKind::Impl(impl_statement) => self.visit_impl_statement(impl_statement),
+            ASTStatementKind::Import(import) => self.visit_import_statement(import),
+            ASTStatementKind::Style(style) => self.visit_style_statement(style),
         }
     }
 
@@ -53,11 +88,20 @@ pub trait ASTVisitor {
         match expression.kind() {
             ASTExpressionKind::Error(span) => self.visit_error_expression(span),
             ASTExpressionKind::Number(expr) => self.visit_number_expression(expr),
+            ASTExpressionKind::String(expr) => self.visit_string_expression(expr),
+            ASTExpressionKind::Array(expr) => self.visit_array_expression(expr),
             ASTExpressionKind::Binary(expr) => self.visit_binary_expression(expr),
             ASTExpressionKind::Variable(expr) => self.visit_variable_expression(expr),
             ASTExpressionKind::Parenthesized(parenthesized) => {
                 self.visit_parenthesized_expression(parenthesized)
             }
+            ASTExpressionKind::Call(expr) => self.visit_call_expression(expr),
+            ASTExpressionKind::Member(expr) => self.visit_member_expression(expr),
+            ASTExpressionKind::Index(expr) => self.visit_index_expression(expr),
+            ASTExpressionKind::Assignment(expr) => self.visit_assignment_expression(expr),
+            ASTExpressionKind::Element(expr) => self.visit_element_expression(expr),
+            ASTExpressionKind::Text(expr) => self.visit_text_expression(expr),
+            ASTExpressionKind::Closure(expr) => self.visit_closure_expression(expr),
         }
     }
 
@@ -65,14 +109,118 @@ pub trait ASTVisitor {
         self.default_visit_expression(expression);
     }
 
+    fn default_visit_type(&mut self, ty: &ASTType) {
+        match ty.kind() {
+            ASTTypeKind::Error(span) => self.visit_error_type(span),
+            ASTTypeKind::Named(identifier) => self.visit_named_type(identifier),
+            ASTTypeKind::Literal(literal) => self.visit_literal_type(literal),
+            ASTTypeKind::Union(union) => self.visit_union_type(union),
+            ASTTypeKind::Optional(optional) => self.visit_optional_type(optional),
+            ASTTypeKind::Array(array) => self.visit_array_type(array),
+            ASTTypeKind::Function(function) => self.visit_function_type(function),
+            ASTTypeKind::Generic(generic) => self.visit_generic_type(generic),
+            ASTTypeKind::Trusted(trusted) => self.visit_trusted_type(trusted),
+            ASTTypeKind::Object(object) => self.visit_object_type(object),
+        }
+    }
+
+    fn visit_type(&mut self, ty: &ASTType) {
+        self.default_visit_type(ty);
+    }
+
     fn visit_variable_expression(&mut self, _expression: &ASTVariableExpression) {}
 
     fn visit_let_statement(&mut self, _let_statement: &ASTLetStatement) {}
 
+    fn visit_type_alias_statement(&mut self, type_alias: &ASTTypeAliasStatement) {
+        self.visit_type(&type_alias.ty);
+    }
+
+    fn visit_component_statement(&mut self, component: &ASTComponentStatement) {
+        for generic in &component.generics {
+            self.visit_generic_parameter(generic);
+        }
+        for parameter in &component.parameters {
+            self.visit_parameter(parameter);
+        }
+        for statement in &component.body {
+            self.visit_statement(statement);
+        }
+    }
+
+    fn visit_callback_statement(&mut self, callback: &ASTCallbackStatement) {
+        for parameter in &callback.parameters {
+            self.visit_parameter(parameter);
+        }
+        for statement in &callback.body {
+            self.visit_statement(statement);
+        }
+    }
+
+    fn visit_for_statement(&mut self, for_statement: &ASTForStatement) {
+        self.visit_expression(&for_statement.iterable);
+        for statement in &for_statement.body {
+            self.visit_statement(statement);
+        }
+    }
+
+    fn visit_while_statement(&mut self, while_statement: &ASTWhileStatement) {
+        self.visit_expression(&while_statement.condition);
+        for statement in &while_statement.body {
+            self.visit_statement(statement);
+        }
+    }
+
+    fn visit_break_statement(&mut self, _break_statement: &ASTBreakStatement) {}
+
+    fn visit_continue_statement(&mut self, _continue_statement: &ASTContinueStatement) {}
+
+    fn visit_return_statement(&mut self, return_statement: &ASTReturnStatement) {
+        if let Some(value) = &return_statement.value {
+            self.visit_expression(value);
+        }
+    }
+
+    fn visit_impl_statement(&mut self, impl_statement: &ASTImplStatement) {
+        for generic in &impl_statement.generics {
+            self.visit_generic_parameter(generic);
+        }
+        for member in &impl_statement.members {
+            self.visit_type(&member.ty);
+        }
+    }
+
+    fn visit_import_statement(&mut self, _import: &ASTImportStatement) {}
+
+    fn visit_style_statement(&mut self, _style: &ASTStyleStatement) {}
+
+    fn visit_generic_parameter(&mut self, generic: &ASTGenericParameter) {
+        if let Some(constraint) = &generic.constraint {
+            self.visit_type(&constraint.ty);
+        }
+    }
+
+    fn visit_parameter(&mut self, parameter: &ASTParameter) {
+        if let Some(ty) = &parameter.ty {
+            self.visit_type(ty);
+        }
+        if let Some(default) = &parameter.default {
+            self.visit_expression(default);
+        }
+    }
+
     fn visit_error_expression(&mut self, _span: &TextSpan) {}
 
     fn visit_number_expression(&mut self, _expression: &ASTNumberExpression) {}
 
+    fn visit_string_expression(&mut self, _expression: &ASTStringExpression) {}
+
+    fn visit_array_expression(&mut self, expression: &ASTArrayExpression) {
+        for element in &expression.elements {
+            self.visit_expression(element);
+        }
+    }
+
     fn visit_binary_expression(&mut self, expression: &ASTBinaryExpression) {
         self.visit_expression(&expression.left);
         self.visit_expression(&expression.right);
@@ -81,4 +229,91 @@ pub trait ASTVisitor {
     fn visit_parenthesized_expression(&mut self, expression: &ASTParenthesizedExpression) {
         self.visit_expression(&expression.inner);
     }
+
+    fn visit_call_expression(&mut self, expression: &ASTCallExpression) {
+        self.visit_expression(&expression.callee);
+        for ty in &expression.type_arguments {
+            self.visit_type(ty);
+        }
+        for argument in &expression.arguments {
+            self.visit_expression(argument);
+        }
+    }
+
+    fn visit_member_expression(&mut self, expression: &ASTMemberExpression) {
+        self.visit_expression(&expression.object);
+    }
+
+    fn visit_index_expression(&mut self, expression: &ASTIndexExpression) {
+        self.visit_expression(&expression.object);
+        self.visit_expression(&expression.index);
+    }
+
+    fn visit_assignment_expression(&mut self, expression: &ASTAssignmentExpression) {
+        self.visit_expression(&expression.target);
+        self.visit_expression(&expression.value);
+    }
+
+    fn visit_element_expression(&mut self, expression: &ASTElementExpression) {
+        for attribute in &expression.attributes {
+            self.visit_expression(&attribute.value);
+        }
+        for child in expression.children.iter().flatten() {
+            self.visit_expression(child);
+        }
+    }
+
+    fn visit_text_expression(&mut self, _expression: &ASTTextExpression) {}
+
+    fn visit_closure_expression(&mut self, expression: &ASTClosureExpression) {
+        for parameter in &expression.parameters {
+            if let ASTClosureParameter::Binding(parameter) = parameter {
+                self.visit_parameter(parameter);
+            }
+        }
+        self.visit_expression(&expression.body);
+    }
+
+    fn visit_error_type(&mut self, _span: &TextSpan) {}
+
+    fn visit_named_type(&mut self, _identifier: &Token) {}
+
+    fn visit_literal_type(&mut self, _literal: &Token) {}
+
+    fn visit_union_type(&mut self, union: &ASTUnionType) {
+        for member in union.members() {
+            self.visit_type(member);
+        }
+    }
+
+    fn visit_optional_type(&mut self, optional: &ASTOptionalType) {
+        self.visit_type(&optional.inner);
+    }
+
+    fn visit_array_type(&mut self, array: &ASTArrayType) {
+        self.visit_type(&array.element);
+    }
+
+    fn visit_function_type(&mut self, function: &ASTFunctionType) {
+        for parameter in &function.parameters {
+            self.visit_parameter(parameter);
+        }
+        self.visit_type(&function.return_type);
+    }
+
+    fn visit_generic_type(&mut self, generic: &ASTGenericType) {
+        for argument in &generic.arguments {
+            self.visit_type(argument);
+        }
+    }
+
+    fn visit_trusted_type(&mut self, trusted: &ASTTrustedType) {
+        self.visit_type(&trusted.inner);
+    }
+
+    fn visit_object_type(&mut self, object: &ASTObjectType) {
+        for property in &object.properties {
+            self.visit_type(&property.ty);
+        }
+    }
 }
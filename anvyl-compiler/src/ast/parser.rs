@@ -1,4 +1,7 @@
 use std::cell::Cell;
+use std::rc::Rc;
+
+use bumpalo::boxed::Box;
 
 use crate::prelude::*;
 
@@ -6,6 +9,12 @@ pub struct Counter {
     value: Cell<usize>,
 }
 
+impl Default for Counter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Counter {
     pub fn new() -> Self {
         Self {
@@ -24,33 +33,114 @@ impl Counter {
     }
 }
 
-pub struct Parser {
+pub struct Parser<'a> {
     tokens: Vec<Token>,
+    /// The arena the nodes are allocated in.
+    arena: &'a ASTArena,
+    /// The source text, which text within elements and the CSS of styles is read from.
+    source: SourceText,
+    /// Whether each token is the first on its line.
+    line_starts: Vec<bool>,
     current: Counter,
+    ids: Counter,
+    /// Set once a syntax error is reported, suppressing further errors until the parser
+    /// synchronises at the next statement boundary.
+    panicking: Cell<bool>,
     diagnostics_bag: DiagnosticsBagCell,
 }
 
-impl Parser {
-    pub fn new(tokens: Vec<Token>, diagnostics_bag: DiagnosticsBagCell) -> Self {
+impl<'a> Parser<'a> {
+    pub fn new(
+        tokens: Vec<Token>,
+        source: &SourceText,
+        arena: &'a ASTArena,
+        diagnostics_bag: DiagnosticsBagCell,
+    ) -> Self {
+        let (tokens, line_starts) = Self::significant(tokens, true);
+
         Self {
-            tokens: tokens
-                .iter()
-                .filter(|t| t.kind != TokenKind::Whitespace)
-                .cloned()
-                .collect(),
+            tokens,
+            arena,
+            source: source.clone(),
+            line_starts,
             current: Counter::new(),
+            ids: Counter::new(),
+            panicking: Cell::new(false),
             diagnostics_bag,
         }
     }
 
-    fn peek(&self, offset: isize) -> &Token {
-        let mut index = (self.current.get_value() as isize + offset) as usize;
+    /// Drops the whitespace and newlines between `tokens`, along with whether each of the
+    /// tokens left is the first on its line.
+    fn significant(mut tokens: Vec<Token>, mut line_start: bool) -> (Vec<Token>, Vec<bool>) {
+        let mut line_starts = Vec::with_capacity(tokens.len());
+        tokens.retain(|token| match token.kind {
+            TokenKind::Newline => {
+                line_start = true;
+                false
+            }
+            TokenKind::Whitespace => false,
+            _ => {
+                line_starts.push(line_start);
+                line_start = false;
+                true
+            }
+        });
+        (tokens, line_starts)
+    }
 
-        if index >= self.tokens.len() {
-            index = self.tokens.len() - 1;
+    /// Replaces the tokens from the current one on by lexing the source again from the
+    /// `position` within the current token.
+    fn relex(&mut self, position: usize) {
+        let mut lexer = Lexer::starting_at(self.source.as_str(), position);
+        let mut tokens = Vec::new();
+        while let Some(token) = lexer.next_token() {
+            tokens.push(token);
         }
+        let line_start = self.source.as_str()[..position]
+            .trim_end_matches([' ', '\t'])
+            .ends_with('\n');
+        let (tokens, line_starts) = Self::significant(tokens, line_start);
 
-        self.tokens.get(index).unwrap()
+        let index = self.index(0);
+        self.tokens.truncate(index);
+        self.tokens.extend(tokens);
+        self.line_starts.truncate(index);
+        self.line_starts.extend(line_starts);
+    }
+
+    fn index(&self, offset: isize) -> usize {
+        let index = (self.current.get_value() as isize + offset) as usize;
+        index.min(self.tokens.len() - 1)
+    }
+
+    fn peek(&self, offset: isize) -> &Token {
+        self.tokens.get(self.index(offset)).unwrap()
+    }
+
+    fn next_id(&self) -> NodeId {
+        NodeId::new(self.ids.increment())
+    }
+
+    /// Spans the source from the `start` position up to the end of the last consumed token.
+    fn span_from(&self, start: usize) -> TextSpan {
+        let end = match self.current.get_value() {
+            0 => start,
+            _ => self.peek(-1).span.end.max(start),
+        };
+        TextSpan::new(start, end)
+    }
+
+    fn is_line_start(&self) -> bool {
+        self.line_starts[self.index(0)]
+    }
+
+    /// Reports a syntax error, unless one was already reported since the parser last
+    /// synchronised, in which case it is most likely a consequence of the first.
+    fn report(&self, report: impl FnOnce(&mut DiagnosticsBag)) {
+        if !self.panicking.replace(true) {
+            report(&mut self.diagnostics_bag.borrow_mut());
+        }
     }
 
     fn current(&self) -> &Token {
@@ -62,21 +152,105 @@ impl Parser {
         self.peek(-1)
     }
 
+    /// Consumes the current token if it is of the `expected` kind.
+    ///
+    /// Otherwise the error is reported and the token is left in place, as if the expected
+    /// token had been inserted before it.
     fn consume_and_expect(&self, expected: TokenKind) -> &Token {
-        let token = self.consume();
+        let token = self.current();
         if token.kind != expected {
-            self.diagnostics_bag
-                .borrow_mut()
-                .report_unexpected_token(&expected, token);
+            self.report(|diagnostics| diagnostics.report_unexpected_token(&expected, token));
+            return token;
+        }
+        self.consume()
+    }
+
+    /// Consumes the separator between list items, returning whether the list continues.
+    fn parse_separator(&self, separator: TokenKind, closing: TokenKind) -> bool {
+        match &self.current().kind {
+            kind if *kind == closing => true,
+            kind if *kind == separator => {
+                self.consume();
+                true
+            }
+            _ => {
+                self.consume_and_expect(separator);
+                false
+            }
+        }
+    }
+
+    /// Ends a statement at a `;`, or inserts one automatically before a line break, a `}`
+    /// or the end of the file.
+    fn parse_statement_terminator(&self) {
+        match self.current().kind {
+            TokenKind::Semicolon => {
+                self.consume();
+            }
+            TokenKind::RightBrace | TokenKind::EOF => {}
+            _ if self.is_line_start() => {}
+            // The next statement starts on the same line, e.g. `let a = 1 let b = 2`, so the
+            // parser is still in sync once the missing `;` is reported.
+            ref kind if kind.is_statement_keyword() && !self.panicking.get() => {
+                self.consume_and_expect(TokenKind::Semicolon);
+                self.panicking.set(false);
+            }
+            _ => {
+                self.consume_and_expect(TokenKind::Semicolon);
+            }
+        }
+    }
+
+    /// Skips tokens until the next statement boundary: after a `;`, before a `}`, or
+    /// before a statement keyword starting a new line.
+    fn synchronize(&self) {
+        self.panicking.set(false);
+        if matches!(
+            self.peek(-1).kind,
+            TokenKind::Semicolon | TokenKind::RightBrace
+        ) {
+            return;
+        }
+
+        while !self.is_at_end() {
+            match &self.current().kind {
+                TokenKind::Semicolon => {
+                    self.consume();
+                    return;
+                }
+                TokenKind::RightBrace => return,
+                kind if kind.is_statement_keyword() && self.is_line_start() => return,
+                _ => {
+                    self.consume();
+                }
+            }
         }
-        token
+    }
+
+    /// Whether a token ends the enclosing statement or list, and so shouldn't be consumed
+    /// when it appears where an expression or type was expected.
+    fn is_closing_token(kind: &TokenKind) -> bool {
+        kind.is_statement_keyword()
+            || matches!(
+                kind,
+                TokenKind::Semicolon
+                    | TokenKind::RightBrace
+                    | TokenKind::RightParen
+                    | TokenKind::RightBracket
+                    | TokenKind::EOF
+            )
     }
 
     fn is_at_end(&self) -> bool {
-        self.current.get_value() >= self.tokens.len()
+        self.current().kind == TokenKind::EOF
+    }
+
+    /// The first `NodeId` not handed out yet, for nodes synthesised after parsing.
+    pub fn next_node_id(&self) -> NodeId {
+        NodeId::new(self.ids.get_value())
     }
 
-    pub fn next_statement(&mut self) -> Option<ASTStatement> {
+    pub fn next_statement(&mut self) -> Option<ASTStatement<'a>> {
         if self.is_at_end() {
             return None;
         }
@@ -85,57 +259,1059 @@ impl Parser {
     }
 }
 
-impl Parser {
-    fn parse_statement(&mut self) -> ASTStatement {
+impl<'a> Parser<'a> {
+    fn parse_statement(&mut self) -> ASTStatement<'a> {
+        let start = self.current.get_value();
+        let statement = self.parse_statement_kind();
+
+        if self.panicking.get() {
+            self.synchronize();
+        }
+        // A statement which failed without consuming anything would be parsed again forever.
+        if self.current.get_value() == start {
+            self.consume();
+        }
+
+        statement
+    }
+
+    fn parse_statement_kind(&mut self) -> ASTStatement<'a> {
         match self.current().kind {
-            TokenKind::Let => self.parse_let_statement(),
+            TokenKind::Let | TokenKind::Const => self.parse_let_statement(),
+            TokenKind::Type => self.parse_type_alias_statement(),
+            TokenKind::Component => self.parse_component_statement(),
+            TokenKind::Callback => self.parse_callback_statement(),
+            TokenKind::Impl => self.parse_impl_statement(),
+            TokenKind::For => self.parse_for_statement(None),
+            TokenKind::While => self.parse_while_statement(None),
+            TokenKind::Break => self.parse_break_statement(),
+            TokenKind::Continue => self.parse_continue_statement(),
+            TokenKind::Return => self.parse_return_statement(),
+            TokenKind::Import => self.parse_import_statement(),
+            TokenKind::Pub => match self.peek(self.skip_visibility(0)).kind {
+                TokenKind::Let | TokenKind::Const => self.parse_let_statement(),
+                TokenKind::Type => self.parse_type_alias_statement(),
+                TokenKind::Component => self.parse_component_statement(),
+                _ => {
+                    let keyword = self.current().clone();
+                    self.parse_visibility();
+                    self.diagnostics_bag
+                        .borrow_mut()
+                        .report_invalid_visibility_target(&keyword);
+                    self.parse_statement()
+                }
+            },
+            TokenKind::Identifier if self.is_modifier() => self.parse_component_statement(),
+            TokenKind::Identifier
+                if self.current().text() == "style"
+                    && self.peek(1).kind == TokenKind::LeftBrace =>
+            {
+                self.parse_style_statement()
+            }
+            TokenKind::Identifier if self.peek(1).kind == TokenKind::Colon => {
+                self.parse_labelled_statement()
+            }
             _ => self.parse_expression_statement(),
         }
     }
 
-    fn parse_let_statement(&mut self) -> ASTStatement {
-        self.consume_and_expect(TokenKind::Let);
+    fn parse_labelled_statement(&mut self) -> ASTStatement<'a> {
+        let label = self.consume().clone();
+        self.consume_and_expect(TokenKind::Colon);
+
+        match self.current().kind {
+            TokenKind::For => self.parse_for_statement(Some(label)),
+            TokenKind::While => self.parse_while_statement(Some(label)),
+            _ => {
+                self.diagnostics_bag
+                    .borrow_mut()
+                    .report_misplaced_label(&label);
+
+                self.parse_statement()
+            }
+        }
+    }
+
+    fn parse_for_statement(&mut self, label: Option<Token>) -> ASTStatement<'a> {
+        let start = match &label {
+            Some(label) => label.span.start,
+            None => self.current().span.start,
+        };
+        self.consume_and_expect(TokenKind::For);
+
+        let parenthesized = self.current().kind == TokenKind::LeftParen;
+        if parenthesized {
+            self.consume();
+        }
+
+        let keyword = match self.current().kind {
+            TokenKind::Const | TokenKind::Let => Some(self.consume().clone()),
+            _ => None,
+        };
         let identifier = self.consume_and_expect(TokenKind::Identifier).clone();
+
+        let kind = match self.current().kind {
+            TokenKind::In => {
+                self.consume();
+                ASTForKind::In
+            }
+            _ => {
+                self.consume_and_expect(TokenKind::Of);
+                ASTForKind::Of
+            }
+        };
+
+        let iterable = self.parse_expression();
+        if parenthesized {
+            self.consume_and_expect(TokenKind::RightParen);
+        }
+        let body = self.parse_block();
+
+        ASTStatement::for_statement(
+            ASTForStatement::new(label, keyword, identifier, kind, iterable, body),
+            self.next_id(),
+            self.span_from(start),
+            self.arena,
+        )
+    }
+
+    fn parse_while_statement(&mut self, label: Option<Token>) -> ASTStatement<'a> {
+        let start = match &label {
+            Some(label) => label.span.start,
+            None => self.current().span.start,
+        };
+        self.consume_and_expect(TokenKind::While);
+        let condition = self.parse_expression();
+        let body = self.parse_block();
+
+        ASTStatement::while_statement(
+            label,
+            condition,
+            body,
+            self.next_id(),
+            self.span_from(start),
+            self.arena,
+        )
+    }
+
+    fn parse_break_statement(&mut self) -> ASTStatement<'a> {
+        let start = self.current().span.start;
+        let keyword = self.consume_and_expect(TokenKind::Break).clone();
+        let label = self.parse_optional_label();
+        self.parse_statement_terminator();
+
+        ASTStatement::break_statement(
+            keyword,
+            label,
+            self.next_id(),
+            self.span_from(start),
+            self.arena,
+        )
+    }
+
+    fn parse_continue_statement(&mut self) -> ASTStatement<'a> {
+        let start = self.current().span.start;
+        let keyword = self.consume_and_expect(TokenKind::Continue).clone();
+        let label = self.parse_optional_label();
+        self.parse_statement_terminator();
+
+        ASTStatement::continue_statement(
+            keyword,
+            label,
+            self.next_id(),
+            self.span_from(start),
+            self.arena,
+        )
+    }
+
+    fn parse_return_statement(&mut self) -> ASTStatement<'a> {
+        let start = self.current().span.start;
+        let keyword = self.consume_and_expect(TokenKind::Return).clone();
+        // Like JavaScript, a value on the next line isn't returned.
+        let value = match self.current().kind {
+            TokenKind::Semicolon | TokenKind::RightBrace | TokenKind::EOF => None,
+            _ if self.is_line_start() => None,
+            _ => Some(self.parse_expression()),
+        };
+        self.parse_statement_terminator();
+
+        ASTStatement::return_statement(
+            keyword,
+            value,
+            self.next_id(),
+            self.span_from(start),
+            self.arena,
+        )
+    }
+
+    /// Parses `import <module>::<item>`, `import <module>::{<items>}` or `import <module>::*`,
+    /// where the module path may have several `::` separated segments.
+    /// Parses a `style { ... }` block, whose contents are CSS rather than Anvyl, so they are
+    /// kept as written rather than parsed.
+    fn parse_style_statement(&mut self) -> ASTStatement<'a> {
+        let start = self.current().span.start;
+        let keyword = self.consume().clone();
+        let css_start = self.consume_and_expect(TokenKind::LeftBrace).span.end;
+
+        let mut depth = 0;
+        loop {
+            match self.current().kind {
+                TokenKind::LeftBrace => depth += 1,
+                TokenKind::RightBrace if depth == 0 => break,
+                TokenKind::RightBrace => depth -= 1,
+                TokenKind::EOF => break,
+                _ => {}
+            }
+            self.consume();
+        }
+        let css_end = self.current().span.start.max(css_start);
+        let css = TextSpan::new(css_start, css_end);
+        let css = self.source.slice(css).to_string();
+        self.consume_and_expect(TokenKind::RightBrace);
+
+        ASTStatement::style(
+            keyword,
+            css,
+            self.next_id(),
+            self.span_from(start),
+            self.arena,
+        )
+    }
+
+    fn parse_import_statement(&mut self) -> ASTStatement<'a> {
+        let start = self.current().span.start;
+        let keyword = self.consume_and_expect(TokenKind::Import).clone();
+
+        let mut path = vec![self.consume_and_expect(TokenKind::Identifier).clone()];
+        let mut items = None;
+        while items.is_none() && self.current().kind == TokenKind::ColonColon {
+            self.consume();
+            match self.current().kind {
+                TokenKind::Asterisk => items = Some(ASTImportItems::Glob(self.consume().clone())),
+                TokenKind::LeftBrace => {
+                    items = Some(ASTImportItems::Named(self.parse_import_list()))
+                }
+                _ => path.push(self.consume_and_expect(TokenKind::Identifier).clone()),
+            }
+        }
+        // Without a `*` or `{ ... }`, the last segment is the item being imported.
+        let items =
+            items.unwrap_or_else(|| ASTImportItems::Named(path.pop().into_iter().collect()));
+        self.parse_statement_terminator();
+
+        ASTStatement::import_statement(
+            keyword,
+            path,
+            items,
+            self.next_id(),
+            self.span_from(start),
+            self.arena,
+        )
+    }
+
+    /// Parses the `{ A, B }` list of items in an import.
+    fn parse_import_list(&mut self) -> Vec<Token> {
+        self.consume_and_expect(TokenKind::LeftBrace);
+
+        let mut items = Vec::new();
+        while self.current().kind != TokenKind::RightBrace && !self.is_at_end() {
+            items.push(self.consume_and_expect(TokenKind::Identifier).clone());
+
+            if !self.parse_separator(TokenKind::Comma, TokenKind::RightBrace) {
+                break;
+            }
+        }
+        self.consume_and_expect(TokenKind::RightBrace);
+        items
+    }
+
+    /// Parses the label of a `break` or `continue`, which must be on the same line.
+    fn parse_optional_label(&mut self) -> Option<Token> {
+        match self.current().kind {
+            TokenKind::Identifier if !self.is_line_start() => Some(self.consume().clone()),
+            _ => None,
+        }
+    }
+
+    fn parse_let_statement(&mut self) -> ASTStatement<'a> {
+        let start = self.current().span.start;
+        let visibility = self.parse_visibility();
+        let keyword = self.consume().clone();
+        let pattern = self.parse_pattern();
+        let type_annotation = self.parse_optional_type_annotation();
         self.consume_and_expect(TokenKind::Equals);
         let initializer = self.parse_expression();
-        self.consume_and_expect(TokenKind::Semicolon);
+        self.parse_statement_terminator();
+
+        let let_statement =
+            ASTLetStatement::new(visibility, keyword, pattern, type_annotation, initializer);
+        ASTStatement::let_statement(
+            let_statement,
+            self.next_id(),
+            self.span_from(start),
+            self.arena,
+        )
+    }
+
+    /// Parses the bindings of a declaration: an identifier, or `{ a, b }` destructuring.
+    fn parse_pattern(&mut self) -> ASTPattern {
+        if self.current().kind != TokenKind::LeftBrace {
+            let identifier = self.consume_and_expect(TokenKind::Identifier).clone();
+            return ASTPattern::Identifier(identifier);
+        }
+        self.consume();
+
+        let mut properties = Vec::new();
+        while self.current().kind != TokenKind::RightBrace && !self.is_at_end() {
+            properties.push(self.consume_and_expect(TokenKind::Identifier).clone());
+
+            if !self.parse_separator(TokenKind::Comma, TokenKind::RightBrace) {
+                break;
+            }
+        }
+        self.consume_and_expect(TokenKind::RightBrace);
+
+        ASTPattern::Object(properties)
+    }
+
+    fn parse_component_statement(&mut self) -> ASTStatement<'a> {
+        let start = self.current().span.start;
+        let modifiers = self.parse_modifiers();
+        let visibility = self.parse_visibility();
+        self.consume_and_expect(TokenKind::Component);
+        let identifier = self.consume_and_expect(TokenKind::Identifier).clone();
+        let generics = self.parse_generic_parameters();
+        let parameters = self.parse_parameters();
+        let body = self.parse_block();
+
+        ASTStatement::component(
+            ASTComponentStatement::new(
+                modifiers, visibility, identifier, generics, parameters, body,
+            ),
+            self.next_id(),
+            self.span_from(start),
+            self.arena,
+        )
+    }
+
+    /// Whether the current token starts a list of modifiers, e.g.
+    /// `rename_props(ChatViewArguments)`, applied to the component declared after it.
+    fn is_modifier(&self) -> bool {
+        let mut offset = 0;
+        while self.peek(offset).kind == TokenKind::Identifier
+            && self.peek(offset + 1).kind == TokenKind::LeftParen
+        {
+            offset += 1;
+            let mut depth = 0;
+            loop {
+                match self.peek(offset).kind {
+                    TokenKind::LeftParen => depth += 1,
+                    TokenKind::RightParen => {
+                        depth -= 1;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                    TokenKind::EOF => return false,
+                    _ => {}
+                }
+                offset += 1;
+            }
+            offset += 1;
+        }
+        offset > 0 && self.peek(self.skip_visibility(offset)).kind == TokenKind::Component
+    }
+
+    /// The offset of the token after the visibility at `offset`, e.g. `pub(dir) default`,
+    /// which is `offset` itself when there is none.
+    fn skip_visibility(&self, mut offset: isize) -> isize {
+        if self.peek(offset).kind != TokenKind::Pub {
+            return offset;
+        }
+        offset += 1;
+        if self.peek(offset).kind == TokenKind::LeftParen {
+            offset += 3;
+        }
+        if self.is_default_keyword(self.peek(offset)) {
+            offset += 1;
+        }
+        offset
+    }
+
+    /// Parses the `pub`, `pub(dir)` or `pub(super)` before a top-level declaration, followed
+    /// by `default` when the declaration is the default export of its file.
+    fn parse_visibility(&mut self) -> ASTVisibility {
+        if self.current().kind != TokenKind::Pub {
+            return ASTVisibility::default();
+        }
+        let keyword = Some(self.consume().clone());
+
+        let mut scope = None;
+        if self.current().kind == TokenKind::LeftParen {
+            self.consume();
+            let identifier = self.consume_and_expect(TokenKind::Identifier).clone();
+            if !matches!(identifier.text(), "dir" | "super") {
+                self.diagnostics_bag
+                    .borrow_mut()
+                    .report_unknown_visibility_scope(&identifier);
+            }
+            self.consume_and_expect(TokenKind::RightParen);
+            scope = Some(identifier);
+        }
+
+        let default = match self.is_default_keyword(self.current()) {
+            true => Some(self.consume().clone()),
+            false => None,
+        };
+
+        ASTVisibility {
+            keyword,
+            scope,
+            default,
+        }
+    }
+
+    /// `default` is only a keyword after a visibility, so it stays usable as a name.
+    fn is_default_keyword(&self, token: &Token) -> bool {
+        token.kind == TokenKind::Identifier && token.text() == "default"
+    }
+
+    /// Parses the `<identifier>(<arguments>)` modifiers before a declaration, whose
+    /// arguments are names, or props written as `#<prop>`.
+    fn parse_modifiers(&mut self) -> Vec<ASTModifier> {
+        let mut modifiers = Vec::new();
+        while self.current().kind == TokenKind::Identifier {
+            let start = self.current().span.start;
+            let identifier = self.consume().clone();
+            self.consume_and_expect(TokenKind::LeftParen);
+
+            let mut arguments = Vec::new();
+            while self.current().kind != TokenKind::RightParen && !self.is_at_end() {
+                let prop = self.current().kind == TokenKind::Hashtag;
+                if prop {
+                    self.consume();
+                }
+                let identifier = self.consume_and_expect(TokenKind::Identifier).clone();
+                arguments.push(match prop {
+                    true => ASTModifierArgument::Prop(identifier),
+                    false => ASTModifierArgument::Name(identifier),
+                });
+
+                if !self.parse_separator(TokenKind::Comma, TokenKind::RightParen) {
+                    break;
+                }
+            }
+            self.consume_and_expect(TokenKind::RightParen);
+            modifiers.push(ASTModifier::new(
+                identifier,
+                arguments,
+                self.span_from(start),
+            ));
+        }
+        modifiers
+    }
+
+    fn parse_impl_statement(&mut self) -> ASTStatement<'a> {
+        let start = self.current().span.start;
+        self.consume_and_expect(TokenKind::Impl);
+        let identifier = self.consume_and_expect(TokenKind::Identifier).clone();
+        let generics = self.parse_generic_parameters();
+        self.consume_and_expect(TokenKind::LeftBrace);
+
+        let mut members = Vec::new();
+        while self.current().kind != TokenKind::RightBrace && !self.is_at_end() {
+            if self.current().kind != TokenKind::Identifier {
+                self.consume_and_expect(TokenKind::Identifier);
+                break;
+            }
+
+            let start = self.current().span.start;
+            let identifier = self.consume().clone();
+            let optional = self.current().kind == TokenKind::Question;
+            if optional {
+                self.consume();
+            }
+            self.consume_and_expect(TokenKind::Colon);
+            let ty = self.parse_type();
+            members.push(ASTPropMember::new(
+                identifier,
+                optional,
+                ty,
+                self.span_from(start),
+            ));
+
+            if matches!(self.current().kind, TokenKind::Comma | TokenKind::Semicolon) {
+                self.consume();
+            }
+        }
+
+        self.consume_and_expect(TokenKind::RightBrace);
+        ASTStatement::impl_statement(
+            identifier,
+            generics,
+            members,
+            self.next_id(),
+            self.span_from(start),
+            self.arena,
+        )
+    }
+
+    /// Parses an optional `<T, U extends V, W excludes X>` list of generic parameters.
+    fn parse_generic_parameters(&mut self) -> Vec<ASTGenericParameter<'a>> {
+        let mut generics = Vec::new();
+        if self.current().kind != TokenKind::LeftChevron {
+            return generics;
+        }
+        self.consume();
+
+        while self.current().kind != TokenKind::RightChevron && !self.is_at_end() {
+            let start = self.current().span.start;
+            let identifier = self.consume_and_expect(TokenKind::Identifier).clone();
+            let kind = match self.current().kind {
+                TokenKind::Extends => Some(ASTGenericConstraintKind::Extends),
+                TokenKind::Excludes => Some(ASTGenericConstraintKind::Excludes),
+                _ => None,
+            };
+            let constraint = kind.map(|kind| {
+                self.consume();
+                ASTGenericConstraint {
+                    kind,
+                    ty: self.parse_type(),
+                }
+            });
+            generics.push(ASTGenericParameter::new(
+                identifier,
+                constraint,
+                self.span_from(start),
+            ));
+
+            if !self.parse_separator(TokenKind::Comma, TokenKind::RightChevron) {
+                break;
+            }
+        }
+
+        self.consume_and_expect(TokenKind::RightChevron);
+        generics
+    }
+
+    fn parse_callback_statement(&mut self) -> ASTStatement<'a> {
+        let start = self.current().span.start;
+        self.consume_and_expect(TokenKind::Callback);
+        let identifier = self.consume_and_expect(TokenKind::Identifier).clone();
+        let parameters = self.parse_parameters();
+        let body = self.parse_block();
 
-        ASTStatement::let_statement(identifier, initializer)
+        ASTStatement::callback(
+            identifier,
+            parameters,
+            body,
+            self.next_id(),
+            self.span_from(start),
+            self.arena,
+        )
     }
 
-    fn parse_expression_statement(&mut self) -> ASTStatement {
+    fn parse_parameters(&mut self) -> Vec<ASTParameter<'a>> {
+        self.consume_and_expect(TokenKind::LeftParen);
+
+        let mut parameters = Vec::new();
+        while self.current().kind != TokenKind::RightParen && !self.is_at_end() {
+            let start = self.current().span.start;
+            let identifier = self.consume_and_expect(TokenKind::Identifier).clone();
+            let optional = self.current().kind == TokenKind::Question;
+            if optional {
+                self.consume();
+            }
+            let ty = self.parse_optional_type_annotation();
+            let default = match self.current().kind {
+                TokenKind::Equals => {
+                    self.consume();
+                    Some(self.parse_expression())
+                }
+                _ => None,
+            };
+            parameters.push(ASTParameter::new(
+                identifier,
+                optional,
+                ty,
+                default,
+                self.span_from(start),
+            ));
+
+            if !self.parse_separator(TokenKind::Comma, TokenKind::RightParen) {
+                break;
+            }
+        }
+
+        self.consume_and_expect(TokenKind::RightParen);
+        parameters
+    }
+
+    fn parse_block(&mut self) -> Vec<ASTStatement<'a>> {
+        self.consume_and_expect(TokenKind::LeftBrace);
+
+        let mut statements = Vec::new();
+        while self.current().kind != TokenKind::RightBrace && !self.is_at_end() {
+            statements.push(self.parse_statement());
+        }
+
+        self.consume_and_expect(TokenKind::RightBrace);
+        statements
+    }
+
+    fn parse_optional_type_annotation(&mut self) -> Option<ASTType<'a>> {
+        if self.current().kind != TokenKind::Colon {
+            return None;
+        }
+
+        self.consume();
+        Some(self.parse_type())
+    }
+
+    fn parse_type_alias_statement(&mut self) -> ASTStatement<'a> {
+        let start = self.current().span.start;
+        let visibility = self.parse_visibility();
+        self.consume_and_expect(TokenKind::Type);
+        let identifier = self.consume_and_expect(TokenKind::Identifier).clone();
+        self.consume_and_expect(TokenKind::Equals);
+        let ty = self.parse_type();
+        self.parse_statement_terminator();
+
+        ASTStatement::type_alias(
+            visibility,
+            identifier,
+            ty,
+            self.next_id(),
+            self.span_from(start),
+            self.arena,
+        )
+    }
+
+    fn parse_expression_statement(&mut self) -> ASTStatement<'a> {
+        let start = self.current().span.start;
         let expr = self.parse_expression();
-        self.consume_and_expect(TokenKind::Semicolon);
+        self.parse_statement_terminator();
 
-        ASTStatement::expression(expr)
+        ASTStatement::expression(expr, self.next_id(), self.span_from(start), self.arena)
     }
 
-    fn parse_expression(&mut self) -> ASTExpression {
-        self.parse_binary_expression(0)
+    fn parse_expression(&mut self) -> ASTExpression<'a> {
+        self.parse_assignment_expression()
     }
 
-    fn parse_primary_expression(&mut self) -> ASTExpression {
-        let token = self.consume();
+    fn parse_assignment_expression(&mut self) -> ASTExpression<'a> {
+        let start = self.current().span.start;
+        let target = self.parse_binary_expression(0);
+
+        let Some(operator) = self.parse_assignment_operator() else {
+            return target;
+        };
+        self.consume();
+
+        if !target.is_assignable() {
+            self.diagnostics_bag
+                .borrow_mut()
+                .report_invalid_assignment_target(&operator.token, *target.span());
+        }
+
+        let value = self.parse_assignment_expression();
+        ASTExpression::assignment(
+            target,
+            operator,
+            value,
+            self.next_id(),
+            self.span_from(start),
+            self.arena,
+        )
+    }
+
+    fn parse_assignment_operator(&mut self) -> Option<ASTAssignmentOperator> {
+        let token = self.current();
+
+        let kind = match token.kind {
+            TokenKind::Equals => ASTAssignmentOperatorKind::Assign,
+            TokenKind::PlusEquals => ASTAssignmentOperatorKind::AddAssign,
+            TokenKind::MinusEquals => ASTAssignmentOperatorKind::SubtractAssign,
+            TokenKind::AsteriskEquals => ASTAssignmentOperatorKind::MultiplyAssign,
+            TokenKind::SlashEquals => ASTAssignmentOperatorKind::DivideAssign,
+            _ => return None,
+        };
+
+        Some(ASTAssignmentOperator::new(kind, token.clone()))
+    }
+
+    fn parse_postfix_expression(&mut self) -> ASTExpression<'a> {
+        let start = self.current().span.start;
+        let mut expression = self.parse_primary_expression();
+
+        loop {
+            // Like JavaScript, a line starting with `(` or `[` continues the previous one.
+            if matches!(
+                self.current().kind,
+                TokenKind::LeftParen | TokenKind::LeftBracket
+            ) && self.is_line_start()
+            {
+                self.diagnostics_bag
+                    .borrow_mut()
+                    .report_ambiguous_line_continuation(self.current());
+            }
+
+            expression = match self.current().kind {
+                TokenKind::LeftParen => {
+                    self.consume();
+                    let arguments = self.parse_arguments();
+                    ASTExpression::call(
+                        expression,
+                        arguments,
+                        self.next_id(),
+                        self.span_from(start),
+                        self.arena,
+                    )
+                }
+                // `<` is not an operator, so after an expression it always opens type arguments.
+                TokenKind::LeftChevron => {
+                    self.consume();
+                    let mut type_arguments = Vec::new();
+                    while self.current().kind != TokenKind::RightChevron && !self.is_at_end() {
+                        type_arguments.push(self.parse_type());
+
+                        if !self.parse_separator(TokenKind::Comma, TokenKind::RightChevron) {
+                            break;
+                        }
+                    }
+                    self.consume_and_expect(TokenKind::RightChevron);
+                    self.consume_and_expect(TokenKind::LeftParen);
+                    let arguments = self.parse_arguments();
+                    ASTExpression::generic_call(
+                        expression,
+                        type_arguments,
+                        arguments,
+                        self.next_id(),
+                        self.span_from(start),
+                        self.arena,
+                    )
+                }
+                TokenKind::Period => {
+                    self.consume();
+                    let property = self.consume_and_expect(TokenKind::Identifier).clone();
+                    ASTExpression::member(
+                        expression,
+                        property,
+                        self.next_id(),
+                        self.span_from(start),
+                        self.arena,
+                    )
+                }
+                TokenKind::LeftBracket => {
+                    self.consume();
+                    let index = self.parse_expression();
+                    self.consume_and_expect(TokenKind::RightBracket);
+                    ASTExpression::index(
+                        expression,
+                        index,
+                        self.next_id(),
+                        self.span_from(start),
+                        self.arena,
+                    )
+                }
+                _ => return expression,
+            };
+        }
+    }
+
+    fn parse_arguments(&mut self) -> Vec<ASTExpression<'a>> {
+        let mut arguments = Vec::new();
+        while self.current().kind != TokenKind::RightParen && !self.is_at_end() {
+            arguments.push(self.parse_expression());
+
+            if !self.parse_separator(TokenKind::Comma, TokenKind::RightParen) {
+                break;
+            }
+        }
+
+        self.consume_and_expect(TokenKind::RightParen);
+        arguments
+    }
+
+    fn parse_primary_expression(&mut self) -> ASTExpression<'a> {
+        let start = self.current().span.start;
+        // Closing tokens are left in place for the enclosing statement or list.
+        let token = match Self::is_closing_token(&self.current().kind) {
+            true => self.current(),
+            false => self.consume(),
+        };
 
         match token.kind {
-            TokenKind::Number(value) => ASTExpression::number(value),
+            TokenKind::Number(value) => {
+                ASTExpression::number(value, self.next_id(), self.span_from(start))
+            }
+            TokenKind::String(_) => {
+                ASTExpression::string(token.clone(), self.next_id(), self.span_from(start))
+            }
             TokenKind::LeftParen => {
                 let expr = self.parse_expression();
                 self.consume_and_expect(TokenKind::RightParen);
 
-                ASTExpression::parenthesized(expr)
+                ASTExpression::parenthesized(
+                    expr,
+                    self.next_id(),
+                    self.span_from(start),
+                    self.arena,
+                )
+            }
+            TokenKind::Identifier => {
+                ASTExpression::identifier(token.clone(), self.next_id(), self.span_from(start))
+            }
+            TokenKind::LeftBracket => {
+                let mut elements = Vec::new();
+                while self.current().kind != TokenKind::RightBracket && !self.is_at_end() {
+                    elements.push(self.parse_expression());
+
+                    if !self.parse_separator(TokenKind::Comma, TokenKind::RightBracket) {
+                        break;
+                    }
+                }
+                self.consume_and_expect(TokenKind::RightBracket);
+
+                ASTExpression::array(elements, self.next_id(), self.span_from(start))
             }
-            TokenKind::Identifier => ASTExpression::identifier(token.clone()),
+            TokenKind::LeftChevron => self.parse_element(start),
+            TokenKind::Pipe => self.parse_closure(start),
             _ => {
-                self.diagnostics_bag
-                    .borrow_mut()
-                    .report_expected_expression(&token);
+                self.report(|diagnostics| diagnostics.report_expected_expression(token));
+
+                ASTExpression::error(self.next_id(), self.span_from(start))
+            }
+        }
+    }
+
+    /// Parses a closure following its opening `|`, e.g. `|message| message.text` or
+    /// `|(index, message)| index`.
+    fn parse_closure(&mut self, start: usize) -> ASTExpression<'a> {
+        let mut parameters = Vec::new();
+        while self.current().kind != TokenKind::Pipe && !self.is_at_end() {
+            let parameter_start = self.current().span.start;
+            let parameter = match self.current().kind {
+                TokenKind::LeftParen => {
+                    self.consume();
+                    let mut items = Vec::new();
+                    while self.current().kind != TokenKind::RightParen && !self.is_at_end() {
+                        items.push(self.consume_and_expect(TokenKind::Identifier).clone());
+
+                        if !self.parse_separator(TokenKind::Comma, TokenKind::RightParen) {
+                            break;
+                        }
+                    }
+                    self.consume_and_expect(TokenKind::RightParen);
+                    ASTClosureParameter::Tuple(items)
+                }
+                _ => {
+                    let identifier = self.consume_and_expect(TokenKind::Identifier).clone();
+                    // Unions aren't allowed, as their `|` would close the parameters.
+                    let ty = match self.current().kind {
+                        TokenKind::Colon => {
+                            self.consume();
+                            let start = self.current().span.start;
+                            match self.is_trusted_modifier() {
+                                true => {
+                                    self.consume();
+                                    let inner = self.parse_primary_type();
+                                    let span = self.span_from(start);
+                                    Some(ASTType::trusted(inner, self.next_id(), span, self.arena))
+                                }
+                                false => Some(self.parse_primary_type()),
+                            }
+                        }
+                        _ => None,
+                    };
+                    ASTClosureParameter::Binding(Box::new_in(
+                        ASTParameter::new(
+                            identifier,
+                            false,
+                            ty,
+                            None,
+                            self.span_from(parameter_start),
+                        ),
+                        self.arena,
+                    ))
+                }
+            };
+            parameters.push(parameter);
+
+            if !self.parse_separator(TokenKind::Comma, TokenKind::Pipe) {
+                break;
+            }
+        }
+        self.consume_and_expect(TokenKind::Pipe);
+        let body = self.parse_expression();
+
+        ASTExpression::closure(
+            parameters,
+            body,
+            self.next_id(),
+            self.span_from(start),
+            self.arena,
+        )
+    }
+
+    /// Parses a JSX element following its opening `<`, or a fragment, `<>...</>`.
+    fn parse_element(&mut self, start: usize) -> ASTExpression<'a> {
+        let name = match self.current().kind {
+            TokenKind::RightChevron => {
+                let position = self.current().span.start;
+                Token::new(
+                    TokenKind::Identifier,
+                    TextSpan::new(position, position),
+                    Rc::from(""),
+                )
+            }
+            _ => self.consume_and_expect(TokenKind::Identifier).clone(),
+        };
+        let mut attributes = Vec::new();
+        while matches!(
+            self.current().kind,
+            TokenKind::Identifier | TokenKind::Hashtag
+        ) {
+            attributes.push(self.parse_attribute());
+        }
+
+        let children = match self.current().kind {
+            TokenKind::Slash => {
+                self.consume();
+                self.consume_and_expect(TokenKind::RightChevron);
+                None
+            }
+            _ => {
+                self.consume_and_expect(TokenKind::RightChevron);
+                Some(self.parse_element_children(&name))
+            }
+        };
+
+        ASTExpression::element(
+            name,
+            attributes,
+            children,
+            self.next_id(),
+            self.span_from(start),
+        )
+    }
+
+    /// Parses `name=<literal>`, `name={<expression>}` or the `#name` shorthand.
+    fn parse_attribute(&mut self) -> ASTAttribute<'a> {
+        if self.current().kind == TokenKind::Hashtag {
+            self.consume();
+            let name = self.consume_and_expect(TokenKind::Identifier).clone();
+            let value = ASTExpression::identifier(name.clone(), self.next_id(), name.span);
+            return ASTAttribute::new(name, value, true);
+        }
+
+        let name = self.parse_attribute_name();
+        self.consume_and_expect(TokenKind::Equals);
+        let value = match self.current().kind {
+            TokenKind::LeftBrace => {
+                self.consume();
+                let value = self.parse_expression();
+                self.consume_and_expect(TokenKind::RightBrace);
+                value
+            }
+            _ => self.parse_primary_expression(),
+        };
+        ASTAttribute::new(name, value, false)
+    }
 
-                ASTExpression::error(token.span.clone())
+    /// Parses the name of an attribute, which may contain dashes, e.g. `aria-label`.
+    fn parse_attribute_name(&mut self) -> Token {
+        let start = self.current().span.start;
+        let name = self.consume_and_expect(TokenKind::Identifier).clone();
+        let mut end = name.span.end;
+        while self.current().kind == TokenKind::Minus
+            && self.current().span.start == end
+            && self.peek(1).kind == TokenKind::Identifier
+            && self.peek(1).span.start == self.current().span.end
+        {
+            self.consume();
+            end = self.consume().span.end;
+        }
+        match end == name.span.end {
+            true => name,
+            false => {
+                let span = self.span_from(start);
+                let text = Rc::from(self.source.slice(span));
+                Token::new(TokenKind::Identifier, span, text)
             }
         }
     }
 
+    /// Parses the children of an element up to and including its closing tag.
+    fn parse_element_children(&mut self, name: &Token) -> Vec<ASTExpression<'a>> {
+        let mut children = Vec::new();
+        loop {
+            if let Some(text) = self.parse_text() {
+                children.push(text);
+            }
+            match self.current().kind {
+                TokenKind::LeftChevron if self.peek(1).kind == TokenKind::Slash => break,
+                TokenKind::LeftChevron => {
+                    let start = self.consume().span.start;
+                    children.push(self.parse_element(start));
+                }
+                TokenKind::LeftBrace => {
+                    self.consume();
+                    children.push(self.parse_expression());
+                    self.consume_and_expect(TokenKind::RightBrace);
+                }
+                _ => {
+                    self.consume_and_expect(TokenKind::LeftChevron);
+                    return children;
+                }
+            }
+        }
+
+        self.consume();
+        self.consume();
+        // Fragments are closed by `</>`.
+        if !name.text().is_empty() {
+            let closing = self.consume_and_expect(TokenKind::Identifier).clone();
+            if closing.kind == TokenKind::Identifier && closing.text() != name.text() {
+                self.report(|diagnostics| {
+                    diagnostics.report_mismatched_closing_tag(name, &closing)
+                });
+            }
+        }
+        self.consume_and_expect(TokenKind::RightChevron);
+        children
+    }
+
+    /// Parses the text up to the next tag or `{`, including the whitespace between them,
+    /// giving `None` when it collapses to nothing, e.g. the indentation before a child.
+    fn parse_text(&mut self) -> Option<ASTExpression<'a>> {
+        let start = self.peek(-1).span.end;
+        while !matches!(
+            self.current().kind,
+            TokenKind::LeftChevron | TokenKind::LeftBrace | TokenKind::EOF
+        ) {
+            let token = self.current();
+            // Quotes in text aren't strings, but are lexed as such, taking the tags after them.
+            if matches!(token.kind, TokenKind::String(_) | TokenKind::Bad)
+                && let Some(offset) = token.text().find(['<', '{'])
+            {
+                let position = token.span.start + offset;
+                self.relex(position);
+                break;
+            }
+            self.consume();
+        }
+
+        let end = self.current().span.start;
+        let span = TextSpan::new(start, end);
+        let raw = self.source.slice(span);
+        let value = ASTTextExpression::collapse(raw);
+        if value.is_empty() {
+            return None;
+        }
+        Some(ASTExpression::text(value, self.next_id(), span))
+    }
+
     fn parse_binary_operator(&mut self) -> Option<ASTBinaryOperator> {
         let token = self.current();
 
@@ -150,20 +1326,312 @@ impl Parser {
         Some(ASTBinaryOperator::new(kind, token.clone()))
     }
 
-    fn parse_binary_expression(&mut self, precedence: u8) -> ASTExpression {
-        let mut left = self.parse_primary_expression();
+    fn parse_binary_expression(&mut self, precedence: u8) -> ASTExpression<'a> {
+        let start = self.current().span.start;
+        let mut left = self.parse_postfix_expression();
 
         while let Some(operator) = self.parse_binary_operator() {
-            self.consume();
             let op_precedence = operator.precedence();
             if op_precedence < precedence {
                 break;
             }
 
-            let right = self.parse_binary_expression(op_precedence);
-            left = ASTExpression::binary(left, operator, right);
+            self.consume();
+            let right = self.parse_binary_expression(op_precedence + 1);
+            left = ASTExpression::binary(
+                left,
+                operator,
+                right,
+                self.next_id(),
+                self.span_from(start),
+                self.arena,
+            );
         }
 
         left
     }
+
+    fn parse_type(&mut self) -> ASTType<'a> {
+        let start = self.current().span.start;
+        if self.is_trusted_modifier() {
+            self.consume();
+            let inner = self.parse_type();
+            return ASTType::trusted(inner, self.next_id(), self.span_from(start), self.arena);
+        }
+
+        let mut members = vec![self.parse_primary_type()];
+
+        while self.current().kind == TokenKind::Pipe {
+            self.consume();
+            members.push(self.parse_primary_type());
+        }
+
+        match members.len() {
+            1 => members.pop().unwrap(),
+            _ => ASTType::union(members, self.next_id(), self.span_from(start)),
+        }
+    }
+
+    /// `trusted` is only a modifier when a type follows it, so it stays usable as a name.
+    fn is_trusted_modifier(&self) -> bool {
+        self.current().text() == "trusted"
+            && matches!(
+                self.peek(1).kind,
+                TokenKind::Identifier
+                    | TokenKind::Number(_)
+                    | TokenKind::String(_)
+                    | TokenKind::LeftParen
+            )
+    }
+
+    fn parse_primary_type(&mut self) -> ASTType<'a> {
+        let start = self.current().span.start;
+        let mut ty = self.parse_atomic_type();
+
+        while self.current().kind == TokenKind::LeftBracket
+            && self.peek(1).kind == TokenKind::RightBracket
+        {
+            self.consume();
+            self.consume();
+            ty = ASTType::array(ty, self.next_id(), self.span_from(start), self.arena);
+        }
+
+        if self.current().kind == TokenKind::Question {
+            self.consume();
+            return ASTType::optional(ty, self.next_id(), self.span_from(start), self.arena);
+        }
+
+        ty
+    }
+
+    /// Whether the parenthesis at the current token opens the parameters of a function
+    /// type, e.g. `(e: T) => string`, rather than a parenthesised type.
+    fn is_function_type(&self) -> bool {
+        let mut depth = 0;
+        let mut offset = 0;
+        loop {
+            match self.peek(offset).kind {
+                TokenKind::LeftParen => depth += 1,
+                TokenKind::RightParen => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return self.peek(offset + 1).kind == TokenKind::FatArrow;
+                    }
+                }
+                TokenKind::EOF => return false,
+                _ => {}
+            }
+            offset += 1;
+        }
+    }
+
+    /// Parses the properties of an object type, e.g. `{ name: string, age?: number }`.
+    fn parse_object_type(&mut self) -> ASTType<'a> {
+        let start = self.current().span.start;
+        self.consume_and_expect(TokenKind::LeftBrace);
+
+        let mut properties = Vec::new();
+        while self.current().kind != TokenKind::RightBrace && !self.is_at_end() {
+            let identifier = self.consume_and_expect(TokenKind::Identifier).clone();
+            let optional = self.current().kind == TokenKind::Question;
+            if optional {
+                self.consume();
+            }
+            self.consume_and_expect(TokenKind::Colon);
+            let ty = self.parse_type();
+            properties.push(ASTPropertyType::new(identifier, optional, ty));
+
+            if !self.parse_separator(TokenKind::Comma, TokenKind::RightBrace) {
+                break;
+            }
+        }
+
+        self.consume_and_expect(TokenKind::RightBrace);
+        ASTType::object(properties, self.next_id(), self.span_from(start))
+    }
+
+    fn parse_atomic_type(&mut self) -> ASTType<'a> {
+        let start = self.current().span.start;
+        if self.current().kind == TokenKind::LeftBrace {
+            return self.parse_object_type();
+        }
+        if self.current().kind == TokenKind::LeftParen && self.is_function_type() {
+            let parameters = self.parse_parameters();
+            self.consume_and_expect(TokenKind::FatArrow);
+            let return_type = self.parse_type();
+
+            return ASTType::function(
+                parameters,
+                return_type,
+                self.next_id(),
+                self.span_from(start),
+                self.arena,
+            );
+        }
+
+        // Closing tokens are left in place for the enclosing statement or list.
+        let token = match Self::is_closing_token(&self.current().kind) {
+            true => self.current(),
+            false => self.consume(),
+        };
+
+        match token.kind {
+            TokenKind::Identifier if self.current().kind == TokenKind::LeftChevron => {
+                let identifier = token.clone();
+                self.consume();
+
+                let mut arguments = Vec::new();
+                while self.current().kind != TokenKind::RightChevron && !self.is_at_end() {
+                    arguments.push(self.parse_type());
+
+                    if !self.parse_separator(TokenKind::Comma, TokenKind::RightChevron) {
+                        break;
+                    }
+                }
+                self.consume_and_expect(TokenKind::RightChevron);
+
+                ASTType::generic(identifier, arguments, self.next_id(), self.span_from(start))
+            }
+            TokenKind::Identifier => {
+                ASTType::named(token.clone(), self.next_id(), self.span_from(start))
+            }
+            TokenKind::Number(_) | TokenKind::String(_) => {
+                ASTType::literal(token.clone(), self.next_id(), self.span_from(start))
+            }
+            TokenKind::LeftParen => {
+                let ty = self.parse_type();
+                self.consume_and_expect(TokenKind::RightParen);
+
+                ty
+            }
+            _ => {
+                self.report(|diagnostics| diagnostics.report_expected_type(token));
+
+                ASTType::error(self.next_id(), self.span_from(start))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::testing::parse_with_errors;
+
+    /// The source text of each statement parsed from `source`, along with the syntax errors.
+    fn recover(source: &str) -> (Vec<String>, Vec<String>) {
+        let (ast, errors) = parse_with_errors(source);
+        let text = SourceText::new(source);
+        let statements = ast
+            .statements
+            .iter()
+            .map(|statement| text.slice(*statement.span()).to_string())
+            .collect();
+        (statements, errors)
+    }
+
+    #[test]
+    fn reports_one_error_per_broken_statement() {
+        // The missing `)` follows from the missing operand, so it isn't reported again.
+        let (statements, errors) = recover("let a = ;\nlet b = 2\nlet c = (1 + ;\nc");
+        assert_eq!(
+            errors,
+            vec![
+                "Expected expression, found ';'",
+                "Expected expression, found ';'",
+            ]
+        );
+        assert_eq!(
+            statements,
+            vec!["let a = ;", "let b = 2", "let c = (1 + ;", "c"]
+        );
+    }
+
+    #[test]
+    fn reports_missing_terminators_between_statements_on_one_line() {
+        // A statement keyword starts the next statement right away, anything else is skipped
+        // up to the next statement keyword starting a line.
+        let (statements, errors) = recover("let a = 1 let b = 2\nlet c = 3 4\nc\nlet d = c");
+        assert_eq!(errors, vec!["Expected ;, found let", "Expected ;, found 4"]);
+        assert_eq!(
+            statements,
+            vec!["let a = 1", "let b = 2", "let c = 3", "let d = c"]
+        );
+    }
+
+    #[test]
+    fn recovers_within_and_after_blocks() {
+        let (statements, errors) = recover(
+            "component A() {\n    let a = ;\n    return <p></p>\n}\nlet b = 1 +\nlet c = 2",
+        );
+        assert_eq!(
+            errors,
+            vec![
+                "Expected expression, found ';'",
+                "Expected expression, found 'let' keyword",
+            ]
+        );
+        assert_eq!(
+            statements,
+            vec![
+                "component A() {\n    let a = ;\n    return <p></p>\n}",
+                "let b = 1 +",
+                "let c = 2",
+            ]
+        );
+
+        let (ast, _) = parse_with_errors("component A() {\n    let a = ;\n    return <p></p>\n}");
+        let ASTStatementKind::Component(component) = ast.statements[0].kind() else {
+            panic!("expected a component");
+        };
+        assert_eq!(component.body.len(), 2);
+    }
+
+    #[test]
+    fn reports_invalid_assignment_targets() {
+        let (_, errors) =
+            parse_with_errors("let x = 1;\n1 = 2;\nx + 1 += 2;\n(x) = 3;\nx[0] = 1;\nx.y = 2;\n");
+        assert_eq!(
+            errors,
+            vec![
+                "Invalid left-hand side of '=', expected a variable, member or index",
+                "Invalid left-hand side of '+=', expected a variable, member or index",
+                "Invalid left-hand side of '=', expected a variable, member or index",
+            ]
+        );
+    }
+
+    #[test]
+    fn terminates_statements_at_line_breaks() {
+        let (statements, errors) =
+            recover("let a = [1]\nlet b = a;\nlet c = a\n  .length\nlet d = c +\n  1\nd");
+        assert_eq!(errors, Vec::<String>::new());
+        // A line starting with `.` or following an operator continues the statement.
+        assert_eq!(
+            statements,
+            vec![
+                "let a = [1]",
+                "let b = a;",
+                "let c = a\n  .length",
+                "let d = c +\n  1",
+                "d",
+            ]
+        );
+    }
+
+    #[test]
+    fn warns_about_lines_continuing_the_previous_statement() {
+        let (statements, errors) = recover("let a = f\n(1)\nlet b = a\n[0]\nb;\n(a)");
+        assert_eq!(
+            errors,
+            vec![
+                "A line starting with '(' continues the previous statement, add a ';' before it if that isn't intended",
+                "A line starting with '[' continues the previous statement, add a ';' before it if that isn't intended",
+            ]
+        );
+        assert_eq!(
+            statements,
+            vec!["let a = f\n(1)", "let b = a\n[0]", "b;", "(a)"]
+        );
+    }
 }
@@ -2,12 +2,17 @@ use crate::{ast::syntax::SyntaxColors, prelude::*};
 
 pub struct ASTPrinter {
     result: String,
+    indent: usize,
+    /// The source of the tree, which the text of error nodes is read from.
+    source: SourceText,
 }
 
 impl ASTPrinter {
-    pub fn new() -> Self {
+    pub fn new(source: &SourceText) -> Self {
         Self {
             result: String::new(),
+            indent: 0,
+            source: source.clone(),
         }
     }
 
@@ -20,11 +25,128 @@ impl ASTPrinter {
     }
 
     fn push_whitespace(&mut self) {
-        self.result.push_str(" ");
+        self.result.push(' ');
+    }
+
+    /// Prints the `pub[(<scope>)] [default] ` before a declaration, if any.
+    fn push_visibility(&mut self, visibility: &ASTVisibility) {
+        if !visibility.is_exported() {
+            return;
+        }
+        self.push(SyntaxColors::keyword().apply_to("pub").to_string());
+        if let Some(scope) = &visibility.scope {
+            let scope = format!("({})", scope.text());
+            self.push(SyntaxColors::text().apply_to(scope).to_string());
+        }
+        self.push_whitespace();
+        if visibility.is_default() {
+            self.push(SyntaxColors::keyword().apply_to("default").to_string());
+            self.push_whitespace();
+        }
     }
 
     fn push_newline(&mut self) {
         self.result.push('\n');
+        self.result.push_str(&"  ".repeat(self.indent));
+    }
+
+    fn push_parameters(&mut self, parameters: &[ASTParameter]) {
+        self.push(SyntaxColors::text().apply_to("(").to_string());
+        for (index, parameter) in parameters.iter().enumerate() {
+            if index > 0 {
+                self.push(SyntaxColors::text().apply_to(", ").to_string());
+            }
+            self.push(
+                SyntaxColors::variable()
+                    .apply_to(parameter.identifier())
+                    .to_string(),
+            );
+            if parameter.optional {
+                self.push(SyntaxColors::text().apply_to("?").to_string());
+            }
+            if let Some(ty) = &parameter.ty {
+                self.push(SyntaxColors::text().apply_to(": ").to_string());
+                self.visit_type(ty);
+            }
+            if let Some(default) = &parameter.default {
+                self.push(SyntaxColors::text().apply_to(" = ").to_string());
+                self.visit_expression(default);
+            }
+        }
+        self.push(SyntaxColors::text().apply_to(")").to_string());
+    }
+
+    fn push_generics(&mut self, generics: &[ASTGenericParameter]) {
+        if generics.is_empty() {
+            return;
+        }
+
+        self.push(SyntaxColors::text().apply_to("<").to_string());
+        for (index, generic) in generics.iter().enumerate() {
+            if index > 0 {
+                self.push(SyntaxColors::text().apply_to(", ").to_string());
+            }
+            self.push(
+                SyntaxColors::text()
+                    .apply_to(generic.identifier())
+                    .to_string(),
+            );
+            if let Some(constraint) = &generic.constraint {
+                let keyword = match constraint.kind {
+                    ASTGenericConstraintKind::Extends => "extends",
+                    ASTGenericConstraintKind::Excludes => "excludes",
+                };
+                self.push_whitespace();
+                self.push(SyntaxColors::keyword().apply_to(keyword).to_string());
+                self.push_whitespace();
+                self.visit_type(&constraint.ty);
+            }
+        }
+        self.push(SyntaxColors::text().apply_to(">").to_string());
+    }
+
+    fn push_nested_type(&mut self, ty: &ASTType) {
+        if ty.needs_parentheses() {
+            self.push(SyntaxColors::text().apply_to("(").to_string());
+            self.visit_type(ty);
+            self.push(SyntaxColors::text().apply_to(")").to_string());
+        } else {
+            self.visit_type(ty);
+        }
+    }
+
+    fn push_label(&mut self, label: &Option<Token>) {
+        if let Some(label) = label {
+            self.push(
+                SyntaxColors::text()
+                    .apply_to(format!("{}: ", label.text()))
+                    .to_string(),
+            );
+        }
+    }
+
+    fn push_jump_label(&mut self, label: &Option<Token>) {
+        if let Some(label) = label {
+            self.push_whitespace();
+            self.push(
+                SyntaxColors::text()
+                    .apply_to(label.text().to_string())
+                    .to_string(),
+            );
+        }
+        self.push(SyntaxColors::text().apply_to(";").to_string());
+    }
+
+    fn push_block(&mut self, statements: &[ASTStatement]) {
+        self.push(SyntaxColors::text().apply_to(" {").to_string());
+        self.indent += 1;
+        for statement in statements {
+            self.push_newline();
+            self.visit_statement(statement);
+        }
+        self.indent -= 1;
+        self.push_newline();
+        self.push(SyntaxColors::text().apply_to("}").to_string());
     }
 }
 
@@ -32,7 +154,7 @@ impl ASTVisitor for ASTPrinter {
     fn visit_error_expression(&mut self, span: &TextSpan) {
         self.push(
             SyntaxColors::error()
-                .apply_to(span.clone_text())
+                .apply_to(self.source.slice(*span))
                 .to_string(),
         );
     }
@@ -45,18 +167,367 @@ impl ASTVisitor for ASTPrinter {
         );
     }
 
+    fn visit_string_expression(&mut self, expression: &ASTStringExpression) {
+        self.push(
+            SyntaxColors::string()
+                .apply_to(expression.literal.text().to_string())
+                .to_string(),
+        );
+    }
+
+    fn visit_statement(&mut self, statement: &ASTStatement) {
+        self.default_visit_statement(statement);
+
+        if let ASTStatementKind::Expression(_) = statement.kind() {
+            self.push(SyntaxColors::text().apply_to(";").to_string());
+        }
+        if self.indent == 0 {
+            self.push_newline();
+        }
+    }
+
     fn visit_let_statement(&mut self, let_statement: &ASTLetStatement) {
-        self.push(SyntaxColors::keyword().apply_to("let").to_string());
-        self.push_whitespace();
+        self.push_visibility(&let_statement.visibility);
         self.push(
-            SyntaxColors::text()
-                .apply_to(let_statement.identifier.span.clone_text())
+            SyntaxColors::keyword()
+                .apply_to(let_statement.keyword.text().to_string())
                 .to_string(),
         );
+        self.push_whitespace();
+        match &let_statement.pattern {
+            ASTPattern::Identifier(identifier) => self.push(
+                SyntaxColors::text()
+                    .apply_to(identifier.text().to_string())
+                    .to_string(),
+            ),
+            ASTPattern::Object(properties) => {
+                let properties: Vec<&str> =
+                    properties.iter().map(|property| property.text()).collect();
+                self.push(
+                    SyntaxColors::text()
+                        .apply_to(format!("{{ {} }}", properties.join(", ")))
+                        .to_string(),
+                );
+            }
+        }
+        if let Some(ty) = &let_statement.type_annotation {
+            self.push(SyntaxColors::text().apply_to(": ").to_string());
+            self.visit_type(ty);
+        }
         self.push(SyntaxColors::text().apply_to(" = ").to_string());
         self.visit_expression(&let_statement.initializer);
         self.push(SyntaxColors::text().apply_to(";").to_string());
+    }
+
+    fn visit_component_statement(&mut self, component: &ASTComponentStatement) {
+        for modifier in component.modifiers() {
+            self.push(
+                SyntaxColors::keyword()
+                    .apply_to(modifier.identifier())
+                    .to_string(),
+            );
+            self.push(SyntaxColors::text().apply_to("(").to_string());
+            for (index, argument) in modifier.arguments().iter().enumerate() {
+                if index > 0 {
+                    self.push(SyntaxColors::text().apply_to(", ").to_string());
+                }
+                let argument = match argument {
+                    ASTModifierArgument::Name(name) => name.text().to_string(),
+                    ASTModifierArgument::Prop(prop) => format!("#{}", prop.text()),
+                };
+                self.push(SyntaxColors::text().apply_to(argument).to_string());
+            }
+            self.push(SyntaxColors::text().apply_to(")").to_string());
+            self.push_newline();
+        }
+        self.push_visibility(&component.visibility);
+        self.push(SyntaxColors::keyword().apply_to("component").to_string());
+        self.push_whitespace();
+        self.push(
+            SyntaxColors::text()
+                .apply_to(component.identifier())
+                .to_string(),
+        );
+        self.push_generics(&component.generics);
+        self.push_parameters(&component.parameters);
+        self.push_block(&component.body);
+    }
+
+    fn visit_impl_statement(&mut self, impl_statement: &ASTImplStatement) {
+        self.push(SyntaxColors::keyword().apply_to("impl").to_string());
+        self.push_whitespace();
+        self.push(
+            SyntaxColors::text()
+                .apply_to(impl_statement.identifier())
+                .to_string(),
+        );
+        self.push_generics(&impl_statement.generics);
+        self.push(SyntaxColors::text().apply_to(" {").to_string());
+        self.indent += 1;
+        for member in &impl_statement.members {
+            self.push_newline();
+            self.push(
+                SyntaxColors::variable()
+                    .apply_to(member.identifier())
+                    .to_string(),
+            );
+            if member.is_optional() {
+                self.push(SyntaxColors::text().apply_to("?").to_string());
+            }
+            self.push(SyntaxColors::text().apply_to(": ").to_string());
+            self.visit_type(&member.ty);
+        }
+        self.indent -= 1;
         self.push_newline();
+        self.push(SyntaxColors::text().apply_to("}").to_string());
+    }
+
+    fn visit_callback_statement(&mut self, callback: &ASTCallbackStatement) {
+        self.push(SyntaxColors::keyword().apply_to("callback").to_string());
+        self.push_whitespace();
+        self.push(
+            SyntaxColors::text()
+                .apply_to(callback.identifier())
+                .to_string(),
+        );
+        self.push_parameters(&callback.parameters);
+        self.push_block(&callback.body);
+    }
+
+    fn visit_for_statement(&mut self, for_statement: &ASTForStatement) {
+        self.push_label(&for_statement.label);
+        self.push(SyntaxColors::keyword().apply_to("for").to_string());
+        self.push_whitespace();
+        if let Some(keyword) = &for_statement.keyword {
+            self.push(
+                SyntaxColors::keyword()
+                    .apply_to(keyword.text().to_string())
+                    .to_string(),
+            );
+            self.push_whitespace();
+        }
+        self.push(
+            SyntaxColors::variable()
+                .apply_to(for_statement.identifier())
+                .to_string(),
+        );
+        self.push_whitespace();
+        let kind = match for_statement.kind {
+            ASTForKind::Of => "of",
+            ASTForKind::In => "in",
+        };
+        self.push(SyntaxColors::keyword().apply_to(kind).to_string());
+        self.push_whitespace();
+        self.visit_expression(&for_statement.iterable);
+        self.push_block(&for_statement.body);
+    }
+
+    fn visit_while_statement(&mut self, while_statement: &ASTWhileStatement) {
+        self.push_label(&while_statement.label);
+        self.push(SyntaxColors::keyword().apply_to("while").to_string());
+        self.push_whitespace();
+        self.visit_expression(&while_statement.condition);
+        self.push_block(&while_statement.body);
+    }
+
+    fn visit_break_statement(&mut self, break_statement: &ASTBreakStatement) {
+        self.push(SyntaxColors::keyword().apply_to("break").to_string());
+        self.push_jump_label(&break_statement.label);
+    }
+
+    fn visit_continue_statement(&mut self, continue_statement: &ASTContinueStatement) {
+        self.push(SyntaxColors::keyword().apply_to("continue").to_string());
+        self.push_jump_label(&continue_statement.label);
+    }
+
+    fn visit_return_statement(&mut self, return_statement: &ASTReturnStatement) {
+        self.push(SyntaxColors::keyword().apply_to("return").to_string());
+        if let Some(value) = &return_statement.value {
+            self.push_whitespace();
+            self.visit_expression(value);
+        }
+        self.push(SyntaxColors::text().apply_to(";").to_string());
+    }
+
+    fn visit_style_statement(&mut self, style: &ASTStyleStatement) {
+        self.push(SyntaxColors::keyword().apply_to("style").to_string());
+        self.push(SyntaxColors::text().apply_to(" {").to_string());
+        self.push(SyntaxColors::text().apply_to(style.css()).to_string());
+        self.push(SyntaxColors::text().apply_to("}").to_string());
+    }
+
+    fn visit_import_statement(&mut self, import: &ASTImportStatement) {
+        self.push(SyntaxColors::keyword().apply_to("import").to_string());
+        self.push_whitespace();
+        let mut path: Vec<&str> = import.path().collect();
+        let items = match &import.items {
+            ASTImportItems::Glob(_) => "*".to_string(),
+            ASTImportItems::Named(items) => {
+                let items: Vec<&str> = items.iter().map(|item| item.text()).collect();
+                match items.as_slice() {
+                    [item] => item.to_string(),
+                    items => format!("{{{}}}", items.join(", ")),
+                }
+            }
+        };
+        path.push(&items);
+        self.push(SyntaxColors::text().apply_to(path.join("::")).to_string());
+        self.push(SyntaxColors::text().apply_to(";").to_string());
+    }
+
+    fn visit_array_expression(&mut self, expression: &ASTArrayExpression) {
+        self.push(SyntaxColors::text().apply_to("[").to_string());
+        for (index, element) in expression.elements.iter().enumerate() {
+            if index > 0 {
+                self.push(SyntaxColors::text().apply_to(", ").to_string());
+            }
+            self.visit_expression(element);
+        }
+        self.push(SyntaxColors::text().apply_to("]").to_string());
+    }
+
+    fn visit_call_expression(&mut self, expression: &ASTCallExpression) {
+        self.visit_expression(&expression.callee);
+        if !expression.type_arguments.is_empty() {
+            self.push(SyntaxColors::text().apply_to("<").to_string());
+            for (index, argument) in expression.type_arguments.iter().enumerate() {
+                if index > 0 {
+                    self.push(SyntaxColors::text().apply_to(", ").to_string());
+                }
+                self.visit_type(argument);
+            }
+            self.push(SyntaxColors::text().apply_to(">").to_string());
+        }
+        self.push(SyntaxColors::text().apply_to("(").to_string());
+        for (index, argument) in expression.arguments.iter().enumerate() {
+            if index > 0 {
+                self.push(SyntaxColors::text().apply_to(", ").to_string());
+            }
+            self.visit_expression(argument);
+        }
+        self.push(SyntaxColors::text().apply_to(")").to_string());
+    }
+
+    fn visit_member_expression(&mut self, expression: &ASTMemberExpression) {
+        self.visit_expression(&expression.object);
+        self.push(SyntaxColors::text().apply_to(".").to_string());
+        self.push(
+            SyntaxColors::variable()
+                .apply_to(expression.property())
+                .to_string(),
+        );
+    }
+
+    fn visit_index_expression(&mut self, expression: &ASTIndexExpression) {
+        self.visit_expression(&expression.object);
+        self.push(SyntaxColors::text().apply_to("[").to_string());
+        self.visit_expression(&expression.index);
+        self.push(SyntaxColors::text().apply_to("]").to_string());
+    }
+
+    fn visit_assignment_expression(&mut self, expression: &ASTAssignmentExpression) {
+        self.visit_expression(&expression.target);
+        self.push_whitespace();
+        self.push(
+            SyntaxColors::text()
+                .apply_to(expression.operator.token.text().to_string())
+                .to_string(),
+        );
+        self.push_whitespace();
+        self.visit_expression(&expression.value);
+    }
+
+    fn visit_element_expression(&mut self, expression: &ASTElementExpression) {
+        self.push(
+            SyntaxColors::text()
+                .apply_to(format!("<{}", expression.name()))
+                .to_string(),
+        );
+        for attribute in &expression.attributes {
+            self.push_whitespace();
+            if attribute.shorthand {
+                self.push(
+                    SyntaxColors::variable()
+                        .apply_to(format!("#{}", attribute.name()))
+                        .to_string(),
+                );
+                continue;
+            }
+            self.push(
+                SyntaxColors::variable()
+                    .apply_to(attribute.name())
+                    .to_string(),
+            );
+            self.push(SyntaxColors::text().apply_to("={").to_string());
+            self.visit_expression(&attribute.value);
+            self.push(SyntaxColors::text().apply_to("}").to_string());
+        }
+
+        let Some(children) = &expression.children else {
+            self.push(SyntaxColors::text().apply_to(" />").to_string());
+            return;
+        };
+        self.push(SyntaxColors::text().apply_to(">").to_string());
+        for child in children {
+            match child.kind() {
+                ASTExpressionKind::Element(_) | ASTExpressionKind::Text(_) => {
+                    self.visit_expression(child)
+                }
+                _ => {
+                    self.push(SyntaxColors::text().apply_to("{").to_string());
+                    self.visit_expression(child);
+                    self.push(SyntaxColors::text().apply_to("}").to_string());
+                }
+            }
+        }
+        self.push(
+            SyntaxColors::text()
+                .apply_to(format!("</{}>", expression.name()))
+                .to_string(),
+        );
+    }
+
+    fn visit_text_expression(&mut self, expression: &ASTTextExpression) {
+        self.push(
+            SyntaxColors::text()
+                .apply_to(expression.value())
+                .to_string(),
+        );
+    }
+
+    fn visit_closure_expression(&mut self, expression: &ASTClosureExpression) {
+        self.push(SyntaxColors::text().apply_to("|").to_string());
+        for (index, parameter) in expression.parameters.iter().enumerate() {
+            if index > 0 {
+                self.push(SyntaxColors::text().apply_to(", ").to_string());
+            }
+            match parameter {
+                ASTClosureParameter::Binding(parameter) => {
+                    self.push(
+                        SyntaxColors::variable()
+                            .apply_to(parameter.identifier())
+                            .to_string(),
+                    );
+                    if let Some(ty) = &parameter.ty {
+                        self.push(SyntaxColors::text().apply_to(": ").to_string());
+                        self.visit_type(ty);
+                    }
+                }
+                ASTClosureParameter::Tuple(items) => {
+                    let items: Vec<&str> = items.iter().map(|item| item.text()).collect();
+                    self.push(SyntaxColors::text().apply_to("(").to_string());
+                    self.push(
+                        SyntaxColors::variable()
+                            .apply_to(items.join(", "))
+                            .to_string(),
+                    );
+                    self.push(SyntaxColors::text().apply_to(")").to_string());
+                }
+            }
+        }
+        self.push(SyntaxColors::text().apply_to("|").to_string());
+        self.push_whitespace();
+        self.visit_expression(&expression.body);
     }
 
     fn visit_binary_expression(&mut self, bin_expr: &ASTBinaryExpression) {
@@ -64,7 +535,7 @@ impl ASTVisitor for ASTPrinter {
         self.push_whitespace();
         self.push(
             SyntaxColors::text()
-                .apply_to(bin_expr.operator.token.span.clone_text())
+                .apply_to(bin_expr.operator.token.text().to_string())
                 .to_string(),
         );
         self.push_whitespace();
@@ -84,4 +555,108 @@ impl ASTVisitor for ASTPrinter {
                 .to_string(),
         );
     }
+
+    fn visit_type_alias_statement(&mut self, type_alias: &ASTTypeAliasStatement) {
+        self.push_visibility(&type_alias.visibility);
+        self.push(SyntaxColors::keyword().apply_to("type").to_string());
+        self.push_whitespace();
+        self.push(
+            SyntaxColors::text()
+                .apply_to(type_alias.identifier())
+                .to_string(),
+        );
+        self.push(SyntaxColors::text().apply_to(" = ").to_string());
+        self.visit_type(&type_alias.ty);
+        self.push(SyntaxColors::text().apply_to(";").to_string());
+    }
+
+    fn visit_error_type(&mut self, span: &TextSpan) {
+        self.push(
+            SyntaxColors::error()
+                .apply_to(self.source.slice(*span))
+                .to_string(),
+        );
+    }
+
+    fn visit_named_type(&mut self, identifier: &Token) {
+        self.push(
+            SyntaxColors::text()
+                .apply_to(identifier.text().to_string())
+                .to_string(),
+        );
+    }
+
+    fn visit_literal_type(&mut self, literal: &Token) {
+        self.push(
+            SyntaxColors::get_token_color(&literal.kind)
+                .apply_to(literal.text().to_string())
+                .to_string(),
+        );
+    }
+
+    fn visit_trusted_type(&mut self, trusted: &ASTTrustedType) {
+        self.push(SyntaxColors::keyword().apply_to("trusted ").to_string());
+        self.push_nested_type(&trusted.inner);
+    }
+
+    fn visit_union_type(&mut self, union: &ASTUnionType) {
+        for (index, member) in union.members().iter().enumerate() {
+            if index > 0 {
+                self.push(SyntaxColors::text().apply_to(" | ").to_string());
+            }
+            self.push_nested_type(member);
+        }
+    }
+
+    fn visit_optional_type(&mut self, optional: &ASTOptionalType) {
+        self.push_nested_type(&optional.inner);
+        self.push(SyntaxColors::text().apply_to("?").to_string());
+    }
+
+    fn visit_array_type(&mut self, array: &ASTArrayType) {
+        self.push_nested_type(&array.element);
+        self.push(SyntaxColors::text().apply_to("[]").to_string());
+    }
+
+    fn visit_object_type(&mut self, object: &ASTObjectType) {
+        self.push(SyntaxColors::text().apply_to("{ ").to_string());
+        for (index, property) in object.properties().iter().enumerate() {
+            if index > 0 {
+                self.push(SyntaxColors::text().apply_to(", ").to_string());
+            }
+            self.push(
+                SyntaxColors::text()
+                    .apply_to(property.identifier())
+                    .to_string(),
+            );
+            if property.is_optional() {
+                self.push(SyntaxColors::text().apply_to("?").to_string());
+            }
+            self.push(SyntaxColors::text().apply_to(": ").to_string());
+            self.visit_type(property.ty());
+        }
+        self.push(SyntaxColors::text().apply_to(" }").to_string());
+    }
+
+    fn visit_function_type(&mut self, function: &ASTFunctionType) {
+        self.push_parameters(&function.parameters);
+        self.push(SyntaxColors::text().apply_to(" => ").to_string());
+        self.visit_type(&function.return_type);
+    }
+
+    fn visit_generic_type(&mut self, generic: &ASTGenericType) {
+        self.push(
+            SyntaxColors::text()
+                .apply_to(generic.identifier())
+                .to_string(),
+        );
+        self.push(SyntaxColors::text().apply_to("<").to_string());
+        for (index, argument) in generic.arguments.iter().enumerate() {
+            if index > 0 {
+                self.push(SyntaxColors::text().apply_to(", ").to_string());
+            }
+            self.visit_type(argument);
+        }
+        self.push(SyntaxColors::text().apply_to(">").to_string());
+    }
 }
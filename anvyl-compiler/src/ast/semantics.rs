@@ -0,0 +1,432 @@
+use std::collections::HashMap;
+
+use crate::ast::evaluator::Value;
+use crate::prelude::{TextSpan, Token};
+
+/// Identifies a statement, expression or type node, assigned by the parser in source order.
+///
+/// IDs are stable for a given source text, so semantic facts can be stored in side tables
+/// instead of on the nodes themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    pub fn new(index: usize) -> Self {
+        Self(index)
+    }
+
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// Semantic facts about nodes, keyed by their `NodeId`.
+#[derive(Debug)]
+pub struct SideTable<T> {
+    entries: HashMap<NodeId, T>,
+}
+
+impl<T> Default for SideTable<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SideTable<T> {
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, id: NodeId, value: T) {
+        self.entries.insert(id, value);
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&T> {
+        self.entries.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut T> {
+        self.entries.get_mut(&id)
+    }
+
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.entries.contains_key(&id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, &T)> {
+        self.entries.iter().map(|(id, value)| (*id, value))
+    }
+}
+
+/// An interned identifier, comparable and hashable without touching the string itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Name(u32);
+
+/// Stores each distinct identifier once, handing out a `Name` for it.
+#[derive(Debug, Default)]
+pub struct Interner {
+    names: HashMap<Box<str>, Name>,
+    strings: Vec<Box<str>>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn intern(&mut self, string: &str) -> Name {
+        if let Some(name) = self.names.get(string) {
+            return *name;
+        }
+        let name = Name(self.strings.len() as u32);
+        self.strings.push(string.into());
+        self.names.insert(string.into(), name);
+        name
+    }
+
+    /// Returns the `Name` of `string` if it has been interned, without interning it.
+    pub fn get(&self, string: &str) -> Option<Name> {
+        self.names.get(string).copied()
+    }
+
+    pub fn resolve(&self, name: Name) -> &str {
+        &self.strings[name.0 as usize]
+    }
+}
+
+/// Identifies a declared value within a `SemanticModel`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SymbolId(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Variable,
+    Parameter,
+    LoopVariable,
+    Component,
+    Callback,
+    /// An item brought in by name from another module, e.g. `AuthContext`.
+    Import,
+}
+
+/// A declared value, e.g. a `let` binding or a component parameter.
+#[derive(Debug)]
+pub struct Symbol {
+    pub(crate) name: String,
+    pub(crate) kind: SymbolKind,
+    /// The span of the declared identifier.
+    pub(crate) span: TextSpan,
+    pub(crate) mutable: bool,
+}
+
+impl Symbol {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> SymbolKind {
+        self.kind
+    }
+
+    pub fn span(&self) -> &TextSpan {
+        &self.span
+    }
+
+    pub fn is_mutable(&self) -> bool {
+        self.mutable
+    }
+}
+
+/// Identifies a scope within a `SemanticModel`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScopeId(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeKind {
+    /// The top level of a file.
+    Module,
+    /// A component's props and body.
+    Component,
+    /// A callback's or closure's parameters and body.
+    Function,
+    /// A block nested in another scope, e.g. the body of a `for` loop.
+    Block,
+    /// A loop, holding its loop variable if it has one.
+    Loop,
+}
+
+/// A region of the program in which declarations are visible.
+#[derive(Debug)]
+pub struct Scope {
+    pub(crate) kind: ScopeKind,
+    pub(crate) parent: Option<ScopeId>,
+    /// The source range the scope covers, `None` for the module scope which covers everything.
+    pub(crate) span: Option<TextSpan>,
+    pub(crate) symbols: HashMap<Name, SymbolId>,
+}
+
+impl Scope {
+    pub fn kind(&self) -> ScopeKind {
+        self.kind
+    }
+
+    pub fn parent(&self) -> Option<ScopeId> {
+        self.parent
+    }
+
+    pub fn span(&self) -> Option<&TextSpan> {
+        self.span.as_ref()
+    }
+
+    pub fn symbols(&self) -> impl Iterator<Item = SymbolId> {
+        self.symbols.values().copied()
+    }
+
+    fn contains(&self, position: usize) -> bool {
+        match &self.span {
+            Some(span) => span.start() <= position && position < span.end(),
+            None => true,
+        }
+    }
+}
+
+/// The type of an expression, as far as it can be inferred from its literals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InferredType {
+    Number,
+    String,
+    Array(Box<InferredType>),
+    Unknown,
+}
+
+/// Everything the `SymbolChecker` learns about a program.
+#[derive(Debug, Default)]
+pub struct SemanticModel {
+    symbols: Vec<Symbol>,
+    /// The symbols each node declares, in declaration order. Statements declare their
+    /// bindings, components and callbacks their own name followed by their parameters, and
+    /// closures record their parameters against their body.
+    declarations: SideTable<Vec<SymbolId>>,
+    /// Every scope, each listed after the scope enclosing it.
+    pub(crate) scopes: Vec<Scope>,
+    /// Identifiers of every declared symbol.
+    pub(crate) names: Interner,
+    /// The symbol each variable expression refers to.
+    pub(crate) resolved: SideTable<SymbolId>,
+    pub(crate) types: SideTable<InferredType>,
+    /// Values of expressions which can be computed at compile time, e.g. `60 * 60`.
+    pub(crate) constants: SideTable<Value>,
+    /// Literal union types referenced in value position, holding the literal tokens the
+    /// type should be materialised into.
+    pub(crate) materialized: SideTable<Vec<Token>>,
+    /// Names declared nowhere in the file, which are left to the glob imports to provide,
+    /// in order of first use.
+    pub(crate) glob_references: Vec<String>,
+}
+
+impl SemanticModel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a symbol declared by the node `declaration`.
+    pub(crate) fn declare(&mut self, declaration: NodeId, symbol: Symbol) -> SymbolId {
+        let id = SymbolId(self.symbols.len());
+        self.symbols.push(symbol);
+        match self.declarations.get_mut(declaration) {
+            Some(symbols) => symbols.push(id),
+            None => self.declarations.insert(declaration, vec![id]),
+        }
+        id
+    }
+
+    /// The symbols declared by a node, in declaration order.
+    pub fn declared_symbols(&self, declaration: NodeId) -> &[SymbolId] {
+        self.declarations
+            .get(declaration)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// The symbol `name` declared by a node, e.g. the binding of a `let` statement.
+    ///
+    /// Parameters shadow the name of the component or callback declaring them.
+    pub fn declared_symbol(&self, declaration: NodeId, name: &str) -> Option<SymbolId> {
+        self.declared_symbols(declaration)
+            .iter()
+            .rev()
+            .find(|symbol| self.symbol(**symbol).name == name)
+            .copied()
+    }
+
+    pub fn symbol(&self, id: SymbolId) -> &Symbol {
+        &self.symbols[id.0]
+    }
+
+    pub fn names(&self) -> &Interner {
+        &self.names
+    }
+
+    pub fn symbols(&self) -> impl Iterator<Item = (SymbolId, &Symbol)> {
+        self.symbols
+            .iter()
+            .enumerate()
+            .map(|(index, symbol)| (SymbolId(index), symbol))
+    }
+
+    pub(crate) fn add_scope(
+        &mut self,
+        kind: ScopeKind,
+        parent: Option<ScopeId>,
+        span: Option<TextSpan>,
+    ) -> ScopeId {
+        self.scopes.push(Scope {
+            kind,
+            parent,
+            span,
+            symbols: HashMap::new(),
+        });
+        ScopeId(self.scopes.len() - 1)
+    }
+
+    pub fn scope(&self, id: ScopeId) -> &Scope {
+        &self.scopes[id.0]
+    }
+
+    pub(crate) fn scope_mut(&mut self, id: ScopeId) -> &mut Scope {
+        &mut self.scopes[id.0]
+    }
+
+    /// The innermost scope containing the source `position`.
+    pub fn scope_at(&self, position: usize) -> Option<ScopeId> {
+        // Nested scopes come after the scopes enclosing them.
+        self.scopes
+            .iter()
+            .rposition(|scope| scope.contains(position))
+            .map(ScopeId)
+    }
+
+    /// The symbols visible at the source `position`, innermost first, excluding shadowed ones.
+    pub fn visible_symbols(&self, position: usize) -> Vec<&Symbol> {
+        let mut visible: Vec<&Symbol> = Vec::new();
+        let mut scope = self.scope_at(position);
+        while let Some(id) = scope {
+            let mut symbols: Vec<&Symbol> = self
+                .scope(id)
+                .symbols()
+                .map(|symbol| self.symbol(symbol))
+                .filter(|symbol| symbol.span.start() < position)
+                .filter(|symbol| !visible.iter().any(|inner| inner.name == symbol.name))
+                .collect();
+            symbols.sort_by_key(|symbol| symbol.span.start());
+            visible.extend(symbols);
+            scope = self.scope(id).parent;
+        }
+        visible
+    }
+
+    pub fn resolved_symbol(&self, id: NodeId) -> Option<&Symbol> {
+        self.resolved.get(id).map(|symbol| self.symbol(*symbol))
+    }
+
+    pub fn inferred_type(&self, id: NodeId) -> Option<&InferredType> {
+        self.types.get(id)
+    }
+
+    pub fn constant_value(&self, id: NodeId) -> Option<&Value> {
+        self.constants.get(id)
+    }
+
+    pub fn materialized_type(&self, id: NodeId) -> Option<&[Token]> {
+        self.materialized.get(id).map(Vec::as_slice)
+    }
+
+    pub fn glob_references(&self) -> &[String] {
+        &self.glob_references
+    }
+
+    /// Whether `name` is left to the glob imports to provide.
+    pub fn is_glob_reference(&self, name: &str) -> bool {
+        self.glob_references
+            .iter()
+            .any(|reference| reference == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::symbols::SymbolChecker;
+    use crate::prelude::*;
+    use crate::testing::parse;
+
+    const SOURCE: &str = "component Card(title: string) {
+    let a = 1;
+    for x of [a] {
+        let title = x;
+        let inner = title;
+    }
+    return <p>{title}</p>
+}
+";
+
+    /// The kind of the scope at `needle`, and the names and kinds of the symbols visible there.
+    fn scope_at(model: &SemanticModel, needle: &str) -> (ScopeKind, Vec<(String, SymbolKind)>) {
+        let position = SOURCE.find(needle).unwrap();
+        let scope = model.scope_at(position).unwrap();
+        let symbols = model
+            .visible_symbols(position)
+            .iter()
+            .map(|symbol| (symbol.name().to_string(), symbol.kind()))
+            .collect();
+        (model.scope(scope).kind(), symbols)
+    }
+
+    #[test]
+    fn finds_the_innermost_scope_and_the_symbols_visible_at_a_position() {
+        let ast = parse(SOURCE);
+        let mut checker = SymbolChecker::new(DiagnosticsBag::new_ref_cell());
+        checker.check(&ast);
+        let model = checker.model();
+
+        let name = |name: &str, kind| (name.to_string(), kind);
+        // Only symbols declared before the position are visible.
+        assert_eq!(
+            scope_at(model, "let a"),
+            (
+                ScopeKind::Component,
+                vec![
+                    name("title", SymbolKind::Parameter),
+                    name("Card", SymbolKind::Component),
+                ]
+            )
+        );
+        // `title` in the loop shadows the parameter.
+        assert_eq!(
+            scope_at(model, "let inner"),
+            (
+                ScopeKind::Block,
+                vec![
+                    name("title", SymbolKind::Variable),
+                    name("x", SymbolKind::LoopVariable),
+                    name("a", SymbolKind::Variable),
+                    name("Card", SymbolKind::Component),
+                ]
+            )
+        );
+        assert_eq!(
+            scope_at(model, "return"),
+            (
+                ScopeKind::Component,
+                vec![
+                    name("title", SymbolKind::Parameter),
+                    name("a", SymbolKind::Variable),
+                    name("Card", SymbolKind::Component),
+                ]
+            )
+        );
+        let end = model.scope_at(SOURCE.len()).unwrap();
+        assert_eq!(model.scope(end).kind(), ScopeKind::Module);
+    }
+}
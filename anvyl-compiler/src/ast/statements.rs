@@ -1,48 +1,820 @@
+use bumpalo::boxed::Box;
+
 use crate::prelude::*;
 
 #[derive(Debug)]
-pub enum ASTStatementKind {
-    Expression(ASTExpression),
-    LetStatement(ASTLetStatement),
+pub enum ASTStatementKind<'a> {
+    Expression(Box<'a, ASTExpression<'a>>),
+    LetStatement(Box<'a, ASTLetStatement<'a>>),
+    TypeAlias(Box<'a, ASTTypeAliasStatement<'a>>),
+    Component(Box<'a, ASTComponentStatement<'a>>),
+    Callback(Box<'a, ASTCallbackStatement<'a>>),
+    For(Box<'a, ASTForStatement<'a>>),
+    While(Box<'a, ASTWhileStatement<'a>>),
+    Break(Box<'a, ASTBreakStatement>),
+    Continue(Box<'a, ASTContinueStatement>),
+    Return(Box<'a, ASTReturnStatement<'a>>),
+    Impl(Box<'a, ASTImplStatement<'a>>),
+    Import(Box<'a, ASTImportStatement>),
+    Style(Box<'a, ASTStyleStatement>),
 }
 
 #[derive(Debug)]
-pub struct ASTStatement {
-    pub(crate) kind: ASTStatementKind,
+pub struct ASTStatement<'a> {
+    pub(crate) id: NodeId,
+    pub(crate) kind: ASTStatementKind<'a>,
+    pub(crate) span: TextSpan,
 }
 
-impl ASTStatement {
-    pub fn new(kind: ASTStatementKind) -> Self {
-        Self { kind }
+impl<'a> ASTStatement<'a> {
+    pub fn new(kind: ASTStatementKind<'a>, id: NodeId, span: TextSpan) -> Self {
+        Self { id, kind, span }
     }
 
-    pub fn expression(expr: ASTExpression) -> Self {
-        ASTStatement::new(ASTStatementKind::Expression(expr))
+    pub fn expression(
+        expr: ASTExpression<'a>,
+        id: NodeId,
+        span: TextSpan,
+        arena: &'a ASTArena,
+    ) -> Self {
+        ASTStatement::new(
+            ASTStatementKind::Expression(Box::new_in(expr, arena)),
+            id,
+            span,
+        )
     }
 
-    pub fn let_statement(identifier: Token, initializer: ASTExpression) -> Self {
-        ASTStatement::new(ASTStatementKind::LetStatement(ASTLetStatement::new(
-            identifier,
-            initializer,
-        )))
+    pub fn let_statement(
+        let_statement: ASTLetStatement<'a>,
+        id: NodeId,
+        span: TextSpan,
+        arena: &'a ASTArena,
+    ) -> Self {
+        ASTStatement::new(
+            ASTStatementKind::LetStatement(Box::new_in(let_statement, arena)),
+            id,
+            span,
+        )
+    }
+
+    pub fn type_alias(
+        visibility: ASTVisibility,
+        identifier: Token,
+        ty: ASTType<'a>,
+        id: NodeId,
+        span: TextSpan,
+        arena: &'a ASTArena,
+    ) -> Self {
+        ASTStatement::new(
+            ASTStatementKind::TypeAlias(Box::new_in(
+                ASTTypeAliasStatement::new(visibility, identifier, ty),
+                arena,
+            )),
+            id,
+            span,
+        )
+    }
+
+    pub fn component(
+        component: ASTComponentStatement<'a>,
+        id: NodeId,
+        span: TextSpan,
+        arena: &'a ASTArena,
+    ) -> Self {
+        ASTStatement::new(
+            ASTStatementKind::Component(Box::new_in(component, arena)),
+            id,
+            span,
+        )
+    }
+
+    pub fn impl_statement(
+        identifier: Token,
+        generics: Vec<ASTGenericParameter<'a>>,
+        members: Vec<ASTPropMember<'a>>,
+        id: NodeId,
+        span: TextSpan,
+        arena: &'a ASTArena,
+    ) -> Self {
+        ASTStatement::new(
+            ASTStatementKind::Impl(Box::new_in(
+                ASTImplStatement {
+                    identifier,
+                    generics,
+                    members,
+                },
+                arena,
+            )),
+            id,
+            span,
+        )
+    }
+
+    pub fn callback(
+        identifier: Token,
+        parameters: Vec<ASTParameter<'a>>,
+        body: Vec<ASTStatement<'a>>,
+        id: NodeId,
+        span: TextSpan,
+        arena: &'a ASTArena,
+    ) -> Self {
+        ASTStatement::new(
+            ASTStatementKind::Callback(Box::new_in(
+                ASTCallbackStatement {
+                    identifier,
+                    parameters,
+                    body,
+                },
+                arena,
+            )),
+            id,
+            span,
+        )
+    }
+
+    pub fn for_statement(
+        for_statement: ASTForStatement<'a>,
+        id: NodeId,
+        span: TextSpan,
+        arena: &'a ASTArena,
+    ) -> Self {
+        ASTStatement::new(
+            ASTStatementKind::For(Box::new_in(for_statement, arena)),
+            id,
+            span,
+        )
+    }
+
+    pub fn while_statement(
+        label: Option<Token>,
+        condition: ASTExpression<'a>,
+        body: Vec<ASTStatement<'a>>,
+        id: NodeId,
+        span: TextSpan,
+        arena: &'a ASTArena,
+    ) -> Self {
+        ASTStatement::new(
+            ASTStatementKind::While(Box::new_in(
+                ASTWhileStatement {
+                    label,
+                    condition,
+                    body,
+                },
+                arena,
+            )),
+            id,
+            span,
+        )
     }
 
-    pub fn kind(&self) -> &ASTStatementKind {
+    pub fn break_statement(
+        keyword: Token,
+        label: Option<Token>,
+        id: NodeId,
+        span: TextSpan,
+        arena: &'a ASTArena,
+    ) -> Self {
+        ASTStatement::new(
+            ASTStatementKind::Break(Box::new_in(ASTBreakStatement { keyword, label }, arena)),
+            id,
+            span,
+        )
+    }
+
+    pub fn continue_statement(
+        keyword: Token,
+        label: Option<Token>,
+        id: NodeId,
+        span: TextSpan,
+        arena: &'a ASTArena,
+    ) -> Self {
+        ASTStatement::new(
+            ASTStatementKind::Continue(Box::new_in(ASTContinueStatement { keyword, label }, arena)),
+            id,
+            span,
+        )
+    }
+
+    pub fn return_statement(
+        keyword: Token,
+        value: Option<ASTExpression<'a>>,
+        id: NodeId,
+        span: TextSpan,
+        arena: &'a ASTArena,
+    ) -> Self {
+        ASTStatement::new(
+            ASTStatementKind::Return(Box::new_in(ASTReturnStatement { keyword, value }, arena)),
+            id,
+            span,
+        )
+    }
+
+    pub fn style(
+        keyword: Token,
+        css: String,
+        id: NodeId,
+        span: TextSpan,
+        arena: &'a ASTArena,
+    ) -> Self {
+        ASTStatement::new(
+            ASTStatementKind::Style(Box::new_in(ASTStyleStatement { keyword, css }, arena)),
+            id,
+            span,
+        )
+    }
+
+    pub fn import_statement(
+        keyword: Token,
+        path: Vec<Token>,
+        items: ASTImportItems,
+        id: NodeId,
+        span: TextSpan,
+        arena: &'a ASTArena,
+    ) -> Self {
+        ASTStatement::new(
+            ASTStatementKind::Import(Box::new_in(
+                ASTImportStatement {
+                    keyword,
+                    path,
+                    items,
+                },
+                arena,
+            )),
+            id,
+            span,
+        )
+    }
+
+    pub fn kind(&self) -> &ASTStatementKind<'a> {
         &self.kind
     }
+
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    /// The source range of the whole node.
+    pub fn span(&self) -> &TextSpan {
+        &self.span
+    }
+}
+
+/// A `let` or `const` declaration.
+#[derive(Debug)]
+pub struct ASTLetStatement<'a> {
+    pub(crate) visibility: ASTVisibility,
+    pub(crate) keyword: Token,
+    pub(crate) pattern: ASTPattern,
+    pub(crate) type_annotation: Option<ASTType<'a>>,
+    pub(crate) initializer: ASTExpression<'a>,
+}
+
+impl<'a> ASTLetStatement<'a> {
+    pub fn new(
+        visibility: ASTVisibility,
+        keyword: Token,
+        pattern: ASTPattern,
+        type_annotation: Option<ASTType<'a>>,
+        initializer: ASTExpression<'a>,
+    ) -> Self {
+        Self {
+            visibility,
+            keyword,
+            pattern,
+            type_annotation,
+            initializer,
+        }
+    }
+
+    pub fn visibility(&self) -> &ASTVisibility {
+        &self.visibility
+    }
+
+    pub fn pattern(&self) -> &ASTPattern {
+        &self.pattern
+    }
+
+    /// The declared identifier, unless the declaration destructures its initializer.
+    pub fn identifier(&self) -> Option<&Token> {
+        match &self.pattern {
+            ASTPattern::Identifier(identifier) => Some(identifier),
+            ASTPattern::Object(_) => None,
+        }
+    }
+
+    pub fn initializer(&self) -> &ASTExpression<'a> {
+        &self.initializer
+    }
+
+    pub fn is_const(&self) -> bool {
+        self.keyword.kind == TokenKind::Const
+    }
+
+    /// Returns the `stateful(...)` call initialising this binding, if any.
+    pub fn stateful_initializer(&self) -> Option<&ASTCallExpression<'a>> {
+        match self.initializer.kind() {
+            ASTExpressionKind::Call(call) if call.callee_identifier() == Some("stateful") => {
+                Some(call)
+            }
+            _ => None,
+        }
+    }
 }
 
+/// The bindings introduced by a `let` or `const` declaration.
 #[derive(Debug)]
-pub struct ASTLetStatement {
+pub enum ASTPattern {
+    /// A single binding, e.g. `count`.
+    Identifier(Token),
+    /// Properties destructured from an object, e.g. `{ user, room }`.
+    Object(Vec<Token>),
+}
+
+impl ASTPattern {
+    /// Every identifier the pattern declares, in source order.
+    pub fn identifiers(&self) -> &[Token] {
+        match self {
+            ASTPattern::Identifier(identifier) => std::slice::from_ref(identifier),
+            ASTPattern::Object(properties) => properties,
+        }
+    }
+}
+
+/// A `type <identifier> = <type>;` declaration.
+#[derive(Debug)]
+pub struct ASTTypeAliasStatement<'a> {
+    pub(crate) visibility: ASTVisibility,
     pub(crate) identifier: Token,
-    pub(crate) initializer: ASTExpression,
+    pub(crate) ty: ASTType<'a>,
 }
 
-impl ASTLetStatement {
-    pub fn new(identifier: Token, initializer: ASTExpression) -> Self {
+impl<'a> ASTTypeAliasStatement<'a> {
+    pub fn new(visibility: ASTVisibility, identifier: Token, ty: ASTType<'a>) -> Self {
         Self {
+            visibility,
             identifier,
-            initializer,
+            ty,
+        }
+    }
+
+    pub fn visibility(&self) -> &ASTVisibility {
+        &self.visibility
+    }
+
+    pub fn identifier(&self) -> &str {
+        &self.identifier.text
+    }
+}
+
+/// A `<identifier>[?][: <type>][ = <default>]` parameter of a component, callback or
+/// function type.
+#[derive(Debug)]
+pub struct ASTParameter<'a> {
+    pub(crate) identifier: Token,
+    pub(crate) optional: bool,
+    pub(crate) ty: Option<ASTType<'a>>,
+    pub(crate) default: Option<ASTExpression<'a>>,
+    pub(crate) span: TextSpan,
+}
+
+impl<'a> ASTParameter<'a> {
+    pub fn new(
+        identifier: Token,
+        optional: bool,
+        ty: Option<ASTType<'a>>,
+        default: Option<ASTExpression<'a>>,
+        span: TextSpan,
+    ) -> Self {
+        Self {
+            identifier,
+            optional,
+            ty,
+            default,
+            span,
+        }
+    }
+
+    pub fn identifier(&self) -> &str {
+        &self.identifier.text
+    }
+
+    /// Whether the argument may be left out, either because the parameter is marked
+    /// with `?` or because it has a default value.
+    pub fn is_optional(&self) -> bool {
+        self.optional || self.default.is_some()
+    }
+
+    pub fn default(&self) -> Option<&ASTExpression<'a>> {
+        self.default.as_ref()
+    }
+
+    pub fn span(&self) -> &TextSpan {
+        &self.span
+    }
+}
+
+/// A `[<modifiers>] component <identifier>[<generics>](<parameters>) { ... }` declaration.
+#[derive(Debug)]
+pub struct ASTComponentStatement<'a> {
+    pub(crate) modifiers: Vec<ASTModifier>,
+    pub(crate) visibility: ASTVisibility,
+    pub(crate) identifier: Token,
+    pub(crate) generics: Vec<ASTGenericParameter<'a>>,
+    pub(crate) parameters: Vec<ASTParameter<'a>>,
+    pub(crate) body: Vec<ASTStatement<'a>>,
+}
+
+impl<'a> ASTComponentStatement<'a> {
+    pub fn new(
+        modifiers: Vec<ASTModifier>,
+        visibility: ASTVisibility,
+        identifier: Token,
+        generics: Vec<ASTGenericParameter<'a>>,
+        parameters: Vec<ASTParameter<'a>>,
+        body: Vec<ASTStatement<'a>>,
+    ) -> Self {
+        Self {
+            modifiers,
+            visibility,
+            identifier,
+            generics,
+            parameters,
+            body,
+        }
+    }
+
+    pub fn identifier(&self) -> &str {
+        &self.identifier.text
+    }
+
+    pub fn modifiers(&self) -> &[ASTModifier] {
+        &self.modifiers
+    }
+
+    pub fn visibility(&self) -> &ASTVisibility {
+        &self.visibility
+    }
+
+    /// The first modifier named `name`, e.g. `rename_props`.
+    pub fn modifier(&self, name: &str) -> Option<&ASTModifier> {
+        self.modifiers
+            .iter()
+            .find(|modifier| modifier.identifier() == name)
+    }
+
+    /// The component named by `extend_props(<component>)`, whose props this one inherits.
+    pub fn extended_component(&self) -> Option<&Token> {
+        match self
+            .modifier("extend_props")
+            .map(|modifier| modifier.arguments())
+        {
+            Some([ASTModifierArgument::Name(name)]) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// The props made controllable with `controllable(#<prop>, ...)`.
+    pub fn controlled_props(&self) -> Vec<&Token> {
+        self.modifiers
+            .iter()
+            .filter(|modifier| modifier.identifier() == "controllable")
+            .flat_map(|modifier| modifier.arguments())
+            .filter_map(|argument| match argument {
+                ASTModifierArgument::Prop(prop) => Some(prop),
+                ASTModifierArgument::Name(_) => None,
+            })
+            .collect()
+    }
+
+    /// Whether the component takes props, either of its own or inherited ones.
+    pub fn has_props(&self) -> bool {
+        !self.parameters.is_empty() || self.extended_component().is_some()
+    }
+
+    /// The name of the props interface: `<Component>Props`, unless renamed with
+    /// `rename_props(<name>)`.
+    pub fn props_name(&self) -> String {
+        match self
+            .modifier("rename_props")
+            .map(|modifier| modifier.arguments())
+        {
+            Some([ASTModifierArgument::Name(name)]) => name.text().to_string(),
+            _ => format!("{}Props", self.identifier()),
+        }
+    }
+}
+
+/// A modifier applied to the declaration below it, e.g. `rename_props(ChatViewArguments)`.
+#[derive(Debug)]
+pub struct ASTModifier {
+    pub(crate) identifier: Token,
+    pub(crate) arguments: Vec<ASTModifierArgument>,
+    pub(crate) span: TextSpan,
+}
+
+impl ASTModifier {
+    pub fn new(identifier: Token, arguments: Vec<ASTModifierArgument>, span: TextSpan) -> Self {
+        Self {
+            identifier,
+            arguments,
+            span,
+        }
+    }
+
+    pub fn identifier(&self) -> &str {
+        &self.identifier.text
+    }
+
+    pub fn arguments(&self) -> &[ASTModifierArgument] {
+        &self.arguments
+    }
+
+    pub fn span(&self) -> &TextSpan {
+        &self.span
+    }
+}
+
+#[derive(Debug)]
+pub enum ASTModifierArgument {
+    /// A plain name, e.g. `ChatViewArguments`.
+    Name(Token),
+    /// A prop of the component, e.g. `#value`.
+    Prop(Token),
+}
+
+impl ASTModifierArgument {
+    pub fn identifier(&self) -> &Token {
+        match self {
+            ASTModifierArgument::Name(identifier) | ASTModifierArgument::Prop(identifier) => {
+                identifier
+            }
+        }
+    }
+}
+
+/// How far a top-level declaration can be imported from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    /// Only the file declaring it, which is the default.
+    Private,
+    /// `pub(dir)`: the files of the declaring file's folder, and of the folders inside it.
+    Directory,
+    /// `pub(super)`: the files of the folder containing the declaring file's folder, and of
+    /// the folders inside it.
+    Super,
+    /// `pub`: any file.
+    Public,
+}
+
+/// The `pub`, `pub(dir)` or `pub(super)` before a top-level declaration, which may be
+/// followed by `default` to make it the default export of its file.
+#[derive(Debug, Default)]
+pub struct ASTVisibility {
+    pub(crate) keyword: Option<Token>,
+    /// `dir` or `super`, restricting which folders can import the declaration.
+    pub(crate) scope: Option<Token>,
+    pub(crate) default: Option<Token>,
+}
+
+impl ASTVisibility {
+    pub fn visibility(&self) -> Visibility {
+        match (&self.keyword, &self.scope) {
+            (None, _) => Visibility::Private,
+            (Some(_), None) => Visibility::Public,
+            (Some(_), Some(scope)) if scope.text() == "super" => Visibility::Super,
+            (Some(_), Some(_)) => Visibility::Directory,
+        }
+    }
+
+    /// Whether the declaration is a TypeScript export, which it is for any `pub`.
+    pub fn is_exported(&self) -> bool {
+        self.keyword.is_some()
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.default.is_some()
+    }
+}
+
+/// A `callback <identifier>(<parameters>) { ... }` declaration inside a component.
+#[derive(Debug)]
+pub struct ASTCallbackStatement<'a> {
+    pub(crate) identifier: Token,
+    pub(crate) parameters: Vec<ASTParameter<'a>>,
+    pub(crate) body: Vec<ASTStatement<'a>>,
+}
+
+impl<'a> ASTCallbackStatement<'a> {
+    pub fn identifier(&self) -> &str {
+        &self.identifier.text
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ASTForKind {
+    /// `for x of <iterable>` iterates over values.
+    Of,
+    /// `for k in <object>` iterates over keys.
+    In,
+}
+
+/// A `[<label>:] for [(][const|let] <identifier> of|in <iterable>[)] { ... }` loop.
+#[derive(Debug)]
+pub struct ASTForStatement<'a> {
+    pub(crate) label: Option<Token>,
+    pub(crate) keyword: Option<Token>,
+    pub(crate) identifier: Token,
+    pub(crate) kind: ASTForKind,
+    pub(crate) iterable: ASTExpression<'a>,
+    pub(crate) body: Vec<ASTStatement<'a>>,
+}
+
+impl<'a> ASTForStatement<'a> {
+    pub fn new(
+        label: Option<Token>,
+        keyword: Option<Token>,
+        identifier: Token,
+        kind: ASTForKind,
+        iterable: ASTExpression<'a>,
+        body: Vec<ASTStatement<'a>>,
+    ) -> Self {
+        Self {
+            label,
+            keyword,
+            identifier,
+            kind,
+            iterable,
+            body,
         }
     }
+
+    pub fn identifier(&self) -> &str {
+        &self.identifier.text
+    }
+
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_ref().map(|label| label.text())
+    }
+
+    /// Whether the loop variable was declared with `let`, rather than `const` or nothing.
+    pub fn is_mutable(&self) -> bool {
+        self.keyword
+            .as_ref()
+            .is_some_and(|keyword| keyword.kind == TokenKind::Let)
+    }
+}
+
+/// A `[<label>:] while <condition> { ... }` loop.
+///
+/// With no comparison operators, it runs until the condition is falsy, like a count
+/// decremented down to `0`, or until it's left through `break`.
+#[derive(Debug)]
+pub struct ASTWhileStatement<'a> {
+    pub(crate) label: Option<Token>,
+    pub(crate) condition: ASTExpression<'a>,
+    pub(crate) body: Vec<ASTStatement<'a>>,
+}
+
+impl<'a> ASTWhileStatement<'a> {
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_ref().map(|label| label.text())
+    }
+}
+
+/// A `break [<label>];` statement.
+#[derive(Debug)]
+pub struct ASTBreakStatement {
+    pub(crate) keyword: Token,
+    pub(crate) label: Option<Token>,
+}
+
+impl ASTBreakStatement {
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_ref().map(|label| label.text())
+    }
+}
+
+/// A `continue [<label>];` statement.
+#[derive(Debug)]
+pub struct ASTContinueStatement {
+    pub(crate) keyword: Token,
+    pub(crate) label: Option<Token>,
+}
+
+impl ASTContinueStatement {
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_ref().map(|label| label.text())
+    }
+}
+
+/// A `return [<value>];` statement.
+#[derive(Debug)]
+pub struct ASTReturnStatement<'a> {
+    pub(crate) keyword: Token,
+    pub(crate) value: Option<ASTExpression<'a>>,
+}
+
+impl<'a> ASTReturnStatement<'a> {
+    pub fn value(&self) -> Option<&ASTExpression<'a>> {
+        self.value.as_ref()
+    }
+}
+
+/// A prop declared in an `impl` block, e.g. `getOptionLabel?: (e: T) => string`.
+#[derive(Debug)]
+pub struct ASTPropMember<'a> {
+    pub(crate) identifier: Token,
+    pub(crate) optional: bool,
+    pub(crate) ty: ASTType<'a>,
+    pub(crate) span: TextSpan,
+}
+
+impl<'a> ASTPropMember<'a> {
+    pub fn new(identifier: Token, optional: bool, ty: ASTType<'a>, span: TextSpan) -> Self {
+        Self {
+            identifier,
+            optional,
+            ty,
+            span,
+        }
+    }
+
+    pub fn span(&self) -> &TextSpan {
+        &self.span
+    }
+
+    pub fn identifier(&self) -> &str {
+        &self.identifier.text
+    }
+
+    pub fn is_optional(&self) -> bool {
+        self.optional
+    }
+}
+
+/// An `impl <component><<generics>> { ... }` block, which redeclares props of a component
+/// when its generic arguments satisfy the constraints, e.g. `impl Autocomplete<T excludes string>`.
+#[derive(Debug)]
+pub struct ASTImplStatement<'a> {
+    pub(crate) identifier: Token,
+    pub(crate) generics: Vec<ASTGenericParameter<'a>>,
+    pub(crate) members: Vec<ASTPropMember<'a>>,
+}
+
+impl<'a> ASTImplStatement<'a> {
+    pub fn identifier(&self) -> &str {
+        &self.identifier.text
+    }
+
+    pub fn generics(&self) -> &[ASTGenericParameter<'a>] {
+        &self.generics
+    }
+
+    pub fn members(&self) -> &[ASTPropMember<'a>] {
+        &self.members
+    }
+}
+
+/// An `import <module>::<item>;`, `import <module>::{<items>};` or `import <module>::*;`
+/// statement, e.g. `import context::{AuthContext, ChatContext}`.
+#[derive(Debug)]
+pub struct ASTImportStatement {
+    pub(crate) keyword: Token,
+    /// The module the items are imported from, e.g. `context`. Empty for `import react;`.
+    pub(crate) path: Vec<Token>,
+    pub(crate) items: ASTImportItems,
+}
+
+impl ASTImportStatement {
+    /// The segments of the module path, e.g. `["react", "dom"]` for `import react::dom::*`.
+    pub fn path(&self) -> impl Iterator<Item = &str> {
+        self.path.iter().map(|segment| segment.text())
+    }
+
+    pub fn items(&self) -> &ASTImportItems {
+        &self.items
+    }
+}
+
+/// A `style { <css> }` block of a component, whose rules only apply to what the component
+/// renders.
+#[derive(Debug)]
+pub struct ASTStyleStatement {
+    pub(crate) keyword: Token,
+    /// The stylesheet between the braces, kept as written.
+    pub(crate) css: String,
+}
+
+impl ASTStyleStatement {
+    pub fn css(&self) -> &str {
+        &self.css
+    }
+}
+
+#[derive(Debug)]
+pub enum ASTImportItems {
+    /// `*`, which brings every export of the module into scope.
+    Glob(Token),
+    /// The items imported by name, e.g. `chat_controller` or `{ AuthContext, ChatContext }`.
+    Named(Vec<Token>),
 }
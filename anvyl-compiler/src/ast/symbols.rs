@@ -1,40 +1,1247 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::ast::ASTVisitor;
+use crate::ast::evaluator::Value;
 use crate::prelude::*;
 
+/// Types which are always in scope.
+const BUILTIN_TYPES: &[&str] = &[
+    "string",
+    "number",
+    "boolean",
+    "undefined",
+    "null",
+    "unknown",
+    "any",
+    "never",
+    "void",
+    "object",
+];
+
+/// Generic types which are always in scope, e.g. `Array<T>`.
+const BUILTIN_GENERIC_TYPES: &[&str] = &[
+    "Array", "Record", "Partial", "Required", "Readonly", "Pick", "Omit", "Promise",
+];
+
+/// Values which are always in scope, including compiler intrinsics such as `stateful`.
+const BUILTIN_VALUES: &[&str] = &["undefined", "null", "stateful", "validate"];
+
+/// Modifiers which may be applied to a component.
+const COMPONENT_MODIFIERS: &[&str] = &["rename_props", "extend_props", "controllable"];
+
+/// The generic parameters and props of a declared component, which `impl` blocks must match.
+struct ComponentSignature {
+    generics: Vec<String>,
+    props: Vec<String>,
+}
+
+/// Resolves names to their declarations, recording the results in a `SemanticModel`
+/// alongside the constant values and types of expressions it can infer.
 pub struct SymbolChecker {
-    symbols: HashMap<String, ()>,
+    /// The scope declarations are currently added to.
+    scope: ScopeId,
+    /// Names declared later on in each scope being visited, innermost last, so a use before
+    /// the declaration can be told apart from an undeclared variable.
+    upcoming: Vec<HashSet<String>>,
+    /// Symbols referenced at least once.
+    used: HashSet<SymbolId>,
+    /// The span of the statement being visited, for the scopes it opens.
+    statement_span: Option<TextSpan>,
+    /// The statement being visited, which the symbols it declares are recorded against.
+    statement: Option<NodeId>,
+    model: SemanticModel,
+    symbol_types: HashMap<SymbolId, InferredType>,
+    symbol_constants: HashMap<SymbolId, Value>,
+    /// Declared type aliases, along with their members when they are a finite union of literals.
+    types: HashMap<String, Option<Vec<Token>>>,
+    /// Generic parameters in scope, e.g. `T` inside `component Autocomplete<T>`.
+    generics: Vec<String>,
+    components: HashMap<String, ComponentSignature>,
+    /// How many components the checker is currently inside of.
+    component_depth: usize,
+    /// Labels of the loops enclosing the current statement, innermost last.
+    loops: Vec<Option<String>>,
+    /// Whether a `*` import was seen, which may provide any name not declared in the file.
+    glob_import: bool,
+    /// Whether a declaration was already made the `pub default` export of the file.
+    default_export: bool,
     diagnostics: DiagnosticsBagCell,
 }
 
 impl SymbolChecker {
     pub fn new(diagnostics: DiagnosticsBagCell) -> Self {
+        let mut model = SemanticModel::new();
+        let scope = model.add_scope(ScopeKind::Module, None, None);
         Self {
-            symbols: HashMap::new(),
+            scope,
+            upcoming: vec![HashSet::new()],
+            used: HashSet::new(),
+            statement_span: None,
+            statement: None,
+            model,
+            symbol_types: HashMap::new(),
+            symbol_constants: HashMap::new(),
+            types: HashMap::new(),
+            generics: Vec::new(),
+            components: HashMap::new(),
+            component_depth: 0,
+            loops: Vec::new(),
+            glob_import: false,
+            default_export: false,
             diagnostics,
         }
     }
-}
 
-impl ASTVisitor for SymbolChecker {
-    fn visit_variable_expression(&mut self, expression: &ASTVariableExpression) {
+    /// Checks a whole program.
+    ///
+    /// Unlike visiting the statements one by one, this knows about top-level declarations
+    /// before they are reached, so using them too early is reported as such.
+    pub fn check(&mut self, ast: &AST) {
+        self.upcoming[0] = Self::declared_names(&ast.statements);
+        ast.visit(self);
+    }
+
+    pub fn model(&self) -> &SemanticModel {
+        &self.model
+    }
+
+    pub fn into_model(self) -> SemanticModel {
+        self.model
+    }
+
+    fn symbol(identifier: &Token, kind: SymbolKind, mutable: bool) -> Symbol {
+        Symbol {
+            name: identifier.text().to_string(),
+            kind,
+            span: identifier.span,
+            mutable,
+        }
+    }
+
+    /// The statement being visited.
+    fn declaring_statement(&self) -> NodeId {
+        self.statement
+            .expect("declarations are only visited through their statement")
+    }
+
+    fn declare(&mut self, declaration: NodeId, symbol: Symbol) -> SymbolId {
+        let name = self.model.names.intern(&symbol.name);
+        if self.model.scope(self.scope).symbols.contains_key(&name) {
+            self.diagnostics
+                .borrow_mut()
+                .report_duplicate_declaration(symbol.name.clone(), symbol.span);
+        }
+        if let Some(upcoming) = self.upcoming.last_mut() {
+            upcoming.remove(&symbol.name);
+        }
+
+        let id = self.model.declare(declaration, symbol);
+        self.model.scope_mut(self.scope).symbols.insert(name, id);
+        id
+    }
+
+    fn lookup(&self, identifier: &str) -> Option<SymbolId> {
+        // A name which was never interned can't have been declared.
+        let identifier = self.model.names.get(identifier)?;
+        let mut scope = Some(self.scope);
+        while let Some(id) = scope {
+            let scope_binding = self.model.scope(id);
+            if let Some(symbol) = scope_binding.symbols.get(&identifier) {
+                return Some(*symbol);
+            }
+            scope = scope_binding.parent;
+        }
+        None
+    }
+
+    /// The names a list of statements declares directly.
+    fn declared_names(body: &[ASTStatement]) -> HashSet<String> {
+        body.iter()
+            .filter_map(|statement| match statement.kind() {
+                ASTStatementKind::LetStatement(let_statement) => {
+                    Some(let_statement.pattern.identifiers())
+                }
+                ASTStatementKind::Component(component) => {
+                    Some(std::slice::from_ref(&component.identifier))
+                }
+                ASTStatementKind::Callback(callback) => {
+                    Some(std::slice::from_ref(&callback.identifier))
+                }
+                ASTStatementKind::Import(import) => match &import.items {
+                    ASTImportItems::Named(items) => Some(items.as_slice()),
+                    ASTImportItems::Glob(_) => None,
+                },
+                _ => None,
+            })
+            .flatten()
+            .map(|identifier| identifier.text().to_string())
+            .collect()
+    }
+
+    /// Whether the current scope is inside a component or callback body.
+    fn in_function(&self) -> bool {
+        let mut scope = Some(self.scope);
+        while let Some(id) = scope {
+            let current = self.model.scope(id);
+            if matches!(current.kind(), ScopeKind::Component | ScopeKind::Function) {
+                return true;
+            }
+            scope = current.parent();
+        }
+        false
+    }
+
+    fn enter_scope(&mut self, kind: ScopeKind, span: Option<TextSpan>, body: &[ASTStatement]) {
+        self.scope = self.model.add_scope(kind, Some(self.scope), span);
+        self.upcoming.push(Self::declared_names(body));
+    }
+
+    /// Returns to the enclosing scope, warning about variables which were never used.
+    fn leave_scope(&mut self) {
+        let scope = self.model.scope(self.scope);
+        let mut unused: Vec<&Symbol> = scope
+            .symbols()
+            .filter(|symbol| !self.used.contains(symbol))
+            .map(|symbol| self.model.symbol(symbol))
+            .filter(|symbol| match symbol.kind {
+                SymbolKind::Variable | SymbolKind::LoopVariable => true,
+                // Props are part of a component's interface, even when it ignores them.
+                SymbolKind::Parameter => scope.kind == ScopeKind::Function,
+                SymbolKind::Component | SymbolKind::Callback | SymbolKind::Import => false,
+            })
+            .filter(|symbol| !symbol.name.starts_with('_'))
+            .collect();
+        unused.sort_by_key(|symbol| symbol.span.start());
+        let mut diagnostics = self.diagnostics.borrow_mut();
+        for symbol in unused {
+            diagnostics.report_unused_variable(symbol.name.clone(), symbol.span);
+        }
+        drop(diagnostics);
+
+        self.scope = scope.parent.expect("the module scope is never left");
+        self.upcoming.pop();
+    }
+
+    /// Visits statements in a new scope, so their declarations aren't visible afterwards.
+    ///
+    /// `declarations` are made in the new scope on behalf of the node `declaration`.
+    fn visit_scoped(
+        &mut self,
+        kind: ScopeKind,
+        span: Option<TextSpan>,
+        declaration: NodeId,
+        declarations: Vec<(Symbol, InferredType)>,
+        body: &[ASTStatement],
+    ) {
+        self.enter_scope(kind, span, body);
+        for (symbol, ty) in declarations {
+            let symbol = self.declare(declaration, symbol);
+            self.symbol_types.insert(symbol, ty);
+        }
+        for statement in body {
+            self.visit_statement(statement);
+        }
+        self.leave_scope();
+
+        // Everything following a `break`, `continue` or `return` in the same block is dead.
+        let jump = body.iter().position(|statement| {
+            matches!(
+                statement.kind(),
+                ASTStatementKind::Break(_)
+                    | ASTStatementKind::Continue(_)
+                    | ASTStatementKind::Return(_)
+            )
+        });
+        if let Some(unreachable) = jump.and_then(|index| body.get(index + 1)) {
+            self.diagnostics
+                .borrow_mut()
+                .report_unreachable_code(*unreachable.span());
+        }
+    }
+
+    /// Visits a function-like body, which can't `break` or `continue` enclosing loops.
+    fn visit_function_body(
+        &mut self,
+        kind: ScopeKind,
+        span: Option<TextSpan>,
+        declaration: NodeId,
+        parameters: &[ASTParameter],
+        body: &[ASTStatement],
+    ) {
+        let loops = std::mem::take(&mut self.loops);
+        for parameter in parameters {
+            self.visit_parameter(parameter);
+        }
+        let declarations = parameters
+            .iter()
+            .map(|parameter| {
+                let ty = match (&parameter.ty, &parameter.default) {
+                    (Some(ty), _) => Self::annotated_type(ty),
+                    (None, Some(default)) => self.inferred_type(default.id()),
+                    (None, None) => InferredType::Unknown,
+                };
+                (
+                    Self::symbol(&parameter.identifier, SymbolKind::Parameter, true),
+                    ty,
+                )
+            })
+            .collect();
+        self.visit_scoped(kind, span, declaration, declarations, body);
+        self.loops = loops;
+    }
+
+    /// Visits a closure in a scope of its own, holding its parameters.
+    fn visit_closure(&mut self, span: &TextSpan, closure: &ASTClosureExpression) {
+        let loops = std::mem::take(&mut self.loops);
+        self.enter_scope(ScopeKind::Function, Some(*span), &[]);
+        for parameter in &closure.parameters {
+            let declarations = match parameter {
+                ASTClosureParameter::Binding(parameter) => {
+                    self.visit_parameter(parameter);
+                    let ty = parameter
+                        .ty
+                        .as_ref()
+                        .map_or(InferredType::Unknown, Self::annotated_type);
+                    vec![(&parameter.identifier, ty)]
+                }
+                ASTClosureParameter::Tuple(items) => items
+                    .iter()
+                    .map(|item| (item, InferredType::Unknown))
+                    .collect(),
+            };
+            for (identifier, ty) in declarations {
+                let symbol = self.declare(
+                    closure.body.id(),
+                    Self::symbol(identifier, SymbolKind::Parameter, false),
+                );
+                self.symbol_types.insert(symbol, ty);
+            }
+        }
+        self.visit_expression(&closure.body);
+        self.leave_scope();
+        self.loops = loops;
+    }
+
+    /// Exports are only declared at the top level of a file, which has at most one default.
+    fn check_visibility(&mut self, visibility: &ASTVisibility) {
+        let Some(keyword) = &visibility.keyword else {
+            return;
+        };
+        if self.model.scope(self.scope).parent().is_some() {
+            self.diagnostics
+                .borrow_mut()
+                .report_misplaced_visibility(keyword);
+            return;
+        }
+
+        if let Some(default) = &visibility.default
+            && std::mem::replace(&mut self.default_export, true)
+        {
+            self.diagnostics
+                .borrow_mut()
+                .report_duplicate_default_export(default);
+        }
+    }
+
+    fn check_modifiers(&mut self, component: &ASTComponentStatement) {
+        let mut diagnostics = self.diagnostics.borrow_mut();
+        for (index, modifier) in component.modifiers().iter().enumerate() {
+            match modifier.identifier() {
+                "rename_props" | "extend_props" => {
+                    // Only the first one is applied, see `ASTComponentStatement::modifier`.
+                    if component.modifiers()[..index]
+                        .iter()
+                        .any(|previous| previous.identifier() == modifier.identifier())
+                    {
+                        diagnostics.report_duplicate_modifier(&modifier.identifier);
+                    }
+                    match modifier.arguments() {
+                        [ASTModifierArgument::Name(_)] => {}
+                        _ => diagnostics.report_invalid_modifier_arguments(
+                            &modifier.identifier,
+                            "a single name as its argument",
+                        ),
+                    }
+                }
+                "controllable" => {
+                    let arguments = modifier.arguments();
+                    if arguments.is_empty()
+                        || arguments
+                            .iter()
+                            .any(|argument| matches!(argument, ASTModifierArgument::Name(_)))
+                    {
+                        diagnostics.report_invalid_modifier_arguments(
+                            &modifier.identifier,
+                            "one or more '#<prop>' arguments",
+                        );
+                    }
+                    for argument in arguments {
+                        let prop = argument.identifier();
+                        if !component
+                            .parameters
+                            .iter()
+                            .any(|parameter| parameter.identifier() == prop.text())
+                        {
+                            diagnostics.report_unknown_prop(component.identifier(), prop);
+                        }
+                    }
+                }
+                _ => diagnostics.report_unknown_modifier(&modifier.identifier, COMPONENT_MODIFIERS),
+            }
+        }
+    }
+
+    /// Resolves the component named by `extend_props`, returning its props when it is
+    /// declared in this file.
+    ///
+    /// Imported components are accepted as they are, as their props aren't known.
+    fn resolve_base_component(&mut self, base: &Token) -> Vec<String> {
+        let Some(symbol) = self.lookup(base.text()) else {
+            match self.glob_import {
+                true => self.resolve_undeclared(base),
+                false => self
+                    .diagnostics
+                    .borrow_mut()
+                    .report_undeclared_component(base),
+            }
+            return Vec::new();
+        };
+        self.used.insert(symbol);
+
+        let signature = self.components.get(base.text());
+        match (&self.model.symbol(symbol).kind, signature) {
+            (SymbolKind::Import, _) => Vec::new(),
+            // A component being declared isn't in `components` yet, so can't extend itself.
+            (SymbolKind::Component, Some(signature)) => {
+                if !signature.generics.is_empty() {
+                    self.diagnostics
+                        .borrow_mut()
+                        .report_generic_base_component(base);
+                }
+                signature.props.clone()
+            }
+            _ => {
+                self.diagnostics
+                    .borrow_mut()
+                    .report_undeclared_component(base);
+                Vec::new()
+            }
+        }
+    }
+
+    fn enter_loop(&mut self, label: &Option<Token>) {
+        if let Some(label) = label
+            && self
+                .loops
+                .iter()
+                .flatten()
+                .any(|existing| existing == label.text())
+        {
+            self.diagnostics.borrow_mut().report_duplicate_label(label);
+        }
+
+        self.loops
+            .push(label.as_ref().map(|label| label.text().to_string()));
+    }
+
+    fn check_jump(&mut self, keyword: &Token, label: &Option<Token>) {
+        let mut diagnostics = self.diagnostics.borrow_mut();
+        match label {
+            _ if self.loops.is_empty() => diagnostics.report_jump_outside_loop(keyword),
+            Some(label)
+                if !self
+                    .loops
+                    .iter()
+                    .flatten()
+                    .any(|existing| existing == label.text()) =>
+            {
+                diagnostics.report_undeclared_label(label)
+            }
+            _ => {}
+        }
+    }
+
+    /// Brings generic parameters into scope, returning how many were declared.
+    fn declare_generics(&mut self, generics: &[ASTGenericParameter]) -> usize {
+        for generic in generics {
+            self.generics.push(generic.identifier.text().to_string());
+        }
+        for generic in generics {
+            self.visit_generic_parameter(generic);
+        }
+        generics.len()
+    }
+
+    fn leave_generics(&mut self, count: usize) {
+        self.generics.truncate(self.generics.len() - count);
+    }
+
+    /// Resolves a variable to the symbol it refers to, or a literal union type to
+    /// materialise in its place.
+    fn resolve(&mut self, id: NodeId, expression: &ASTVariableExpression) {
+        let identifier = expression.identifier.text();
+        if let Some(symbol) = self.lookup(identifier) {
+            self.model.resolved.insert(id, symbol);
+            self.used.insert(symbol);
+            return;
+        }
+        if BUILTIN_VALUES.contains(&identifier) {
+            return;
+        }
         if self
-            .symbols
-            .get(&expression.identifier.span.clone_text())
-            .is_none()
+            .upcoming
+            .iter()
+            .any(|upcoming| upcoming.contains(identifier))
         {
-            let mut diagnostics_binding = self.diagnostics.borrow_mut();
-            diagnostics_binding.report_undeclared_variable(
-                expression.identifier.span.clone_text(),
-                expression.identifier.span.clone(),
-            );
+            self.diagnostics
+                .borrow_mut()
+                .report_used_before_declaration(
+                    expression.identifier.text().to_string(),
+                    expression.identifier.span,
+                );
+            return;
+        }
+
+        match self.types.get(identifier) {
+            Some(Some(members)) => self.model.materialized.insert(id, members.clone()),
+            Some(None) => self.diagnostics.borrow_mut().report_type_used_as_value(
+                expression.identifier.text().to_string(),
+                expression.identifier.span,
+            ),
+            None => self.resolve_undeclared(&expression.identifier),
+        }
+    }
+
+    /// Resolves the tag of a component element, e.g. `Column` in `<Column gap=2>`.
+    ///
+    /// Lowercase tags are intrinsic elements such as `<div>`, which are never declared.
+    /// Components may be used before their declaration, as they are hoisted functions.
+    fn resolve_tag(&mut self, id: NodeId, name: &Token) {
+        let identifier = name.text();
+        if !identifier.starts_with(char::is_uppercase) {
+            return;
+        }
+        if let Some(symbol) = self.lookup(identifier) {
+            self.model.resolved.insert(id, symbol);
+            self.used.insert(symbol);
+            return;
+        }
+        if !self
+            .upcoming
+            .iter()
+            .any(|upcoming| upcoming.contains(identifier))
+        {
+            self.resolve_undeclared(name);
+        }
+    }
+
+    /// Handles a name declared nowhere in the file, which only a glob import can provide.
+    fn resolve_undeclared(&mut self, identifier: &Token) {
+        if !self.glob_import {
+            self.diagnostics
+                .borrow_mut()
+                .report_undeclared_variable(identifier.text().to_string(), identifier.span);
+            return;
+        }
+
+        let name = identifier.text();
+        if !self.model.is_glob_reference(name) {
+            self.model.glob_references.push(name.to_string());
+        }
+    }
+
+    fn inferred_type(&self, id: NodeId) -> InferredType {
+        self.model
+            .types
+            .get(id)
+            .cloned()
+            .unwrap_or(InferredType::Unknown)
+    }
+
+    /// The type of an annotation, as far as `InferredType` can describe it.
+    fn annotated_type(ty: &ASTType) -> InferredType {
+        match ty.kind() {
+            ASTTypeKind::Named(identifier) => match identifier.text() {
+                "number" => InferredType::Number,
+                "string" => InferredType::String,
+                _ => InferredType::Unknown,
+            },
+            ASTTypeKind::Literal(literal) => Self::literal_type(literal),
+            ASTTypeKind::Array(array) => {
+                InferredType::Array(Box::new(Self::annotated_type(array.element())))
+            }
+            _ => InferredType::Unknown,
+        }
+    }
+
+    fn literal_type(literal: &Token) -> InferredType {
+        match literal.kind {
+            TokenKind::Number(_) => InferredType::Number,
+            TokenKind::String(_) => InferredType::String,
+            _ => InferredType::Unknown,
+        }
+    }
+
+    /// The common type of array elements, or `Unknown` when they differ.
+    fn element_type(mut types: impl Iterator<Item = InferredType>) -> InferredType {
+        match types.next() {
+            Some(first) if types.all(|ty| ty == first) => first,
+            _ => InferredType::Unknown,
         }
     }
 
+    /// The constant value of a binary expression over constant operands.
+    ///
+    /// Only folds what gives the same result in JavaScript: sums, differences and products
+    /// that fit an `i64`, and divisions without a remainder.
+    fn fold(operator: &ASTBinaryOperatorKind, left: &Value, right: &Value) -> Option<Value> {
+        let number = |value: Option<i64>| value.map(Value::Number);
+        match (operator, left, right) {
+            (ASTBinaryOperatorKind::Add, Value::Number(left), Value::Number(right)) => {
+                number(left.checked_add(*right))
+            }
+            (ASTBinaryOperatorKind::Add, Value::String(_), _)
+            | (ASTBinaryOperatorKind::Add, _, Value::String(_)) => {
+                Some(Value::String(format!("{}{}", left, right)))
+            }
+            (ASTBinaryOperatorKind::Subtract, Value::Number(left), Value::Number(right)) => {
+                number(left.checked_sub(*right))
+            }
+            (ASTBinaryOperatorKind::Multiply, Value::Number(left), Value::Number(right)) => {
+                number(left.checked_mul(*right))
+            }
+            (ASTBinaryOperatorKind::Divide, Value::Number(left), Value::Number(right)) => {
+                match left.checked_rem(*right) {
+                    Some(0) => number(left.checked_div(*right)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Records the type and constant value of an expression, from those of its children.
+    fn infer(&mut self, expression: &ASTExpression) {
+        let id = expression.id();
+        let (ty, constant) = match expression.kind() {
+            ASTExpressionKind::Number(number) => {
+                (InferredType::Number, Some(Value::Number(number.number())))
+            }
+            ASTExpressionKind::String(string) => (
+                InferredType::String,
+                Some(Value::String(string.value().to_string())),
+            ),
+            ASTExpressionKind::Array(array) => {
+                let types = array
+                    .elements
+                    .iter()
+                    .map(|element| self.inferred_type(element.id()));
+                let element = Self::element_type(types);
+                (InferredType::Array(Box::new(element)), None)
+            }
+            ASTExpressionKind::Binary(binary) => {
+                let left = self.inferred_type(binary.left.id());
+                let right = self.inferred_type(binary.right.id());
+                let ty = match (&binary.operator.kind, left, right) {
+                    (ASTBinaryOperatorKind::Add, InferredType::String, _)
+                    | (ASTBinaryOperatorKind::Add, _, InferredType::String) => InferredType::String,
+                    (_, InferredType::Number, InferredType::Number) => InferredType::Number,
+                    _ => InferredType::Unknown,
+                };
+
+                let constant = match (
+                    self.model.constants.get(binary.left.id()),
+                    self.model.constants.get(binary.right.id()),
+                ) {
+                    (Some(left), Some(right)) => Self::fold(&binary.operator.kind, left, right),
+                    _ => None,
+                };
+                (ty, constant)
+            }
+            ASTExpressionKind::Parenthesized(parenthesized) => (
+                self.inferred_type(parenthesized.inner.id()),
+                self.model.constants.get(parenthesized.inner.id()).cloned(),
+            ),
+            ASTExpressionKind::Variable(_) => match self.model.resolved.get(id) {
+                Some(symbol) => (
+                    self.symbol_types
+                        .get(symbol)
+                        .cloned()
+                        .unwrap_or(InferredType::Unknown),
+                    self.symbol_constants.get(symbol).cloned(),
+                ),
+                None => match self.model.materialized.get(id) {
+                    Some(members) => {
+                        let element = Self::element_type(members.iter().map(Self::literal_type));
+                        (InferredType::Array(Box::new(element)), None)
+                    }
+                    None => (InferredType::Unknown, None),
+                },
+            },
+            ASTExpressionKind::Member(member) => {
+                let ty = match (self.inferred_type(member.object.id()), member.property()) {
+                    (InferredType::Array(_) | InferredType::String, "length") => {
+                        InferredType::Number
+                    }
+                    _ => InferredType::Unknown,
+                };
+                (ty, None)
+            }
+            ASTExpressionKind::Index(index) => {
+                let ty = match self.inferred_type(index.object.id()) {
+                    InferredType::Array(element) => *element,
+                    InferredType::String => InferredType::String,
+                    _ => InferredType::Unknown,
+                };
+                (ty, None)
+            }
+            ASTExpressionKind::Assignment(assignment) => {
+                (self.inferred_type(assignment.value.id()), None)
+            }
+            ASTExpressionKind::Error(_)
+            | ASTExpressionKind::Call(_)
+            | ASTExpressionKind::Element(_)
+            | ASTExpressionKind::Text(_)
+            | ASTExpressionKind::Closure(_) => (InferredType::Unknown, None),
+        };
+
+        self.model.types.insert(id, ty);
+        // Division by zero has no meaningful constant value.
+        if let Some(value) = constant.filter(|value| *value != Value::Undefined) {
+            self.model.constants.insert(id, value);
+        }
+    }
+
+    /// Resolves the literal members of a type, following type aliases.
+    ///
+    /// Returns `None` when the type is not a finite union of literal types.
+    fn literal_members(&self, ty: &ASTType) -> Option<Vec<Token>> {
+        match ty.kind() {
+            ASTTypeKind::Error(_)
+            | ASTTypeKind::Optional(_)
+            | ASTTypeKind::Array(_)
+            | ASTTypeKind::Function(_)
+            | ASTTypeKind::Generic(_)
+            | ASTTypeKind::Object(_) => None,
+            ASTTypeKind::Literal(literal) => Some(vec![literal.clone()]),
+            ASTTypeKind::Named(identifier) => self.types.get(identifier.text())?.clone(),
+            ASTTypeKind::Trusted(trusted) => self.literal_members(trusted.inner()),
+            ASTTypeKind::Union(union) => {
+                let mut members: Vec<Token> = Vec::new();
+                for member in union.members() {
+                    for literal in self.literal_members(member)? {
+                        if !members.iter().any(|existing| existing.kind == literal.kind) {
+                            members.push(literal);
+                        }
+                    }
+                }
+                Some(members)
+            }
+        }
+    }
+}
+
+impl ASTVisitor for SymbolChecker {
+    fn visit_statement(&mut self, statement: &ASTStatement) {
+        self.statement_span = Some(*statement.span());
+        self.statement = Some(statement.id());
+        self.default_visit_statement(statement);
+    }
+
+    fn visit_expression(&mut self, expression: &ASTExpression) {
+        match expression.kind() {
+            ASTExpressionKind::Variable(variable) => self.resolve(expression.id(), variable),
+            ASTExpressionKind::Element(element) => {
+                self.resolve_tag(expression.id(), &element.name);
+                self.default_visit_expression(expression);
+            }
+            ASTExpressionKind::Closure(closure) => self.visit_closure(expression.span(), closure),
+            _ => self.default_visit_expression(expression),
+        }
+        self.infer(expression);
+    }
+
     fn visit_let_statement(&mut self, let_statement: &ASTLetStatement) {
-        self.symbols
-            .insert(let_statement.identifier.span.clone_text(), ());
+        let statement = self.declaring_statement();
+        self.check_visibility(&let_statement.visibility);
+        if let (Some(default), ASTPattern::Object(_)) =
+            (&let_statement.visibility.default, &let_statement.pattern)
+        {
+            self.diagnostics
+                .borrow_mut()
+                .report_destructured_default_export(default);
+        }
+        if let Some(ty) = &let_statement.type_annotation {
+            self.visit_type(ty);
+        }
         self.visit_expression(&let_statement.initializer);
+
+        // Stateful bindings are reassigned through their setter.
+        let stateful = let_statement.stateful_initializer().is_some();
+        let mutable = !let_statement.is_const() || stateful;
+        let identifier = match &let_statement.pattern {
+            ASTPattern::Identifier(identifier) => identifier,
+            ASTPattern::Object(properties) => {
+                for property in properties {
+                    let symbol = self.declare(
+                        statement,
+                        Self::symbol(property, SymbolKind::Variable, mutable),
+                    );
+                    self.symbol_types.insert(symbol, InferredType::Unknown);
+                }
+                return;
+            }
+        };
+        let symbol = self.declare(
+            statement,
+            Self::symbol(identifier, SymbolKind::Variable, mutable),
+        );
+
+        let initializer = let_statement.initializer.id();
+        let ty = match &let_statement.type_annotation {
+            Some(ty) => Self::annotated_type(ty),
+            None if stateful => InferredType::Unknown,
+            None => self.inferred_type(initializer),
+        };
+        self.symbol_types.insert(symbol, ty);
+        if !mutable && let Some(value) = self.model.constants.get(initializer) {
+            self.symbol_constants.insert(symbol, value.clone());
+        }
+    }
+
+    fn visit_component_statement(&mut self, component: &ASTComponentStatement) {
+        let span = self.statement_span.take();
+        let statement = self.declaring_statement();
+        self.declare(
+            statement,
+            Self::symbol(&component.identifier, SymbolKind::Component, false),
+        );
+        self.check_visibility(&component.visibility);
+        self.check_modifiers(component);
+        if component.has_props() {
+            let name = component.props_name();
+            if self.types.contains_key(&name) {
+                let span = match component
+                    .modifier("rename_props")
+                    .map(ASTModifier::arguments)
+                {
+                    Some([ASTModifierArgument::Name(renamed)]) => renamed.span,
+                    _ => component.identifier.span,
+                };
+                self.diagnostics
+                    .borrow_mut()
+                    .report_duplicate_props_name(&name, span);
+            }
+            self.types.insert(name, None);
+        }
+
+        let mut props: Vec<String> = component
+            .parameters
+            .iter()
+            .map(|parameter| parameter.identifier().to_string())
+            .collect();
+        if let Some(base) = component.extended_component() {
+            for prop in self.resolve_base_component(base) {
+                if !props.contains(&prop) {
+                    props.push(prop);
+                }
+            }
+        }
+        self.components.insert(
+            component.identifier.text().to_string(),
+            ComponentSignature {
+                generics: component
+                    .generics
+                    .iter()
+                    .map(|generic| generic.identifier().to_string())
+                    .collect(),
+                props,
+            },
+        );
+
+        let generics = self.declare_generics(&component.generics);
+        self.component_depth += 1;
+        self.visit_function_body(
+            ScopeKind::Component,
+            span,
+            statement,
+            &component.parameters,
+            &component.body,
+        );
+        self.component_depth -= 1;
+        self.leave_generics(generics);
+    }
+
+    fn visit_impl_statement(&mut self, impl_statement: &ASTImplStatement) {
+        let mut diagnostics = self.diagnostics.borrow_mut();
+        match self.components.get(impl_statement.identifier()) {
+            None => diagnostics.report_undeclared_component(&impl_statement.identifier),
+            Some(signature) if signature.generics.len() != impl_statement.generics.len() => {
+                diagnostics.report_generic_count_mismatch(
+                    &impl_statement.identifier,
+                    signature.generics.len(),
+                    impl_statement.generics.len(),
+                )
+            }
+            Some(signature) => {
+                for (expected, generic) in signature.generics.iter().zip(&impl_statement.generics) {
+                    if expected != generic.identifier() {
+                        diagnostics.report_generic_name_mismatch(expected, &generic.identifier);
+                    }
+                }
+                for member in &impl_statement.members {
+                    if !signature
+                        .props
+                        .iter()
+                        .any(|prop| prop == member.identifier())
+                    {
+                        diagnostics
+                            .report_unknown_prop(impl_statement.identifier(), &member.identifier);
+                    }
+                }
+            }
+        }
+        drop(diagnostics);
+
+        let generics = self.declare_generics(&impl_statement.generics);
+        for member in &impl_statement.members {
+            self.visit_type(&member.ty);
+        }
+        self.leave_generics(generics);
+    }
+
+    fn visit_callback_statement(&mut self, callback: &ASTCallbackStatement) {
+        let span = self.statement_span.take();
+        let statement = self.declaring_statement();
+        self.declare(
+            statement,
+            Self::symbol(&callback.identifier, SymbolKind::Callback, false),
+        );
+        self.visit_function_body(
+            ScopeKind::Function,
+            span,
+            statement,
+            &callback.parameters,
+            &callback.body,
+        );
+    }
+
+    fn visit_for_statement(&mut self, for_statement: &ASTForStatement) {
+        let span = self.statement_span.take();
+        let statement = self.declaring_statement();
+        self.visit_expression(&for_statement.iterable);
+
+        let ty = match (
+            &for_statement.kind,
+            self.inferred_type(for_statement.iterable.id()),
+        ) {
+            (ASTForKind::In, _) => InferredType::Number,
+            (ASTForKind::Of, InferredType::Array(element)) => *element,
+            (ASTForKind::Of, InferredType::String) => InferredType::String,
+            (ASTForKind::Of, _) => InferredType::Unknown,
+        };
+        let symbol = Self::symbol(
+            &for_statement.identifier,
+            SymbolKind::LoopVariable,
+            for_statement.is_mutable(),
+        );
+        self.enter_loop(&for_statement.label);
+        // The body is a block of its own, so it may shadow the loop variable.
+        self.enter_scope(ScopeKind::Loop, span, &[]);
+        let symbol = self.declare(statement, symbol);
+        self.symbol_types.insert(symbol, ty);
+        self.visit_scoped(
+            ScopeKind::Block,
+            span,
+            statement,
+            Vec::new(),
+            &for_statement.body,
+        );
+        self.leave_scope();
+        self.loops.pop();
+    }
+
+    fn visit_while_statement(&mut self, while_statement: &ASTWhileStatement) {
+        let span = self.statement_span.take();
+        let statement = self.declaring_statement();
+        self.visit_expression(&while_statement.condition);
+
+        self.enter_loop(&while_statement.label);
+        self.visit_scoped(
+            ScopeKind::Loop,
+            span,
+            statement,
+            Vec::new(),
+            &while_statement.body,
+        );
+        self.loops.pop();
+    }
+
+    fn visit_break_statement(&mut self, break_statement: &ASTBreakStatement) {
+        self.check_jump(&break_statement.keyword, &break_statement.label);
+    }
+
+    fn visit_continue_statement(&mut self, continue_statement: &ASTContinueStatement) {
+        self.check_jump(&continue_statement.keyword, &continue_statement.label);
+    }
+
+    fn visit_return_statement(&mut self, return_statement: &ASTReturnStatement) {
+        if let Some(value) = &return_statement.value {
+            self.visit_expression(value);
+        }
+        if !self.in_function() {
+            self.diagnostics
+                .borrow_mut()
+                .report_return_outside_function(&return_statement.keyword);
+        }
+    }
+
+    fn visit_import_statement(&mut self, import: &ASTImportStatement) {
+        if self.model.scope(self.scope).parent().is_some() {
+            self.diagnostics
+                .borrow_mut()
+                .report_misplaced_import(&import.keyword);
+        }
+
+        match &import.items {
+            ASTImportItems::Glob(_) => self.glob_import = true,
+            ASTImportItems::Named(items) => {
+                let statement = self.declaring_statement();
+                for item in items {
+                    self.declare(statement, Self::symbol(item, SymbolKind::Import, false));
+                }
+            }
+        }
+    }
+
+    fn visit_style_statement(&mut self, style: &ASTStyleStatement) {
+        if self.model.scope(self.scope).kind() != ScopeKind::Component {
+            self.diagnostics
+                .borrow_mut()
+                .report_misplaced_style(&style.keyword);
+        }
+    }
+
+    fn visit_assignment_expression(&mut self, expression: &ASTAssignmentExpression) {
+        self.visit_expression(&expression.target);
+        self.visit_expression(&expression.value);
+
+        // Members and indexes mutate the value rather than rebinding the variable.
+        let Some(variable) = expression.target_variable() else {
+            return;
+        };
+
+        if let Some(symbol) = self.model.resolved.get(expression.target.id())
+            && !self.model.symbol(*symbol).is_mutable()
+        {
+            self.diagnostics.borrow_mut().report_assignment_to_constant(
+                variable.identifier.text().to_string(),
+                variable.identifier.span,
+                self.component_depth > 0,
+            );
+        }
+    }
+
+    fn visit_type_alias_statement(&mut self, type_alias: &ASTTypeAliasStatement) {
+        self.check_visibility(&type_alias.visibility);
+        if self.types.contains_key(type_alias.identifier()) {
+            self.diagnostics
+                .borrow_mut()
+                .report_duplicate_type(&type_alias.identifier);
+        }
+        // Declared before its type is visited, an alias may refer to itself, e.g. in
+        // `type Tree = { children: Tree[] }`, which makes it no union of literal types.
+        self.types
+            .insert(type_alias.identifier.text().to_string(), None);
+        self.visit_type(&type_alias.ty);
+        let members = self.literal_members(&type_alias.ty);
+        self.types
+            .insert(type_alias.identifier.text().to_string(), members);
+    }
+
+    fn visit_named_type(&mut self, identifier: &Token) {
+        let name = identifier.text();
+        if BUILTIN_TYPES.contains(&name)
+            || self.types.contains_key(name)
+            || self.generics.iter().any(|generic| generic == name)
+        {
+            return;
+        }
+
+        self.diagnostics
+            .borrow_mut()
+            .report_undeclared_type(identifier.text().to_string(), identifier.span);
+    }
+
+    fn visit_generic_type(&mut self, generic: &ASTGenericType) {
+        let name = generic.identifier.text();
+        if !BUILTIN_GENERIC_TYPES.contains(&name) && !self.types.contains_key(name) {
+            self.diagnostics.borrow_mut().report_undeclared_type(
+                generic.identifier.text().to_string(),
+                generic.identifier.span,
+            );
+        }
+
+        for argument in &generic.arguments {
+            self.visit_type(argument);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{compile, evaluate};
+
+    fn fold(operator: ASTBinaryOperatorKind, left: i64, right: i64) -> Option<Value> {
+        SymbolChecker::fold(&operator, &Value::Number(left), &Value::Number(right))
+    }
+
+    #[test]
+    fn folds_arithmetic_within_range() {
+        assert_eq!(
+            fold(ASTBinaryOperatorKind::Add, 2, 3),
+            Some(Value::Number(5))
+        );
+        assert_eq!(
+            fold(ASTBinaryOperatorKind::Subtract, 2, 3),
+            Some(Value::Number(-1))
+        );
+        assert_eq!(
+            fold(ASTBinaryOperatorKind::Multiply, 2, 3),
+            Some(Value::Number(6))
+        );
+        assert_eq!(
+            fold(ASTBinaryOperatorKind::Divide, 8, 2),
+            Some(Value::Number(4))
+        );
+    }
+
+    #[test]
+    fn skips_overflowing_arithmetic() {
+        assert_eq!(fold(ASTBinaryOperatorKind::Add, i64::MAX, 1), None);
+        assert_eq!(fold(ASTBinaryOperatorKind::Subtract, i64::MIN, 1), None);
+        assert_eq!(
+            fold(ASTBinaryOperatorKind::Multiply, 4000000000, 4000000000),
+            None
+        );
+        assert_eq!(fold(ASTBinaryOperatorKind::Divide, i64::MIN, -1), None);
+    }
+
+    #[test]
+    fn skips_divisions_with_a_remainder() {
+        assert_eq!(fold(ASTBinaryOperatorKind::Divide, 7, 2), None);
+        assert_eq!(fold(ASTBinaryOperatorKind::Divide, 7, 0), None);
+    }
+
+    #[test]
+    fn materialises_literal_unions_used_as_values() {
+        let source = "type Field = \"date-created\" | \"date-modified\"
+type Sort = Field | 1 | \"date-created\"
+let sorts = Sort;
+let picked = \"\";
+for f of Field {
+    picked = f;
+}
+sorts";
+        let compilation = compile(source);
+        assert_eq!(compilation.diagnostics, Vec::<String>::new());
+        assert_eq!(
+            compilation.tsx.unwrap(),
+            "type Field = \"date-created\" | \"date-modified\";
+type Sort = Field | 1 | \"date-created\";
+let sorts = [\"date-created\", \"date-modified\", 1];
+let picked = \"\";
+for (const f of [\"date-created\", \"date-modified\"]) {
+  picked = f;
+}
+sorts;
+"
+        );
+        assert_eq!(
+            evaluate(source),
+            Value::Array(vec![
+                Value::String("date-created".to_string()),
+                Value::String("date-modified".to_string()),
+                Value::Number(1),
+            ])
+        );
+    }
+
+    #[test]
+    fn reports_types_that_are_not_finite_literal_unions_used_as_values() {
+        let source = "type Name = string
+type Maybe = \"a\" | Name
+let a = Name;
+let b = Maybe;
+";
+        assert_eq!(
+            compile(source).diagnostics,
+            [
+                "Type 'Name' cannot be used as a value, only finite unions of literal types can",
+                "Type 'Maybe' cannot be used as a value, only finite unions of literal types can",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_assignments_to_constants() {
+        let source = "const x = 1;
+x = 2;
+let y = 1;
+y += 1;
+for (const f of [1]) {
+    f = 2;
+}
+for (let g of [1]) {
+    g = 2;
+}
+";
+        assert_eq!(
+            compile(source).diagnostics,
+            [
+                "Cannot assign to constant 'x'",
+                "Cannot assign to constant 'f'",
+            ]
+        );
+
+        let source = "component Counter() {
+    const count = 1;
+    count += 1;
+    return <div>{count}</div>
+}";
+        assert_eq!(
+            compile(source).diagnostics,
+            [
+                "Cannot assign to constant 'count', declare it with `stateful(...)` to make it assignable"
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_duplicate_early_and_unused_declarations() {
+        let source = "component Card(title: string) {
+    let a = 1;
+    let a = 2;
+    let b = later;
+    let later = 3;
+    let unused = 4;
+    for x of [a] {
+        let title = x;
+        let inner = title;
+    }
+    return <p>{b}</p>
+}";
+        // Shadowing a declaration of an enclosing scope is no duplicate.
+        assert_eq!(
+            compile(source).diagnostics,
+            [
+                "'a' is already declared in this scope",
+                "Variable 'later' is used before its declaration",
+                "Unused variable 'inner'",
+                "Unused variable 'later'",
+                "Unused variable 'unused'",
+            ]
+        );
     }
 }
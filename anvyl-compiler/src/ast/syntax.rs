@@ -48,6 +48,7 @@ impl SyntaxColors {
 
         match token {
             TokenKind::Number(_) => Self::number(),
+            TokenKind::String(_) => Self::string(),
             _ => Self::text(),
         }
     }
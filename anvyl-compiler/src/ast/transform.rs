@@ -0,0 +1,576 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::prelude::*;
+
+/// Like `ASTVisitor`, but with mutable access to every node, so passes can rewrite the tree
+/// in place.
+///
+/// Statement lists are visited through `visit_block`, which may also insert or remove
+/// statements, e.g. to expand one statement into several.
+pub trait ASTVisitorMut<'a> {
+    fn visit_block(&mut self, statements: &mut Vec<ASTStatement<'a>>) {
+        for statement in statements.iter_mut() {
+            self.visit_statement(statement);
+        }
+    }
+
+    fn default_visit_statement(&mut self, statement: &mut ASTStatement<'a>) {
+        match &mut statement.kind {
+            ASTStatementKind::Expression(expr) => self.visit_expression(expr),
+            ASTStatementKind::LetStatement(let_stmt) => self.visit_let_statement(let_stmt),
+            ASTStatementKind::TypeAlias(type_alias) => self.visit_type_alias_statement(type_alias),
+            ASTStatementKind::Component(component) => self.visit_component_statement(component),
+            ASTStatementKind::Callback(callback) => self.visit_callback_statement(callback),
+            ASTStatementKind::For(for_statement) => self.visit_for_statement(for_statement),
+            ASTStatementKind::While(while_statement) => self.visit_while_statement(while_statement),
+            ASTStatementKind::Break(break_statement) => self.visit_break_statement(break_statement),
+            ASTStatementKind::Continue(continue_statement) => {
+                self.visit_continue_statement(continue_statement)
+            }
+            ASTStatementKind::Return(return_statement) => {
+                self.visit_return_statement(return_statement)
+            }
+            ASTStatementKind::Impl(impl_statement) => self.visit_impl_statement(impl_statement),
+            ASTStatementKind::Import(import) => self.visit_import_statement(import),
+            ASTStatementKind::Style(style) => self.visit_style_statement(style),
+        }
+    }
+
+    fn visit_statement(&mut self, statement: &mut ASTStatement<'a>) {
+        self.default_visit_statement(statement);
+    }
+
+    fn default_visit_expression(&mut self, expression: &mut ASTExpression<'a>) {
+        match &mut expression.kind {
+            ASTExpressionKind::Error(span) => self.visit_error_expression(span),
+            ASTExpressionKind::Number(expr) => self.visit_number_expression(expr),
+            ASTExpressionKind::String(expr) => self.visit_string_expression(expr),
+            ASTExpressionKind::Array(expr) => self.visit_array_expression(expr),
+            ASTExpressionKind::Binary(expr) => self.visit_binary_expression(expr),
+            ASTExpressionKind::Variable(expr) => self.visit_variable_expression(expr),
+            ASTExpressionKind::Parenthesized(parenthesized) => {
+                self.visit_parenthesized_expression(parenthesized)
+            }
+            ASTExpressionKind::Call(expr) => self.visit_call_expression(expr),
+            ASTExpressionKind::Member(expr) => self.visit_member_expression(expr),
+            ASTExpressionKind::Index(expr) => self.visit_index_expression(expr),
+            ASTExpressionKind::Assignment(expr) => self.visit_assignment_expression(expr),
+            ASTExpressionKind::Element(expr) => self.visit_element_expression(expr),
+            ASTExpressionKind::Text(expr) => self.visit_text_expression(expr),
+            ASTExpressionKind::Closure(expr) => self.visit_closure_expression(expr),
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &mut ASTExpression<'a>) {
+        self.default_visit_expression(expression);
+    }
+
+    fn default_visit_type(&mut self, ty: &mut ASTType<'a>) {
+        match &mut ty.kind {
+            ASTTypeKind::Error(span) => self.visit_error_type(span),
+            ASTTypeKind::Named(identifier) => self.visit_named_type(identifier),
+            ASTTypeKind::Literal(literal) => self.visit_literal_type(literal),
+            ASTTypeKind::Union(union) => self.visit_union_type(union),
+            ASTTypeKind::Optional(optional) => self.visit_optional_type(optional),
+            ASTTypeKind::Array(array) => self.visit_array_type(array),
+            ASTTypeKind::Function(function) => self.visit_function_type(function),
+            ASTTypeKind::Generic(generic) => self.visit_generic_type(generic),
+            ASTTypeKind::Trusted(trusted) => self.visit_trusted_type(trusted),
+            ASTTypeKind::Object(object) => self.visit_object_type(object),
+        }
+    }
+
+    fn visit_type(&mut self, ty: &mut ASTType<'a>) {
+        self.default_visit_type(ty);
+    }
+
+    fn visit_variable_expression(&mut self, _expression: &mut ASTVariableExpression) {}
+
+    fn visit_let_statement(&mut self, let_statement: &mut ASTLetStatement<'a>) {
+        if let Some(ty) = &mut let_statement.type_annotation {
+            self.visit_type(ty);
+        }
+        self.visit_expression(&mut let_statement.initializer);
+    }
+
+    fn visit_type_alias_statement(&mut self, type_alias: &mut ASTTypeAliasStatement<'a>) {
+        self.visit_type(&mut type_alias.ty);
+    }
+
+    fn visit_component_statement(&mut self, component: &mut ASTComponentStatement<'a>) {
+        for generic in &mut component.generics {
+            self.visit_generic_parameter(generic);
+        }
+        for parameter in &mut component.parameters {
+            self.visit_parameter(parameter);
+        }
+        self.visit_block(&mut component.body);
+    }
+
+    fn visit_callback_statement(&mut self, callback: &mut ASTCallbackStatement<'a>) {
+        for parameter in &mut callback.parameters {
+            self.visit_parameter(parameter);
+        }
+        self.visit_block(&mut callback.body);
+    }
+
+    fn visit_for_statement(&mut self, for_statement: &mut ASTForStatement<'a>) {
+        self.visit_expression(&mut for_statement.iterable);
+        self.visit_block(&mut for_statement.body);
+    }
+
+    fn visit_while_statement(&mut self, while_statement: &mut ASTWhileStatement<'a>) {
+        self.visit_expression(&mut while_statement.condition);
+        self.visit_block(&mut while_statement.body);
+    }
+
+    fn visit_break_statement(&mut self, _break_statement: &mut ASTBreakStatement) {}
+
+    fn visit_continue_statement(&mut self, _continue_statement: &mut ASTContinueStatement) {}
+
+    fn visit_return_statement(&mut self, return_statement: &mut ASTReturnStatement<'a>) {
+        if let Some(value) = &mut return_statement.value {
+            self.visit_expression(value);
+        }
+    }
+
+    fn visit_impl_statement(&mut self, impl_statement: &mut ASTImplStatement<'a>) {
+        for generic in &mut impl_statement.generics {
+            self.visit_generic_parameter(generic);
+        }
+        for member in &mut impl_statement.members {
+            self.visit_type(&mut member.ty);
+        }
+    }
+
+    fn visit_import_statement(&mut self, _import: &mut ASTImportStatement) {}
+
+    fn visit_style_statement(&mut self, _style: &mut ASTStyleStatement) {}
+
+    fn visit_generic_parameter(&mut self, generic: &mut ASTGenericParameter<'a>) {
+        if let Some(constraint) = &mut generic.constraint {
+            self.visit_type(&mut constraint.ty);
+        }
+    }
+
+    fn visit_parameter(&mut self, parameter: &mut ASTParameter<'a>) {
+        if let Some(ty) = &mut parameter.ty {
+            self.visit_type(ty);
+        }
+        if let Some(default) = &mut parameter.default {
+            self.visit_expression(default);
+        }
+    }
+
+    fn visit_error_expression(&mut self, _span: &mut TextSpan) {}
+
+    fn visit_number_expression(&mut self, _expression: &mut ASTNumberExpression) {}
+
+    fn visit_string_expression(&mut self, _expression: &mut ASTStringExpression) {}
+
+    fn visit_array_expression(&mut self, expression: &mut ASTArrayExpression<'a>) {
+        for element in &mut expression.elements {
+            self.visit_expression(element);
+        }
+    }
+
+    fn visit_binary_expression(&mut self, expression: &mut ASTBinaryExpression<'a>) {
+        self.visit_expression(&mut expression.left);
+        self.visit_expression(&mut expression.right);
+    }
+
+    fn visit_parenthesized_expression(&mut self, expression: &mut ASTParenthesizedExpression<'a>) {
+        self.visit_expression(&mut expression.inner);
+    }
+
+    fn visit_call_expression(&mut self, expression: &mut ASTCallExpression<'a>) {
+        self.visit_expression(&mut expression.callee);
+        for ty in &mut expression.type_arguments {
+            self.visit_type(ty);
+        }
+        for argument in &mut expression.arguments {
+            self.visit_expression(argument);
+        }
+    }
+
+    fn visit_member_expression(&mut self, expression: &mut ASTMemberExpression<'a>) {
+        self.visit_expression(&mut expression.object);
+    }
+
+    fn visit_index_expression(&mut self, expression: &mut ASTIndexExpression<'a>) {
+        self.visit_expression(&mut expression.object);
+        self.visit_expression(&mut expression.index);
+    }
+
+    fn visit_assignment_expression(&mut self, expression: &mut ASTAssignmentExpression<'a>) {
+        self.visit_expression(&mut expression.target);
+        self.visit_expression(&mut expression.value);
+    }
+
+    fn visit_element_expression(&mut self, expression: &mut ASTElementExpression<'a>) {
+        for attribute in &mut expression.attributes {
+            self.visit_expression(&mut attribute.value);
+        }
+        for child in expression.children.iter_mut().flatten() {
+            self.visit_expression(child);
+        }
+    }
+
+    fn visit_text_expression(&mut self, _expression: &mut ASTTextExpression) {}
+
+    fn visit_closure_expression(&mut self, expression: &mut ASTClosureExpression<'a>) {
+        for parameter in &mut expression.parameters {
+            if let ASTClosureParameter::Binding(parameter) = parameter {
+                self.visit_parameter(parameter);
+            }
+        }
+        self.visit_expression(&mut expression.body);
+    }
+
+    fn visit_error_type(&mut self, _span: &mut TextSpan) {}
+
+    fn visit_named_type(&mut self, _identifier: &mut Token) {}
+
+    fn visit_literal_type(&mut self, _literal: &mut Token) {}
+
+    fn visit_union_type(&mut self, union: &mut ASTUnionType<'a>) {
+        for member in &mut union.members {
+            self.visit_type(member);
+        }
+    }
+
+    fn visit_optional_type(&mut self, optional: &mut ASTOptionalType<'a>) {
+        self.visit_type(&mut optional.inner);
+    }
+
+    fn visit_array_type(&mut self, array: &mut ASTArrayType<'a>) {
+        self.visit_type(&mut array.element);
+    }
+
+    fn visit_function_type(&mut self, function: &mut ASTFunctionType<'a>) {
+        for parameter in &mut function.parameters {
+            self.visit_parameter(parameter);
+        }
+        self.visit_type(&mut function.return_type);
+    }
+
+    fn visit_generic_type(&mut self, generic: &mut ASTGenericType<'a>) {
+        for argument in &mut generic.arguments {
+            self.visit_type(argument);
+        }
+    }
+
+    fn visit_trusted_type(&mut self, trusted: &mut ASTTrustedType<'a>) {
+        self.visit_type(&mut trusted.inner);
+    }
+
+    fn visit_object_type(&mut self, object: &mut ASTObjectType<'a>) {
+        for property in &mut object.properties {
+            self.visit_type(&mut property.ty);
+        }
+    }
+}
+
+/// Creates nodes for tree transforms.
+///
+/// Synthesised nodes get fresh `NodeId`s following the ones the parser handed out, and take
+/// their span from the source they replace, so diagnostics still point at the original code.
+pub struct ASTBuilder<'a> {
+    arena: &'a ASTArena,
+    next_id: Cell<usize>,
+}
+
+impl<'a> ASTBuilder<'a> {
+    /// Creates a builder handing out IDs from `first_id`, e.g. `Parser::next_node_id()`, and
+    /// allocating nodes in the tree's arena.
+    pub fn new(first_id: NodeId, arena: &'a ASTArena) -> Self {
+        Self {
+            arena,
+            next_id: Cell::new(first_id.index()),
+        }
+    }
+
+    pub fn next_id(&self) -> NodeId {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        NodeId::new(id)
+    }
+
+    /// A token which doesn't appear in the source, located at `span`.
+    pub fn token(&self, kind: TokenKind, literal: &str, span: &TextSpan) -> Token {
+        Token::new(kind, *span, Rc::from(literal))
+    }
+
+    /// Moves `expression` out of the tree, leaving an error node in its place.
+    ///
+    /// Useful for wrapping an expression in place, e.g.
+    /// `*expr = builder.call_named("f", vec![builder.take(expr)], span)`.
+    pub fn take(&self, expression: &mut ASTExpression<'a>) -> ASTExpression<'a> {
+        let span = *expression.span();
+        std::mem::replace(expression, ASTExpression::error(self.next_id(), span))
+    }
+
+    /// Moves the statements out of `statements`, leaving it empty.
+    pub fn take_block(&self, statements: &mut Vec<ASTStatement<'a>>) -> Vec<ASTStatement<'a>> {
+        std::mem::take(statements)
+    }
+
+    pub fn number(&self, value: i64, span: &TextSpan) -> ASTExpression<'a> {
+        ASTExpression::number(value, self.next_id(), *span)
+    }
+
+    pub fn string(&self, value: &str, span: &TextSpan) -> ASTExpression<'a> {
+        let literal = self.token(
+            TokenKind::String(value.to_string()),
+            &format!("\"{}\"", value),
+            span,
+        );
+        ASTExpression::string(literal, self.next_id(), *span)
+    }
+
+    pub fn array(&self, elements: Vec<ASTExpression<'a>>, span: &TextSpan) -> ASTExpression<'a> {
+        ASTExpression::array(elements, self.next_id(), *span)
+    }
+
+    pub fn variable(&self, name: &str, span: &TextSpan) -> ASTExpression<'a> {
+        let identifier = self.token(TokenKind::Identifier, name, span);
+        ASTExpression::identifier(identifier, self.next_id(), *span)
+    }
+
+    pub fn binary(
+        &self,
+        left: ASTExpression<'a>,
+        kind: ASTBinaryOperatorKind,
+        right: ASTExpression<'a>,
+        span: &TextSpan,
+    ) -> ASTExpression<'a> {
+        let token_kind = match kind {
+            ASTBinaryOperatorKind::Add => TokenKind::Plus,
+            ASTBinaryOperatorKind::Subtract => TokenKind::Minus,
+            ASTBinaryOperatorKind::Multiply => TokenKind::Asterisk,
+            ASTBinaryOperatorKind::Divide => TokenKind::Slash,
+        };
+        let token = self.token(token_kind.clone(), &token_kind.to_string(), span);
+        let operator = ASTBinaryOperator::new(kind, token);
+        ASTExpression::binary(left, operator, right, self.next_id(), *span, self.arena)
+    }
+
+    pub fn parenthesized(&self, inner: ASTExpression<'a>, span: &TextSpan) -> ASTExpression<'a> {
+        ASTExpression::parenthesized(inner, self.next_id(), *span, self.arena)
+    }
+
+    pub fn call(
+        &self,
+        callee: ASTExpression<'a>,
+        arguments: Vec<ASTExpression<'a>>,
+        span: &TextSpan,
+    ) -> ASTExpression<'a> {
+        ASTExpression::call(callee, arguments, self.next_id(), *span, self.arena)
+    }
+
+    /// A call to a function by name, e.g. `setCount(count + 1)`.
+    pub fn call_named(
+        &self,
+        callee: &str,
+        arguments: Vec<ASTExpression<'a>>,
+        span: &TextSpan,
+    ) -> ASTExpression<'a> {
+        self.call(self.variable(callee, span), arguments, span)
+    }
+
+    pub fn member(
+        &self,
+        object: ASTExpression<'a>,
+        property: &str,
+        span: &TextSpan,
+    ) -> ASTExpression<'a> {
+        let property = self.token(TokenKind::Identifier, property, span);
+        ASTExpression::member(object, property, self.next_id(), *span, self.arena)
+    }
+
+    pub fn index(
+        &self,
+        object: ASTExpression<'a>,
+        index: ASTExpression<'a>,
+        span: &TextSpan,
+    ) -> ASTExpression<'a> {
+        ASTExpression::index(object, index, self.next_id(), *span, self.arena)
+    }
+
+    /// A plain `target = value` assignment.
+    pub fn assignment(
+        &self,
+        target: ASTExpression<'a>,
+        value: ASTExpression<'a>,
+        span: &TextSpan,
+    ) -> ASTExpression<'a> {
+        let token = self.token(TokenKind::Equals, "=", span);
+        let operator = ASTAssignmentOperator::new(ASTAssignmentOperatorKind::Assign, token);
+        ASTExpression::assignment(target, operator, value, self.next_id(), *span, self.arena)
+    }
+
+    pub fn expression_statement(&self, expression: ASTExpression<'a>) -> ASTStatement<'a> {
+        let span = *expression.span();
+        ASTStatement::expression(expression, self.next_id(), span, self.arena)
+    }
+
+    /// A `let` binding, or a `const` binding when `constant` is set.
+    pub fn let_statement(
+        &self,
+        constant: bool,
+        identifier: &str,
+        type_annotation: Option<ASTType<'a>>,
+        initializer: ASTExpression<'a>,
+        span: &TextSpan,
+    ) -> ASTStatement<'a> {
+        let keyword = match constant {
+            true => self.token(TokenKind::Const, "const", span),
+            false => self.token(TokenKind::Let, "let", span),
+        };
+        let identifier = self.token(TokenKind::Identifier, identifier, span);
+        let let_statement = ASTLetStatement::new(
+            ASTVisibility::default(),
+            keyword,
+            ASTPattern::Identifier(identifier),
+            type_annotation,
+            initializer,
+        );
+        ASTStatement::let_statement(let_statement, self.next_id(), *span, self.arena)
+    }
+
+    pub fn named_type(&self, name: &str, span: &TextSpan) -> ASTType<'a> {
+        let identifier = self.token(TokenKind::Identifier, name, span);
+        ASTType::named(identifier, self.next_id(), *span)
+    }
+
+    pub fn array_type(&self, element: ASTType<'a>, span: &TextSpan) -> ASTType<'a> {
+        ASTType::array(element, self.next_id(), *span, self.arena)
+    }
+
+    pub fn generic_type(
+        &self,
+        name: &str,
+        arguments: Vec<ASTType<'a>>,
+        span: &TextSpan,
+    ) -> ASTType<'a> {
+        let identifier = self.token(TokenKind::Identifier, name, span);
+        ASTType::generic(identifier, arguments, self.next_id(), *span)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::compile_transformed;
+
+    /// Rewrites `a += b` into `a = a + (b)`.
+    struct ExpandCompoundAssignments<'a> {
+        builder: ASTBuilder<'a>,
+    }
+
+    impl<'a> ASTVisitorMut<'a> for ExpandCompoundAssignments<'a> {
+        fn visit_expression(&mut self, expression: &mut ASTExpression<'a>) {
+            self.default_visit_expression(expression);
+            let ASTExpressionKind::Assignment(assignment) = &mut expression.kind else {
+                return;
+            };
+            let (Some(operator), ASTExpressionKind::Variable(target)) = (
+                assignment.operator.binary_operator(),
+                assignment.target.kind(),
+            ) else {
+                return;
+            };
+
+            let span = expression.span;
+            let name = target.identifier().to_string();
+            let value = self.builder.take(&mut assignment.value);
+            let value = self.builder.binary(
+                self.builder.variable(&name, &span),
+                operator,
+                self.builder.parenthesized(value, &span),
+                &span,
+            );
+            *expression =
+                self.builder
+                    .assignment(self.builder.variable(&name, &span), value, &span);
+        }
+    }
+
+    /// Follows every `let` binding with a copy of it, e.g. `let a = 1; let aCopy = a;`.
+    struct CopyBindings<'a> {
+        builder: ASTBuilder<'a>,
+    }
+
+    impl<'a> ASTVisitorMut<'a> for CopyBindings<'a> {
+        fn visit_block(&mut self, statements: &mut Vec<ASTStatement<'a>>) {
+            for statement in self.builder.take_block(statements) {
+                let copy = match &statement.kind {
+                    ASTStatementKind::LetStatement(let_statement) => {
+                        let ASTPattern::Identifier(identifier) = &let_statement.pattern else {
+                            unreachable!("the test only binds identifiers");
+                        };
+                        let span = &statement.span;
+                        let name = identifier.text();
+                        let initializer = self.builder.variable(name, span);
+                        let copy = format!("{}Copy", name);
+                        Some(
+                            self.builder
+                                .let_statement(false, &copy, None, initializer, span),
+                        )
+                    }
+                    _ => None,
+                };
+                statements.push(statement);
+                statements.extend(copy);
+            }
+        }
+    }
+
+    #[test]
+    fn rewrites_expressions_in_place() {
+        let compilation = compile_transformed("let a = 1; a += 2 * 3; a -= a;", |ast, builder| {
+            ast.visit_mut(&mut ExpandCompoundAssignments { builder })
+        });
+        assert_eq!(compilation.diagnostics, Vec::<String>::new());
+        assert_eq!(
+            compilation.tsx.unwrap(),
+            "let a = 1;\na = a + (2 * 3);\na = a - (a);\n"
+        );
+    }
+
+    #[test]
+    fn inserts_statements_into_blocks() {
+        let compilation = compile_transformed("let a = 1; let b = a + 1; b", |ast, builder| {
+            ast.visit_mut(&mut CopyBindings { builder })
+        });
+        assert_eq!(compilation.diagnostics, Vec::<String>::new());
+        assert_eq!(
+            compilation.tsx.unwrap(),
+            "let a = 1;\nlet aCopy = a;\nlet b = a + 1;\nlet bCopy = b;\nb;\n"
+        );
+    }
+
+    #[test]
+    fn tells_apart_declarations_synthesised_from_the_same_source() {
+        let compilation = compile_transformed("const total = 1;", |ast, builder| {
+            // Every synthesised node is located at the statement it was derived from.
+            let span = *ast.statements[0].span();
+            let label = builder.string("total", &span);
+            let count = builder.variable("total", &span);
+            let product = builder.binary(
+                builder.variable("label", &span),
+                ASTBinaryOperatorKind::Multiply,
+                builder.variable("count", &span),
+                &span,
+            );
+            ast.add_statement(builder.let_statement(true, "label", None, label, &span));
+            ast.add_statement(builder.let_statement(true, "count", None, count, &span));
+            ast.add_statement(builder.let_statement(true, "product", None, product, &span));
+        });
+        assert_eq!(
+            compilation.diagnostics,
+            vec!["Operator '*' cannot be applied to types '\"total\"' and '1'"]
+        );
+    }
+}
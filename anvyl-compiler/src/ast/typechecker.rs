@@ -0,0 +1,1629 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+
+use crate::ast::ASTVisitor;
+use crate::ast::hooks::{change_handler_name, default_prop_name};
+use crate::prelude::*;
+
+/// The static type of a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Type {
+    Number,
+    String,
+    Boolean,
+    Undefined,
+    Null,
+    NumberLiteral(i64),
+    StringLiteral(String),
+    Union(Vec<Type>),
+    Array(Box<Type>),
+    /// A fixed number of values, e.g. the `[number, T]` pairs `enumerate()` produces.
+    Tuple(Vec<Type>),
+    /// A lazy iterator over items of the given type, e.g. the result of `items.iter()`.
+    Iterator(Box<Type>),
+    Function(FunctionType),
+    Object(Vec<Property>),
+    /// A component, along with the type of its props.
+    Component(Vec<Property>),
+    /// A generic parameter, e.g. `T` inside `component Autocomplete<T>`.
+    Parameter(GenericParameter),
+    Never,
+    /// A type which couldn't be determined, compatible with everything.
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionType {
+    pub(crate) parameters: Vec<ParameterType>,
+    pub(crate) return_type: Box<Type>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParameterType {
+    pub(crate) name: String,
+    pub(crate) ty: Type,
+    /// Whether arguments must be trusted, e.g. `id: trusted string`.
+    pub(crate) trusted: bool,
+}
+
+/// Whether a value is certain to have its static type at runtime.
+///
+/// Values built from literals and project code are trusted, while parameters and the results
+/// of foreign calls are not, since nothing guarantees what they hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trust {
+    Trusted,
+    Untrusted,
+}
+
+impl Trust {
+    /// Trusted only when both values are, e.g. for the result of `a + b`.
+    pub fn and(self, other: Trust) -> Trust {
+        match (self, other) {
+            (Trust::Trusted, Trust::Trusted) => Trust::Trusted,
+            _ => Trust::Untrusted,
+        }
+    }
+}
+
+/// A member of an object type, e.g. a component prop.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Property {
+    pub(crate) name: String,
+    pub(crate) ty: Type,
+    pub(crate) optional: bool,
+    /// Whether values passed must be trusted, e.g. `id: trusted string`.
+    pub(crate) trusted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenericParameter {
+    pub(crate) name: String,
+    /// The type the parameter `extends`, if any.
+    pub(crate) constraint: Option<Box<Type>>,
+}
+
+impl Type {
+    /// Creates a union of `members`, flattening nested unions and removing duplicates.
+    pub fn union(members: impl IntoIterator<Item = Type>) -> Type {
+        let mut flattened: Vec<Type> = Vec::new();
+        for member in members {
+            let nested = match member {
+                Type::Union(nested) => nested,
+                Type::Never => continue,
+                member => vec![member],
+            };
+            for member in nested {
+                if !flattened.contains(&member) {
+                    flattened.push(member);
+                }
+            }
+        }
+
+        match flattened.len() {
+            0 => Type::Never,
+            1 => flattened.pop().unwrap(),
+            _ => Type::Union(flattened),
+        }
+    }
+
+    /// The type a mutable binding initialised with a value of this type gets, e.g. `number`
+    /// for `1`.
+    pub fn widen(self) -> Type {
+        match self {
+            Type::NumberLiteral(_) => Type::Number,
+            Type::StringLiteral(_) => Type::String,
+            Type::Union(members) => Type::union(members.into_iter().map(Type::widen)),
+            ty => ty,
+        }
+    }
+
+    fn members(&self) -> &[Type] {
+        match self {
+            Type::Union(members) => members,
+            ty => std::slice::from_ref(ty),
+        }
+    }
+
+    fn is_number(&self) -> bool {
+        self.members()
+            .iter()
+            .all(|ty| matches!(ty, Type::Number | Type::NumberLiteral(_)))
+    }
+
+    fn is_string(&self) -> bool {
+        self.members()
+            .iter()
+            .all(|ty| matches!(ty, Type::String | Type::StringLiteral(_)))
+    }
+
+    fn is_unknown(&self) -> bool {
+        self.members()
+            .iter()
+            .any(|ty| matches!(ty, Type::Unknown | Type::Parameter(_)))
+    }
+
+    fn is_nullable(&self) -> bool {
+        self.members()
+            .iter()
+            .any(|ty| matches!(ty, Type::Undefined | Type::Null))
+    }
+
+    /// Removes `undefined` and `null` from a union.
+    fn non_nullable(&self) -> Type {
+        Type::union(
+            self.members()
+                .iter()
+                .filter(|ty| !matches!(ty, Type::Undefined | Type::Null))
+                .cloned(),
+        )
+    }
+
+    /// Whether a value of this type can be used where a value of the `target` type is expected.
+    pub fn is_assignable_to(&self, target: &Type) -> bool {
+        match (self, target) {
+            _ if self == target => true,
+            (Type::Unknown, _) | (_, Type::Unknown) | (Type::Never, _) => true,
+            (Type::Union(members), _) => {
+                members.iter().all(|member| member.is_assignable_to(target))
+            }
+            (_, Type::Union(members)) => members.iter().any(|member| self.is_assignable_to(member)),
+            (Type::NumberLiteral(_), Type::Number) | (Type::StringLiteral(_), Type::String) => true,
+            (Type::Array(source), Type::Array(target))
+            | (Type::Iterator(source), Type::Iterator(target)) => source.is_assignable_to(target),
+            (Type::Tuple(source), Type::Tuple(target)) => {
+                source.len() == target.len()
+                    && source
+                        .iter()
+                        .zip(target)
+                        .all(|(source, target)| source.is_assignable_to(target))
+            }
+            (Type::Function(source), Type::Function(target)) => {
+                source.parameters.len() <= target.parameters.len()
+                    && source
+                        .parameters
+                        .iter()
+                        .zip(&target.parameters)
+                        .all(|(source, target)| {
+                            target.ty.is_assignable_to(&source.ty)
+                                && (target.trusted || !source.trusted)
+                        })
+                    && (*target.return_type == Type::Undefined
+                        || source.return_type.is_assignable_to(&target.return_type))
+            }
+            (Type::Object(source), Type::Object(target))
+            | (Type::Component(source), Type::Component(target)) => {
+                Self::is_structurally_assignable(source, target)
+            }
+            // Parameters are identified by name, whatever constraints narrow them locally.
+            (Type::Parameter(source), Type::Parameter(target)) if source.name == target.name => {
+                true
+            }
+            (Type::Parameter(source), _) => source
+                .constraint
+                .as_ref()
+                .is_some_and(|constraint| constraint.is_assignable_to(target)),
+            _ => false,
+        }
+    }
+
+    /// Whether an object with the `source` properties has every property `target` requires.
+    fn is_structurally_assignable(source: &[Property], target: &[Property]) -> bool {
+        target.iter().all(|expected| {
+            match source
+                .iter()
+                .find(|property| property.name == expected.name)
+            {
+                Some(property) => {
+                    (expected.optional || !property.optional)
+                        && property.ty.is_assignable_to(&expected.ty)
+                }
+                None => expected.optional,
+            }
+        })
+    }
+
+    fn needs_parentheses(&self) -> bool {
+        matches!(self, Type::Union(_) | Type::Function(_))
+    }
+}
+
+fn fmt_properties(f: &mut std::fmt::Formatter<'_>, properties: &[Property]) -> std::fmt::Result {
+    write!(f, "{{")?;
+    for (index, property) in properties.iter().enumerate() {
+        let separator = if index == 0 { " " } else { "; " };
+        let optional = if property.optional { "?" } else { "" };
+        write!(
+            f,
+            "{}{}{}: {}",
+            separator, property.name, optional, property.ty
+        )?;
+    }
+    if !properties.is_empty() {
+        write!(f, " ")?;
+    }
+    write!(f, "}}")
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Number => write!(f, "number"),
+            Type::String => write!(f, "string"),
+            Type::Boolean => write!(f, "boolean"),
+            Type::Undefined => write!(f, "undefined"),
+            Type::Null => write!(f, "null"),
+            Type::NumberLiteral(value) => write!(f, "{}", value),
+            Type::StringLiteral(value) => write!(f, "\"{}\"", value),
+            Type::Union(members) => {
+                let members: Vec<String> = members
+                    .iter()
+                    .map(|member| match member.needs_parentheses() {
+                        true => format!("({})", member),
+                        false => member.to_string(),
+                    })
+                    .collect();
+                write!(f, "{}", members.join(" | "))
+            }
+            Type::Array(element) if element.needs_parentheses() => write!(f, "({})[]", element),
+            Type::Array(element) => write!(f, "{}[]", element),
+            Type::Tuple(elements) => {
+                let elements: Vec<String> = elements.iter().map(Type::to_string).collect();
+                write!(f, "[{}]", elements.join(", "))
+            }
+            Type::Iterator(item) => write!(f, "Iterator<{}>", item),
+            Type::Function(function) => {
+                let parameters: Vec<String> = function
+                    .parameters
+                    .iter()
+                    .map(|parameter| match parameter.trusted {
+                        true => format!("{}: trusted {}", parameter.name, parameter.ty),
+                        false => format!("{}: {}", parameter.name, parameter.ty),
+                    })
+                    .collect();
+                write!(f, "({}) => {}", parameters.join(", "), function.return_type)
+            }
+            Type::Object(properties) => fmt_properties(f, properties),
+            Type::Component(props) => {
+                write!(f, "component ")?;
+                fmt_properties(f, props)
+            }
+            Type::Parameter(parameter) => write!(f, "{}", parameter.name),
+            Type::Never => write!(f, "never"),
+            Type::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Checks that values are used according to their types, recording the type of every
+/// expression.
+///
+/// Runs after the `SymbolChecker`, relying on the names it resolved.
+pub struct TypeChecker<'a> {
+    model: &'a SemanticModel,
+    types: SideTable<Type>,
+    symbol_types: HashMap<SymbolId, Type>,
+    aliases: HashMap<String, Type>,
+    /// Whether a runtime guard can be generated for each type alias, see `has_runtime_guard`.
+    guarded_aliases: HashMap<String, bool>,
+    /// Generic parameters in scope, innermost last.
+    generics: Vec<GenericParameter>,
+    /// The props of each declared component, which `impl` blocks refine.
+    components: HashMap<String, Vec<Property>>,
+    trust: SideTable<Trust>,
+    /// The trust of each variable's current value, as of the statement being checked.
+    symbol_trust: HashMap<SymbolId, Trust>,
+    /// The previous trust of each variable assigned to, so it can be restored when leaving
+    /// a callback, or combined with the new one when leaving a loop.
+    trust_log: Vec<(SymbolId, Option<Trust>)>,
+    /// Variables declared `trusted`, which only ever hold trusted values.
+    trusted_symbols: HashSet<SymbolId>,
+    /// Variables a callback may assign an untrusted value to at any time.
+    tainted: HashSet<SymbolId>,
+    /// How many components and callbacks each variable is declared inside of.
+    symbol_depth: HashMap<SymbolId, usize>,
+    function_depth: usize,
+    /// The type and trust of the items an iterator adaptor passes to the closure about to be
+    /// visited.
+    closure_item: Option<(Type, Trust)>,
+    /// The statement being checked, which the symbols it declares are recorded against.
+    statement: Option<NodeId>,
+    diagnostics: DiagnosticsBagCell,
+}
+
+impl<'a> TypeChecker<'a> {
+    pub fn new(model: &'a SemanticModel, diagnostics: DiagnosticsBagCell) -> Self {
+        Self {
+            model,
+            types: SideTable::new(),
+            symbol_types: HashMap::new(),
+            aliases: HashMap::new(),
+            guarded_aliases: HashMap::new(),
+            generics: Vec::new(),
+            components: HashMap::new(),
+            trust: SideTable::new(),
+            symbol_trust: HashMap::new(),
+            trust_log: Vec::new(),
+            trusted_symbols: HashSet::new(),
+            tainted: HashSet::new(),
+            symbol_depth: HashMap::new(),
+            function_depth: 0,
+            closure_item: None,
+            statement: None,
+            diagnostics,
+        }
+    }
+
+    /// The type of each expression.
+    pub fn types(&self) -> &SideTable<Type> {
+        &self.types
+    }
+
+    pub fn into_types(self) -> SideTable<Type> {
+        self.types
+    }
+
+    /// The props of each component declared in the file.
+    pub fn into_components(self) -> HashMap<String, Vec<Property>> {
+        self.components
+    }
+
+    /// Makes the props of imported components known, keyed by the name they are imported
+    /// as, so components extending them inherit their props.
+    pub fn import_components(&mut self, components: HashMap<String, Vec<Property>>) {
+        self.components.extend(components);
+    }
+
+    /// Whether the value of an expression is trusted.
+    pub fn is_trusted(&self, id: NodeId) -> bool {
+        self.trust.get(id) != Some(&Trust::Untrusted)
+    }
+
+    fn trust_of(&self, expression: &ASTExpression) -> Trust {
+        match self.is_trusted(expression.id()) {
+            true => Trust::Trusted,
+            false => Trust::Untrusted,
+        }
+    }
+
+    /// The trust of a variable's current value. Imported values may hold anything, as their
+    /// trust isn't tracked across files.
+    fn symbol_trust(&self, symbol: SymbolId) -> Trust {
+        let imported = self.model.symbol(symbol).kind() == SymbolKind::Import;
+        match imported || self.tainted.contains(&symbol) {
+            true => Trust::Untrusted,
+            false => self
+                .symbol_trust
+                .get(&symbol)
+                .copied()
+                .unwrap_or(Trust::Trusted),
+        }
+    }
+
+    /// Reports untrusted data flowing into a slot which requires trusted data.
+    fn expect_trusted(&self, trust: Trust, span: &TextSpan) {
+        if trust == Trust::Untrusted {
+            self.diagnostics.borrow_mut().report_untrusted_value(*span);
+        }
+    }
+
+    fn type_of(&self, expression: &ASTExpression) -> Type {
+        self.types
+            .get(expression.id())
+            .cloned()
+            .unwrap_or(Type::Unknown)
+    }
+
+    /// The statement being checked.
+    fn declaring_statement(&self) -> NodeId {
+        self.statement
+            .expect("declarations are only visited through their statement")
+    }
+
+    /// Declares the symbol `identifier` declared by the node `declaration`.
+    fn declare(
+        &mut self,
+        declaration: NodeId,
+        identifier: &Token,
+        ty: Type,
+        trust: Trust,
+    ) -> Option<SymbolId> {
+        let symbol = self.model.declared_symbol(declaration, identifier.text())?;
+        self.declare_symbol(symbol, ty, trust);
+        Some(symbol)
+    }
+
+    /// Declares the component or callback declared by the statement being checked, which
+    /// comes before its parameters.
+    fn declare_function(&mut self, ty: Type) {
+        let statement = self.declaring_statement();
+        if let Some(symbol) = self.model.declared_symbols(statement).first() {
+            self.declare_symbol(*symbol, ty, Trust::Trusted);
+        }
+    }
+
+    fn declare_symbol(&mut self, symbol: SymbolId, ty: Type, trust: Trust) {
+        self.symbol_types.insert(symbol, ty);
+        self.symbol_trust.insert(symbol, trust);
+        self.symbol_depth.insert(symbol, self.function_depth);
+    }
+
+    /// Reports a mismatch unless `found` can be used where `expected` is.
+    fn expect(&self, expected: &Type, found: &Type, span: &TextSpan) {
+        if !found.is_assignable_to(expected) {
+            self.diagnostics.borrow_mut().report_type_mismatch(
+                expected.to_string(),
+                found.to_string(),
+                *span,
+            );
+        }
+    }
+
+    /// The type an annotation describes.
+    fn resolve(&self, ty: &ASTType) -> Type {
+        match ty.kind() {
+            ASTTypeKind::Error(_) => Type::Unknown,
+            ASTTypeKind::Named(identifier) => match identifier.text() {
+                "number" => Type::Number,
+                "string" => Type::String,
+                "boolean" => Type::Boolean,
+                "undefined" | "void" => Type::Undefined,
+                "null" => Type::Null,
+                "never" => Type::Never,
+                name => self
+                    .generics
+                    .iter()
+                    .rev()
+                    .find(|generic| generic.name == name)
+                    .map(|generic| Type::Parameter(generic.clone()))
+                    .or_else(|| self.aliases.get(name).cloned())
+                    .unwrap_or(Type::Unknown),
+            },
+            ASTTypeKind::Literal(literal) => Self::literal(literal),
+            ASTTypeKind::Union(union) => {
+                Type::union(union.members().iter().map(|member| self.resolve(member)))
+            }
+            ASTTypeKind::Optional(optional) => {
+                Type::union([self.resolve(optional.inner()), Type::Undefined])
+            }
+            ASTTypeKind::Array(array) => Type::Array(Box::new(self.resolve(array.element()))),
+            ASTTypeKind::Function(function) => Type::Function(FunctionType {
+                parameters: self.parameters(function.parameters()),
+                return_type: Box::new(self.resolve(function.return_type())),
+            }),
+            ASTTypeKind::Trusted(trusted) => self.resolve(trusted.inner()),
+            ASTTypeKind::Object(object) => Type::Object(
+                object
+                    .properties()
+                    .iter()
+                    .map(|property| {
+                        let ty = self.resolve(property.ty());
+                        Property {
+                            name: property.identifier().to_string(),
+                            optional: property.is_optional() || ty.is_nullable(),
+                            ty: match property.is_optional() {
+                                true => Type::union([ty, Type::Undefined]),
+                                false => ty,
+                            },
+                            trusted: Self::is_trusted_slot(property.ty()),
+                        }
+                    })
+                    .collect(),
+            ),
+            ASTTypeKind::Generic(generic) => match (generic.identifier(), generic.arguments()) {
+                ("Array", [element]) => Type::Array(Box::new(self.resolve(element))),
+                _ => Type::Unknown,
+            },
+        }
+    }
+
+    /// Whether `validate` can check at runtime that a value is of type `ty`. Generic
+    /// parameters and types declared elsewhere, e.g. imported ones, have no runtime
+    /// representation to check against.
+    ///
+    /// `alias` is the type alias being declared, which may refer to itself.
+    fn has_runtime_guard(&self, ty: &ASTType, alias: Option<&str>) -> bool {
+        match ty.kind() {
+            ASTTypeKind::Error(_) | ASTTypeKind::Literal(_) | ASTTypeKind::Function(_) => true,
+            ASTTypeKind::Named(identifier) => match identifier.text() {
+                "number" | "string" | "boolean" | "undefined" | "void" | "null" | "never"
+                | "unknown" => true,
+                name if self.generics.iter().any(|generic| generic.name == name) => false,
+                name if Some(name) == alias => true,
+                name => self.guarded_aliases.get(name).copied().unwrap_or(false),
+            },
+            ASTTypeKind::Union(union) => union
+                .members()
+                .iter()
+                .all(|member| self.has_runtime_guard(member, alias)),
+            ASTTypeKind::Optional(optional) => self.has_runtime_guard(optional.inner(), alias),
+            ASTTypeKind::Array(array) => self.has_runtime_guard(array.element(), alias),
+            ASTTypeKind::Trusted(trusted) => self.has_runtime_guard(trusted.inner(), alias),
+            ASTTypeKind::Object(object) => object
+                .properties()
+                .iter()
+                .all(|property| self.has_runtime_guard(property.ty(), alias)),
+            ASTTypeKind::Generic(generic) => match (generic.identifier(), generic.arguments()) {
+                ("Array", [element]) => self.has_runtime_guard(element, alias),
+                _ => false,
+            },
+        }
+    }
+
+    fn literal(literal: &Token) -> Type {
+        match &literal.kind {
+            TokenKind::Number(value) => Type::NumberLiteral(*value),
+            TokenKind::String(value) => Type::StringLiteral(value.clone()),
+            _ => Type::Unknown,
+        }
+    }
+
+    /// The types of parameters as seen by callers, who may pass `undefined` for any
+    /// parameter with a default.
+    fn parameters(&self, parameters: &[ASTParameter]) -> Vec<ParameterType> {
+        parameters
+            .iter()
+            .map(|parameter| ParameterType {
+                name: parameter.identifier().to_string(),
+                ty: match parameter.default {
+                    Some(_) => Type::union([self.parameter_type(parameter), Type::Undefined]),
+                    None => self.parameter_type(parameter),
+                },
+                trusted: parameter.ty.as_ref().is_some_and(Self::is_trusted_slot),
+            })
+            .collect()
+    }
+
+    /// The type of a parameter within its function: its annotation, or otherwise the
+    /// widened type of its default.
+    fn parameter_type(&self, parameter: &ASTParameter) -> Type {
+        let ty = match (&parameter.ty, &parameter.default) {
+            (Some(ty), _) => self.resolve(ty),
+            (None, Some(default)) => self.type_of(default).widen(),
+            (None, None) => Type::Unknown,
+        };
+        match parameter.optional {
+            true => Type::union([ty, Type::Undefined]),
+            false => ty,
+        }
+    }
+
+    /// Makes the props of `controllable(#<prop>)` optional, adding the `default<Prop>` prop
+    /// they start out as when uncontrolled, and the `on<Prop>Changed` prop notified of
+    /// changes unless the component declares it.
+    fn add_controllable_props(&self, component: &ASTComponentStatement, props: &mut Vec<Property>) {
+        for prop in component.controlled_props() {
+            let name = prop.text();
+            let Some(controlled) = props.iter_mut().find(|property| property.name == name) else {
+                continue;
+            };
+            controlled.optional = true;
+            let ty = controlled.ty.clone();
+            let trusted = controlled.trusted;
+
+            props.push(Property {
+                name: default_prop_name(name),
+                ty: ty.clone(),
+                optional: true,
+                trusted,
+            });
+            let handler = change_handler_name(name);
+            match props.iter_mut().find(|property| property.name == handler) {
+                Some(declared) => declared.optional = true,
+                None => props.push(Property {
+                    name: handler,
+                    ty: Type::Function(FunctionType {
+                        parameters: vec![ParameterType {
+                            name: name.to_string(),
+                            ty,
+                            trusted: false,
+                        }],
+                        return_type: Box::new(Type::Undefined),
+                    }),
+                    optional: true,
+                    trusted: false,
+                }),
+            }
+        }
+    }
+
+    /// Adds the props a component inherits through `extend_props`, unless it redeclares
+    /// them. Redeclared props are omitted from the inherited ones whatever their type, which
+    /// is only needed when the types conflict, so compatible overrides are warned about.
+    ///
+    /// Nothing is inherited from imported components whose props aren't known, see
+    /// `import_components`.
+    fn inherit_props(
+        &self,
+        component: &ASTComponentStatement,
+        base: &Token,
+        props: &mut Vec<Property>,
+    ) {
+        let Some(inherited) = self.components.get(base.text()) else {
+            return;
+        };
+        for property in inherited {
+            let redeclared = component
+                .parameters
+                .iter()
+                .zip(props.iter())
+                .find(|(_, prop)| prop.name == property.name);
+            match redeclared {
+                Some((parameter, prop)) if prop.ty.is_assignable_to(&property.ty) => {
+                    self.diagnostics
+                        .borrow_mut()
+                        .report_compatible_prop_override(&parameter.identifier, base.text());
+                }
+                Some(_) => {}
+                None => props.push(property.clone()),
+            }
+        }
+    }
+
+    /// Checks the defaults of parameters against their annotations.
+    fn visit_defaults(&mut self, parameters: &[ASTParameter]) {
+        for parameter in parameters {
+            let Some(default) = &parameter.default else {
+                continue;
+            };
+            self.visit_expression(default);
+            if parameter.ty.is_some() {
+                self.expect(
+                    &self.parameter_type(parameter),
+                    &self.type_of(default),
+                    default.span(),
+                );
+            }
+        }
+    }
+
+    fn is_trusted_slot(ty: &ASTType) -> bool {
+        matches!(ty.kind(), ASTTypeKind::Trusted(_))
+    }
+
+    fn enter_generics(&mut self, generics: &[ASTGenericParameter]) {
+        for generic in generics {
+            let constraint = generic
+                .constraint()
+                .filter(|constraint| *constraint.kind() == ASTGenericConstraintKind::Extends)
+                .map(|constraint| Box::new(self.resolve(constraint.ty())));
+            self.generics.push(GenericParameter {
+                name: generic.identifier().to_string(),
+                constraint,
+            });
+        }
+    }
+
+    fn leave_generics(&mut self, generics: &[ASTGenericParameter]) {
+        self.generics.truncate(self.generics.len() - generics.len());
+    }
+
+    /// Declares parameters, which are untrusted unless declared `trusted`, since their
+    /// arguments could come from anywhere.
+    fn declare_parameters(&mut self, parameters: &[ASTParameter]) {
+        let statement = self.declaring_statement();
+        for parameter in parameters {
+            let trust = match parameter.ty.as_ref().is_some_and(Self::is_trusted_slot) {
+                true => Trust::Trusted,
+                false => Trust::Untrusted,
+            };
+            let ty = self.parameter_type(parameter);
+            self.declare(statement, &parameter.identifier, ty, trust);
+        }
+    }
+
+    /// Visits the body of a component or callback, which runs at some later point rather than
+    /// where it's declared.
+    fn visit_function_body(&mut self, body: &[ASTStatement]) {
+        self.visit_deferred(|checker| {
+            for statement in body {
+                checker.visit_statement(statement);
+            }
+        });
+    }
+
+    /// Visits code which may run at any later point, such as the body of a closure.
+    fn visit_deferred(&mut self, visit: impl FnOnce(&mut Self)) {
+        let mark = self.trust_log.len();
+        self.function_depth += 1;
+        visit(self);
+        self.function_depth -= 1;
+
+        for (symbol, trust) in self.trust_log.drain(mark..).rev() {
+            match trust {
+                Some(trust) => self.symbol_trust.insert(symbol, trust),
+                None => self.symbol_trust.remove(&symbol),
+            };
+        }
+    }
+
+    /// Visits a loop body, which may run any number of times.
+    fn visit_loop_body(&mut self, body: &[ASTStatement]) {
+        let mark = self.trust_log.len();
+        for statement in body {
+            self.visit_statement(statement);
+        }
+
+        let before: Vec<(SymbolId, Trust)> = self.trust_log[mark..]
+            .iter()
+            .filter_map(|(symbol, trust)| Some((*symbol, (*trust)?)))
+            .collect();
+        for (symbol, trust) in before {
+            self.set_symbol_trust(symbol, self.symbol_trust(symbol).and(trust));
+        }
+    }
+
+    fn set_symbol_trust(&mut self, symbol: SymbolId, trust: Trust) {
+        let previous = self.symbol_trust.insert(symbol, trust);
+        self.trust_log.push((symbol, previous));
+    }
+
+    fn binary_type(&self, operator: &ASTBinaryOperator, left: &Type, right: &Type) -> Type {
+        let ty = match operator.kind {
+            _ if left.is_unknown() || right.is_unknown() => Some(Type::Unknown),
+            ASTBinaryOperatorKind::Add if left.is_string() || right.is_string() => {
+                Some(Type::String)
+            }
+            _ if left.is_number() && right.is_number() => Some(Type::Number),
+            _ => None,
+        };
+
+        ty.unwrap_or_else(|| {
+            self.diagnostics.borrow_mut().report_invalid_operands(
+                &operator.token,
+                left.to_string(),
+                right.to_string(),
+            );
+            Type::Unknown
+        })
+    }
+
+    fn call_type(&self, call: &ASTCallExpression, span: &TextSpan) -> Type {
+        let arguments: Vec<Type> = call
+            .arguments
+            .iter()
+            .map(|argument| self.type_of(argument))
+            .collect();
+        // `stateful(initial)` holds a value of its initial value's type.
+        if call.callee_identifier() == Some("stateful") {
+            return arguments.into_iter().next().unwrap_or(Type::Undefined);
+        }
+        // `validate<T>(value)` checks its argument at runtime, whatever its static type.
+        if call.callee_identifier() == Some("validate") {
+            if call.type_arguments.len() != 1 || arguments.len() != 1 {
+                self.diagnostics.borrow_mut().report_invalid_validate(*span);
+            }
+            if let Some(ty) = call.type_arguments.first()
+                && !self.has_runtime_guard(ty, None)
+            {
+                self.diagnostics.borrow_mut().report_unguarded_type(ty);
+            }
+            return call
+                .type_arguments
+                .first()
+                .map_or(Type::Unknown, |ty| self.resolve(ty));
+        }
+        if let Some(ty) = self.iterator_call_type(call, span) {
+            return ty;
+        }
+
+        match self.type_of(&call.callee) {
+            Type::Function(function) => {
+                let required = function
+                    .parameters
+                    .iter()
+                    .rposition(|parameter| !Type::Undefined.is_assignable_to(&parameter.ty))
+                    .map_or(0, |index| index + 1);
+                let expected = match arguments.len() {
+                    found if found < required => Some(required),
+                    found if found > function.parameters.len() => Some(function.parameters.len()),
+                    _ => None,
+                };
+                if let Some(expected) = expected {
+                    self.diagnostics
+                        .borrow_mut()
+                        .report_argument_count_mismatch(expected, arguments.len(), *span);
+                }
+                for (parameter, (argument, found)) in function
+                    .parameters
+                    .iter()
+                    .zip(call.arguments.iter().zip(&arguments))
+                {
+                    self.expect(&parameter.ty, found, argument.span());
+                    if parameter.trusted {
+                        self.expect_trusted(self.trust_of(argument), argument.span());
+                    }
+                }
+                *function.return_type
+            }
+            Type::Unknown | Type::Parameter(_) | Type::Component(_) => Type::Unknown,
+            callee => {
+                self.diagnostics
+                    .borrow_mut()
+                    .report_not_callable(callee.to_string(), *call.callee.span());
+                Type::Unknown
+            }
+        }
+    }
+
+    /// The items `iter()` goes through, for the types it can be called on.
+    fn iterable_item(ty: &Type) -> Option<Type> {
+        match ty {
+            Type::Array(element) => Some(*element.clone()),
+            ty if ty.is_string() => Some(Type::String),
+            ty if ty.is_unknown() => Some(Type::Unknown),
+            _ => None,
+        }
+    }
+
+    /// Whether `call` makes an iterator with `iter()`, or calls a method of one.
+    fn is_iterator_call(&self, call: &ASTCallExpression) -> bool {
+        call.method()
+            .is_some_and(|(receiver, method)| match self.type_of(receiver) {
+                Type::Iterator(_) => true,
+                receiver => method.text() == "iter" && Self::iterable_item(&receiver).is_some(),
+            })
+    }
+
+    /// The type of an `iter()` call on a collection, or of a method call on an iterator,
+    /// `None` for any other call.
+    fn iterator_call_type(&self, call: &ASTCallExpression, span: &TextSpan) -> Option<Type> {
+        if !self.is_iterator_call(call) {
+            return None;
+        }
+        let (receiver, method) = call.method()?;
+        let name = method.text();
+        let expected = match name {
+            "map" | "filter" | "take" | "find" | "any" | "all" => 1,
+            _ => 0,
+        };
+        if call.arguments.len() != expected {
+            self.diagnostics
+                .borrow_mut()
+                .report_argument_count_mismatch(expected, call.arguments.len(), *span);
+        }
+
+        let item = match self.type_of(receiver) {
+            Type::Iterator(item) => *item,
+            collection => {
+                return Some(Type::Iterator(Box::new(Self::iterable_item(&collection)?)));
+            }
+        };
+        let ty = match name {
+            "map" => Type::Iterator(Box::new(self.closure_result(call, method))),
+            "filter" => {
+                self.closure_result(call, method);
+                Type::Iterator(Box::new(item))
+            }
+            "take" => {
+                if let Some(count) = call.arguments.first() {
+                    self.expect(&Type::Number, &self.type_of(count), count.span());
+                }
+                Type::Iterator(Box::new(item))
+            }
+            "enumerate" => Type::Iterator(Box::new(Type::Tuple(vec![Type::Number, item]))),
+            "sum" => {
+                self.expect(&Type::Number, &item, &method.span);
+                Type::Number
+            }
+            "collect" => Type::Array(Box::new(item)),
+            "find" => {
+                self.closure_result(call, method);
+                Type::union([item, Type::Undefined])
+            }
+            "any" | "all" => {
+                self.closure_result(call, method);
+                Type::Boolean
+            }
+            _ => {
+                self.diagnostics
+                    .borrow_mut()
+                    .report_unknown_iterator_method(method);
+                Type::Unknown
+            }
+        };
+        Some(ty)
+    }
+
+    /// The result type of the closure passed to an iterator adaptor, reporting anything but a
+    /// closure taking at most one item, since adaptors are inlined into a single loop.
+    fn closure_result(&self, call: &ASTCallExpression, method: &Token) -> Type {
+        let Some(argument) = call.arguments.first() else {
+            return Type::Unknown;
+        };
+        match argument.kind() {
+            ASTExpressionKind::Closure(closure) if closure.parameters.len() <= 1 => {
+                self.type_of(&closure.body)
+            }
+            _ => {
+                self.diagnostics
+                    .borrow_mut()
+                    .report_expected_item_closure(method, *argument.span());
+                Type::Unknown
+            }
+        }
+    }
+
+    /// The type a parameter of a closure was declared with.
+    fn closure_parameter(
+        &self,
+        closure: &ASTClosureExpression,
+        parameter: &ASTClosureParameter,
+    ) -> ParameterType {
+        let declared = |identifier: &Token| {
+            self.model
+                .declared_symbol(closure.body.id(), identifier.text())
+                .and_then(|symbol| self.symbol_types.get(&symbol))
+                .cloned()
+                .unwrap_or(Type::Unknown)
+        };
+        match parameter {
+            ASTClosureParameter::Binding(parameter) => ParameterType {
+                name: parameter.identifier().to_string(),
+                ty: declared(&parameter.identifier),
+                trusted: parameter.ty.as_ref().is_some_and(Self::is_trusted_slot),
+            },
+            ASTClosureParameter::Tuple(items) => {
+                let names: Vec<&str> = items.iter().map(|item| item.text()).collect();
+                ParameterType {
+                    name: format!("[{}]", names.join(", ")),
+                    ty: Type::Tuple(items.iter().map(declared).collect()),
+                    trusted: false,
+                }
+            }
+        }
+    }
+
+    /// Reports iterators which are never consumed, and so would never run.
+    fn expect_consumed(&self, expression: &ASTExpression) {
+        if let Type::Iterator(_) = self.type_of(expression) {
+            self.diagnostics
+                .borrow_mut()
+                .report_unconsumed_iterator(*expression.span());
+        }
+    }
+
+    /// Checks the attributes of an element rendering a component against its props, reporting
+    /// attributes it has no prop for, untrusted values passed to `trusted` props, and required
+    /// props which aren't passed.
+    ///
+    /// Elements of components declared further down the file aren't checked, as their props
+    /// aren't known yet, nor are HTML elements.
+    fn check_element(&self, element: &ASTElementExpression) {
+        let Some(props) = self.components.get(element.name()) else {
+            return;
+        };
+        for attribute in element.attributes() {
+            // React handles `key` and `ref` itself, rather than passing them as props, and
+            // TypeScript doesn't check dashed attributes such as `data-id` against the props.
+            if matches!(attribute.name(), "key" | "ref") || attribute.name().contains('-') {
+                continue;
+            }
+            let Some(prop) = props.iter().find(|prop| prop.name == attribute.name()) else {
+                self.diagnostics
+                    .borrow_mut()
+                    .report_unknown_prop(element.name(), &attribute.name);
+                continue;
+            };
+            // Generic props can hold anything they are instantiated with.
+            if !prop.ty.is_unknown() {
+                self.expect(
+                    &prop.ty,
+                    &self.type_of(&attribute.value),
+                    attribute.value.span(),
+                );
+            }
+            if prop.trusted {
+                self.expect_trusted(self.trust_of(&attribute.value), attribute.value.span());
+            }
+        }
+
+        let has_children = element
+            .children()
+            .is_some_and(|children| !children.is_empty());
+        for prop in props {
+            let passed = element
+                .attributes()
+                .iter()
+                .any(|attribute| attribute.name() == prop.name)
+                || (prop.name == "children" && has_children);
+            if !prop.optional && !passed {
+                self.diagnostics
+                    .borrow_mut()
+                    .report_missing_prop(&element.name, &prop.name);
+            }
+        }
+    }
+
+    fn member_type(&self, member: &ASTMemberExpression) -> Type {
+        let object = self.type_of(&member.object);
+        if object.is_nullable() {
+            self.diagnostics
+                .borrow_mut()
+                .report_possibly_undefined(object.to_string(), *member.object.span());
+        }
+
+        match (object.non_nullable(), member.property()) {
+            (Type::Object(properties), property) => {
+                match properties.iter().find(|existing| existing.name == property) {
+                    Some(property) => property.ty.clone(),
+                    None => {
+                        self.diagnostics
+                            .borrow_mut()
+                            .report_unknown_property(object.to_string(), &member.property);
+                        Type::Unknown
+                    }
+                }
+            }
+            (Type::Array(_), "length") => Type::Number,
+            (ty, "length") if ty.is_string() => Type::Number,
+            _ => Type::Unknown,
+        }
+    }
+
+    fn index_type(&self, index: &ASTIndexExpression) -> Type {
+        self.expect(
+            &Type::Number,
+            &self.type_of(&index.index),
+            index.index.span(),
+        );
+        match self.type_of(&index.object) {
+            Type::Array(element) => *element,
+            ty if ty.is_string() => Type::String,
+            _ => Type::Unknown,
+        }
+    }
+
+    fn assignment_type(&self, assignment: &ASTAssignmentExpression) -> Type {
+        let symbol = self.model.resolved.get(assignment.target.id());
+        let target = match assignment.target_variable() {
+            Some(_) => symbol
+                .and_then(|symbol| self.symbol_types.get(symbol))
+                .cloned()
+                .unwrap_or(Type::Unknown),
+            None => self.type_of(&assignment.target),
+        };
+        let value = self.type_of(&assignment.value);
+        let value = match assignment.operator.binary_operator() {
+            Some(kind) => {
+                let operator = ASTBinaryOperator::new(kind, assignment.operator.token.clone());
+                self.binary_type(&operator, &target, &value)
+            }
+            None => value,
+        };
+
+        // Assigning to a constant is reported by the `SymbolChecker` already.
+        let constant = assignment.target_variable().is_some()
+            && symbol.is_some_and(|symbol| !self.model.symbol(*symbol).is_mutable());
+        if !constant {
+            self.expect(&target, &value, assignment.value.span());
+        }
+        value
+    }
+
+    /// Computes the type of an expression from the types of its children.
+    fn check(&mut self, expression: &ASTExpression) {
+        let ty = match expression.kind() {
+            ASTExpressionKind::Error(_) => Type::Unknown,
+            ASTExpressionKind::Number(number) => Type::NumberLiteral(number.number()),
+            ASTExpressionKind::String(string) => Type::StringLiteral(string.value().to_string()),
+            ASTExpressionKind::Array(array) => {
+                let elements = array
+                    .elements()
+                    .iter()
+                    .map(|element| self.type_of(element).widen());
+                match Type::union(elements) {
+                    Type::Never => Type::Array(Box::new(Type::Unknown)),
+                    element => Type::Array(Box::new(element)),
+                }
+            }
+            ASTExpressionKind::Binary(binary) => self.binary_type(
+                &binary.operator,
+                &self.type_of(&binary.left),
+                &self.type_of(&binary.right),
+            ),
+            ASTExpressionKind::Parenthesized(parenthesized) => self.type_of(&parenthesized.inner),
+            ASTExpressionKind::Variable(variable) => {
+                match self.model.resolved.get(expression.id()) {
+                    Some(symbol) => self
+                        .symbol_types
+                        .get(symbol)
+                        .cloned()
+                        .unwrap_or(Type::Unknown),
+                    None => match (
+                        variable.identifier(),
+                        self.model.materialized_type(expression.id()),
+                    ) {
+                        ("undefined", _) => Type::Undefined,
+                        ("null", _) => Type::Null,
+                        (_, Some(members)) => {
+                            Type::Array(Box::new(Type::union(members.iter().map(Self::literal))))
+                        }
+                        _ => Type::Unknown,
+                    },
+                }
+            }
+            ASTExpressionKind::Call(call) => self.call_type(call, expression.span()),
+            ASTExpressionKind::Member(member) => self.member_type(member),
+            ASTExpressionKind::Index(index) => self.index_type(index),
+            ASTExpressionKind::Assignment(assignment) => self.assignment_type(assignment),
+            ASTExpressionKind::Element(element) => {
+                self.check_element(element);
+                Type::Unknown
+            }
+            ASTExpressionKind::Text(_) => Type::String,
+            ASTExpressionKind::Closure(closure) => Type::Function(FunctionType {
+                parameters: closure
+                    .parameters
+                    .iter()
+                    .map(|parameter| self.closure_parameter(closure, parameter))
+                    .collect(),
+                return_type: Box::new(self.type_of(&closure.body)),
+            }),
+        };
+
+        self.types.insert(expression.id(), ty);
+
+        let trust = match expression.kind() {
+            ASTExpressionKind::Error(_)
+            | ASTExpressionKind::Number(_)
+            | ASTExpressionKind::String(_)
+            | ASTExpressionKind::Element(_)
+            | ASTExpressionKind::Text(_)
+            | ASTExpressionKind::Closure(_) => Trust::Trusted,
+            ASTExpressionKind::Array(array) => array
+                .elements()
+                .iter()
+                .fold(Trust::Trusted, |trust, element| {
+                    trust.and(self.trust_of(element))
+                }),
+            ASTExpressionKind::Binary(binary) => self
+                .trust_of(&binary.left)
+                .and(self.trust_of(&binary.right)),
+            ASTExpressionKind::Parenthesized(parenthesized) => self.trust_of(&parenthesized.inner),
+            ASTExpressionKind::Variable(variable) => match self.model.resolved.get(expression.id())
+            {
+                Some(symbol) => self.symbol_trust(*symbol),
+                // Names declared nowhere in the file come from a glob import, unless they are
+                // built in, e.g. `undefined`.
+                None => match self.model.is_glob_reference(variable.identifier()) {
+                    true => Trust::Untrusted,
+                    false => Trust::Trusted,
+                },
+            },
+            ASTExpressionKind::Call(call) => self.call_trust(call),
+            ASTExpressionKind::Member(member) => self.trust_of(&member.object),
+            ASTExpressionKind::Index(index) => self
+                .trust_of(&index.object)
+                .and(self.trust_of(&index.index)),
+            ASTExpressionKind::Assignment(assignment) => self.assign_trust(assignment),
+        };
+        self.trust.insert(expression.id(), trust);
+    }
+
+    /// Project callbacks can be relied on, anything else may return whatever it likes.
+    fn call_trust(&self, call: &ASTCallExpression) -> Trust {
+        if call.callee_identifier() == Some("stateful") {
+            return call
+                .arguments()
+                .first()
+                .map_or(Trust::Trusted, |argument| self.trust_of(argument));
+        }
+        if call.callee_identifier() == Some("validate") {
+            return Trust::Trusted;
+        }
+        // Iterators go through the items of their collection, which `map` may replace.
+        if self.is_iterator_call(call)
+            && let Some((receiver, method)) = call.method()
+        {
+            let mapped = match (method.text(), call.arguments.first()) {
+                ("map", Some(argument)) => match argument.kind() {
+                    ASTExpressionKind::Closure(closure) => self.trust_of(&closure.body),
+                    _ => Trust::Untrusted,
+                },
+                _ => Trust::Trusted,
+            };
+            return self.trust_of(receiver).and(mapped);
+        }
+
+        match self.type_of(&call.callee) {
+            Type::Function(_) => self.trust_of(&call.callee),
+            _ => Trust::Untrusted,
+        }
+    }
+
+    /// Records the trust of the assigned variable's new value.
+    fn assign_trust(&mut self, assignment: &ASTAssignmentExpression) -> Trust {
+        let mut root = assignment.target.as_ref();
+        while let ASTExpressionKind::Member(ASTMemberExpression { object, .. })
+        | ASTExpressionKind::Index(ASTIndexExpression { object, .. }) = root.kind()
+        {
+            root = object;
+        }
+
+        let mut trust = self.trust_of(&assignment.value);
+        if assignment.operator.is_compound() {
+            trust = trust.and(self.trust_of(&assignment.target));
+        }
+        let Some(symbol) = self.model.resolved.get(root.id()).copied() else {
+            return trust;
+        };
+
+        if self.trusted_symbols.contains(&symbol) {
+            self.expect_trusted(trust, assignment.value.span());
+        }
+        // Changing part of a value can't make the rest of it trusted.
+        let value = match assignment.target_variable() {
+            Some(_) => trust,
+            None => trust.and(self.symbol_trust(symbol)),
+        };
+        self.set_symbol_trust(symbol, value);
+        // A callback may run at any time, so whatever it assigns could be seen anywhere.
+        if value == Trust::Untrusted && self.symbol_depth.get(&symbol) < Some(&self.function_depth)
+        {
+            self.tainted.insert(symbol);
+        }
+        trust
+    }
+}
+
+impl ASTVisitor for TypeChecker<'_> {
+    fn visit_statement(&mut self, statement: &ASTStatement) {
+        self.statement = Some(statement.id());
+        self.default_visit_statement(statement);
+        match statement.kind() {
+            ASTStatementKind::Expression(expression) => self.expect_consumed(expression),
+            ASTStatementKind::Return(return_statement) => {
+                if let Some(value) = return_statement.value() {
+                    self.expect_consumed(value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &ASTExpression) {
+        self.default_visit_expression(expression);
+        self.check(expression);
+    }
+
+    fn visit_call_expression(&mut self, call: &ASTCallExpression) {
+        self.visit_expression(&call.callee);
+        for ty in &call.type_arguments {
+            self.visit_type(ty);
+        }
+        // Closures passed to the methods of an iterator are called with its items.
+        let item = call
+            .method()
+            .and_then(|(receiver, _)| match self.type_of(receiver) {
+                Type::Iterator(item) => Some((*item, self.trust_of(receiver))),
+                _ => None,
+            });
+        for argument in &call.arguments {
+            if let ASTExpressionKind::Closure(_) = argument.kind() {
+                self.closure_item = item.clone();
+            }
+            self.visit_expression(argument);
+        }
+    }
+
+    fn visit_closure_expression(&mut self, closure: &ASTClosureExpression) {
+        let item = self.closure_item.take();
+        for (index, parameter) in closure.parameters.iter().enumerate() {
+            // Adaptors pass a single item, anything else may pass whatever it likes.
+            let passed = item.as_ref().filter(|_| index == 0);
+            let (item, trust) = passed.cloned().unwrap_or((Type::Unknown, Trust::Untrusted));
+            match parameter {
+                ASTClosureParameter::Binding(parameter) => {
+                    let (ty, trust) = match &parameter.ty {
+                        Some(annotation) => {
+                            let annotated = self.resolve(annotation);
+                            self.expect(&annotated, &item, &parameter.span);
+                            if !Self::is_trusted_slot(annotation) {
+                                (annotated, trust)
+                            } else {
+                                if passed.is_some() {
+                                    self.expect_trusted(trust, &parameter.span);
+                                }
+                                (annotated, Trust::Trusted)
+                            }
+                        }
+                        None => (item, trust),
+                    };
+                    self.declare(closure.body.id(), &parameter.identifier, ty, trust);
+                }
+                ASTClosureParameter::Tuple(items) => {
+                    for (position, identifier) in items.iter().enumerate() {
+                        let ty = match &item {
+                            Type::Tuple(elements) => {
+                                elements.get(position).cloned().unwrap_or(Type::Undefined)
+                            }
+                            Type::Array(element) => *element.clone(),
+                            _ => Type::Unknown,
+                        };
+                        self.declare(closure.body.id(), identifier, ty, trust);
+                    }
+                }
+            }
+        }
+        self.visit_deferred(|checker| checker.visit_expression(&closure.body));
+    }
+
+    fn visit_let_statement(&mut self, let_statement: &ASTLetStatement) {
+        let statement = self.declaring_statement();
+        self.visit_expression(&let_statement.initializer);
+        self.expect_consumed(&let_statement.initializer);
+
+        let stateful = let_statement.stateful_initializer();
+        // A `stateful(...)` binding holds its argument, rather than the call's result.
+        let initializer = match stateful.and_then(|call| call.arguments().first()) {
+            Some(argument) => argument,
+            None => &let_statement.initializer,
+        };
+        let (span, trust) = (initializer.span(), self.trust_of(initializer));
+        let initializer = self.type_of(initializer);
+
+        let ty = match &let_statement.type_annotation {
+            Some(annotation) => {
+                let annotated = self.resolve(annotation);
+                self.expect(&annotated, &initializer, span);
+                annotated
+            }
+            None if let_statement.is_const() && stateful.is_none() => initializer,
+            None => initializer.widen(),
+        };
+        let identifier = match &let_statement.pattern {
+            ASTPattern::Identifier(identifier) => identifier,
+            ASTPattern::Object(properties) => {
+                for property in properties {
+                    let ty = match &ty {
+                        Type::Object(members) => members
+                            .iter()
+                            .find(|member| member.name == property.text())
+                            .map_or(Type::Unknown, |member| member.ty.clone()),
+                        _ => Type::Unknown,
+                    };
+                    self.declare(statement, property, ty, trust);
+                }
+                return;
+            }
+        };
+        let symbol = self.declare(statement, identifier, ty, trust);
+
+        if let Some(annotation) = &let_statement.type_annotation
+            && Self::is_trusted_slot(annotation)
+        {
+            self.expect_trusted(trust, span);
+            self.trusted_symbols.extend(symbol);
+        }
+    }
+
+    fn visit_type_alias_statement(&mut self, type_alias: &ASTTypeAliasStatement) {
+        let name = type_alias.identifier().to_string();
+        // Guards against aliases referring to themselves.
+        self.aliases.insert(name.clone(), Type::Unknown);
+        let ty = self.resolve(&type_alias.ty);
+        let guarded = self.has_runtime_guard(&type_alias.ty, Some(&name));
+        self.guarded_aliases.insert(name.clone(), guarded);
+        self.aliases.insert(name, ty);
+    }
+
+    fn visit_component_statement(&mut self, component: &ASTComponentStatement) {
+        self.enter_generics(&component.generics);
+        self.visit_defaults(&component.parameters);
+        let mut props: Vec<Property> = component
+            .parameters
+            .iter()
+            .map(|parameter| {
+                let ty = self.parameter_type(parameter);
+                Property {
+                    name: parameter.identifier().to_string(),
+                    optional: parameter.is_optional() || ty.is_nullable(),
+                    ty,
+                    trusted: parameter.ty.as_ref().is_some_and(Self::is_trusted_slot),
+                }
+            })
+            .collect();
+        self.add_controllable_props(component, &mut props);
+        if let Some(base) = component.extended_component() {
+            self.inherit_props(component, base, &mut props);
+        }
+        self.declare_function(Type::Component(props.clone()));
+        self.components
+            .insert(component.identifier().to_string(), props);
+
+        self.declare_parameters(&component.parameters);
+        self.visit_function_body(&component.body);
+        self.leave_generics(&component.generics);
+    }
+
+    fn visit_impl_statement(&mut self, impl_statement: &ASTImplStatement) {
+        let Some(props) = self.components.get(impl_statement.identifier()).cloned() else {
+            return;
+        };
+
+        self.enter_generics(&impl_statement.generics);
+        for member in impl_statement.members() {
+            // Unknown props are reported by the `SymbolChecker`.
+            if let Some(prop) = props.iter().find(|prop| prop.name == member.identifier()) {
+                self.expect(&prop.ty, &self.resolve(&member.ty), member.span());
+            }
+        }
+        self.leave_generics(&impl_statement.generics);
+    }
+
+    fn visit_callback_statement(&mut self, callback: &ASTCallbackStatement) {
+        self.visit_defaults(&callback.parameters);
+        let function = FunctionType {
+            parameters: self.parameters(&callback.parameters),
+            return_type: Box::new(Type::Undefined),
+        };
+        self.declare_function(Type::Function(function));
+
+        self.declare_parameters(&callback.parameters);
+        self.visit_function_body(&callback.body);
+    }
+
+    fn visit_for_statement(&mut self, for_statement: &ASTForStatement) {
+        let statement = self.declaring_statement();
+        self.visit_expression(&for_statement.iterable);
+
+        let iterable = self.type_of(&for_statement.iterable);
+        let ty = match (&for_statement.kind, &iterable) {
+            (_, Type::Unknown | Type::Parameter(_)) => Type::Unknown,
+            (ASTForKind::In, Type::Array(_)) => Type::Number,
+            (ASTForKind::In, ty) if ty.is_string() => Type::Number,
+            (ASTForKind::Of, Type::Array(element)) => *element.clone(),
+            (ASTForKind::Of, ty) if ty.is_string() => Type::String,
+            _ => {
+                self.diagnostics
+                    .borrow_mut()
+                    .report_not_iterable(iterable.to_string(), *for_statement.iterable.span());
+                Type::Unknown
+            }
+        };
+        let trust = self.trust_of(&for_statement.iterable);
+        self.declare(statement, &for_statement.identifier, ty, trust);
+
+        self.visit_loop_body(&for_statement.body);
+    }
+
+    fn visit_while_statement(&mut self, while_statement: &ASTWhileStatement) {
+        self.visit_expression(&while_statement.condition);
+        self.visit_loop_body(&while_statement.body);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::compile;
+
+    const BADGE: &str = "
+        type User = { name: string, age?: number }
+        component Badge(label: string, count?: number) { return <span>{label}</span> }
+    ";
+
+    fn diagnostics(source: &str) -> Vec<String> {
+        compile(&format!("{}{}", BADGE, source)).diagnostics
+    }
+
+    #[test]
+    fn resolves_object_types() {
+        assert_eq!(
+            diagnostics("component Card(user: User) { return <Badge label={user.email} /> }"),
+            [
+                "Property 'email' does not exist on type '{ name: string; age?: number | undefined }'"
+            ]
+        );
+        assert_eq!(
+            diagnostics("component Card(user: User) { return <Badge label={user.age} /> }"),
+            ["Type 'number | undefined' is not assignable to type 'string'"]
+        );
+        assert_eq!(
+            diagnostics("component Card(user: User) { return <Badge label={user.name} /> }"),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn emits_object_types() {
+        let tsx = compile("pub type User = { name: string, age?: number, tags: string[] }")
+            .tsx
+            .unwrap();
+        assert_eq!(
+            tsx,
+            "export type User = { name: string; age?: number; tags: string[] };\n"
+        );
+    }
+
+    #[test]
+    fn checks_attributes_against_props() {
+        assert_eq!(
+            diagnostics("component Card() { return <Badge label={1} count={2} /> }"),
+            ["Type '1' is not assignable to type 'string'"]
+        );
+        assert_eq!(
+            diagnostics("component Card() { return <Badge label=\"new\" colour=\"red\" /> }"),
+            ["Component 'Badge' has no prop named 'colour'"]
+        );
+        assert_eq!(
+            diagnostics("component Card() { return <Badge key=\"k\" count={2} /> }"),
+            ["Component 'Badge' requires the prop 'label'"]
+        );
+    }
+
+    #[test]
+    fn checks_the_trust_of_attributes_passed_to_trusted_props() {
+        let link = "component Link(href: trusted string) { return <a href={href} /> }";
+        assert_eq!(
+            diagnostics(&format!(
+                "{} component Page(url: string) {{ return <Link href={{url}} /> }}",
+                link
+            )),
+            ["This value is untrusted, but a trusted value is required here"]
+        );
+        assert_eq!(
+            diagnostics(&format!(
+                "{} component Page() {{ return <Link href=\"/home\" /> }}",
+                link
+            )),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn distrusts_imported_values() {
+        let page = |import: &str| {
+            format!(
+                "{}
+                component Page() {{
+                    callback save(id: trusted string) {{ return id }}
+                    callback submit() {{ save(foreignValue) }}
+                    return <p />
+                }}",
+                import
+            )
+        };
+        let untrusted = ["This value is untrusted, but a trusted value is required here"];
+        assert_eq!(compile(&page("import api::*")).diagnostics, untrusted);
+        assert_eq!(
+            compile(&page("import api::foreignValue")).diagnostics,
+            untrusted
+        );
+    }
+
+    #[test]
+    fn passes_children_as_the_children_prop() {
+        let source = "
+            component Panel(title: string, children: unknown) { return <div>{children}</div> }
+            component Page() {
+                return <div><Panel title=\"Empty\" /><Panel title=\"Full\"><p /></Panel></div>
+            }
+        ";
+        assert_eq!(
+            compile(source).diagnostics,
+            ["Component 'Panel' requires the prop 'children'"]
+        );
+    }
+
+    #[test]
+    fn reports_types_without_runtime_guards() {
+        let source = "
+            type Tree = { label: string, children: Tree[] }
+            pub default component View<T>(data: unknown) {
+                const tree = validate<Tree>(data)
+                const item = validate<T>(data)
+                const items = validate<Array<T>>(data)
+                return <p>{tree.label}{item}{items}</p>
+            }
+        ";
+        let guard = |ty: &str| {
+            format!(
+                "No runtime check can be generated for type '{}', only for primitives, \
+                 literals, functions, and the arrays, unions, objects and type aliases of them",
+                ty
+            )
+        };
+        assert_eq!(compile(source).diagnostics, [guard("T"), guard("Array<T>")]);
+    }
+}
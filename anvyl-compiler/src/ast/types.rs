@@ -0,0 +1,379 @@
+use std::fmt::Display;
+
+use bumpalo::boxed::Box;
+
+use crate::prelude::{ASTArena, ASTParameter, NodeId, TextSpan, Token};
+
+#[derive(Debug)]
+pub enum ASTTypeKind<'a> {
+    Error(TextSpan),
+    /// A reference to a built-in type or type alias, e.g. `string`.
+    Named(Token),
+    /// A string or number literal type, e.g. `'date-created'`.
+    Literal(Token),
+    Union(ASTUnionType<'a>),
+    /// A type which may also be `undefined`, e.g. `string?`.
+    Optional(ASTOptionalType<'a>),
+    /// An array of a type, e.g. `T[]`.
+    Array(ASTArrayType<'a>),
+    /// A function signature, e.g. `(e: T) => string`.
+    Function(ASTFunctionType<'a>),
+    /// A type alias or built-in applied to type arguments, e.g. `Array<T>`.
+    Generic(ASTGenericType<'a>),
+    /// A slot which only accepts trusted values, e.g. `trusted string`.
+    Trusted(ASTTrustedType<'a>),
+    /// An object with the given properties, e.g. `{ name: string, age?: number }`.
+    Object(ASTObjectType<'a>),
+}
+
+#[derive(Debug)]
+pub struct ASTType<'a> {
+    pub(crate) id: NodeId,
+    pub(crate) kind: ASTTypeKind<'a>,
+    pub(crate) span: TextSpan,
+}
+
+impl<'a> ASTType<'a> {
+    pub fn new(kind: ASTTypeKind<'a>, id: NodeId, span: TextSpan) -> Self {
+        Self { id, kind, span }
+    }
+
+    pub fn named(identifier: Token, id: NodeId, span: TextSpan) -> Self {
+        ASTType::new(ASTTypeKind::Named(identifier), id, span)
+    }
+
+    pub fn literal(literal: Token, id: NodeId, span: TextSpan) -> Self {
+        ASTType::new(ASTTypeKind::Literal(literal), id, span)
+    }
+
+    pub fn union(members: Vec<ASTType<'a>>, id: NodeId, span: TextSpan) -> Self {
+        ASTType::new(ASTTypeKind::Union(ASTUnionType { members }), id, span)
+    }
+
+    pub fn optional(inner: ASTType<'a>, id: NodeId, span: TextSpan, arena: &'a ASTArena) -> Self {
+        ASTType::new(
+            ASTTypeKind::Optional(ASTOptionalType {
+                inner: Box::new_in(inner, arena),
+            }),
+            id,
+            span,
+        )
+    }
+
+    pub fn array(element: ASTType<'a>, id: NodeId, span: TextSpan, arena: &'a ASTArena) -> Self {
+        ASTType::new(
+            ASTTypeKind::Array(ASTArrayType {
+                element: Box::new_in(element, arena),
+            }),
+            id,
+            span,
+        )
+    }
+
+    pub fn function(
+        parameters: Vec<ASTParameter<'a>>,
+        return_type: ASTType<'a>,
+        id: NodeId,
+        span: TextSpan,
+        arena: &'a ASTArena,
+    ) -> Self {
+        ASTType::new(
+            ASTTypeKind::Function(ASTFunctionType {
+                parameters,
+                return_type: Box::new_in(return_type, arena),
+            }),
+            id,
+            span,
+        )
+    }
+
+    pub fn generic(
+        identifier: Token,
+        arguments: Vec<ASTType<'a>>,
+        id: NodeId,
+        span: TextSpan,
+    ) -> Self {
+        ASTType::new(
+            ASTTypeKind::Generic(ASTGenericType {
+                identifier,
+                arguments,
+            }),
+            id,
+            span,
+        )
+    }
+
+    pub fn trusted(inner: ASTType<'a>, id: NodeId, span: TextSpan, arena: &'a ASTArena) -> Self {
+        ASTType::new(
+            ASTTypeKind::Trusted(ASTTrustedType {
+                inner: Box::new_in(inner, arena),
+            }),
+            id,
+            span,
+        )
+    }
+
+    pub fn object(properties: Vec<ASTPropertyType<'a>>, id: NodeId, span: TextSpan) -> Self {
+        ASTType::new(ASTTypeKind::Object(ASTObjectType { properties }), id, span)
+    }
+
+    pub fn error(id: NodeId, span: TextSpan) -> Self {
+        ASTType::new(ASTTypeKind::Error(span), id, span)
+    }
+
+    pub fn kind(&self) -> &ASTTypeKind<'a> {
+        &self.kind
+    }
+
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    /// The source range of the whole node.
+    pub fn span(&self) -> &TextSpan {
+        &self.span
+    }
+
+    /// Whether the type must be parenthesised when nested in a postfix or union type,
+    /// e.g. `(a | b)[]`.
+    pub fn needs_parentheses(&self) -> bool {
+        matches!(
+            self.kind,
+            ASTTypeKind::Union(_) | ASTTypeKind::Function(_) | ASTTypeKind::Trusted(_)
+        )
+    }
+}
+
+/// Writes the type as it is written in Anvyl, e.g. in diagnostics.
+impl Display for ASTType<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let nested = |ty: &ASTType| match ty.needs_parentheses() {
+            true => format!("({})", ty),
+            false => ty.to_string(),
+        };
+        let join = |types: &[ASTType], separator: &str| {
+            types
+                .iter()
+                .map(ASTType::to_string)
+                .collect::<Vec<_>>()
+                .join(separator)
+        };
+        match &self.kind {
+            ASTTypeKind::Error(_) => write!(f, "?"),
+            ASTTypeKind::Named(identifier) | ASTTypeKind::Literal(identifier) => {
+                write!(f, "{}", identifier.text())
+            }
+            ASTTypeKind::Union(union) => write!(f, "{}", join(&union.members, " | ")),
+            ASTTypeKind::Optional(optional) => write!(f, "{}?", nested(&optional.inner)),
+            ASTTypeKind::Array(array) => write!(f, "{}[]", nested(&array.element)),
+            ASTTypeKind::Function(function) => {
+                let parameters: Vec<String> = function
+                    .parameters
+                    .iter()
+                    .map(|parameter| match &parameter.ty {
+                        Some(ty) => format!("{}: {}", parameter.identifier(), ty),
+                        None => parameter.identifier().to_string(),
+                    })
+                    .collect();
+                write!(f, "({}) => {}", parameters.join(", "), function.return_type)
+            }
+            ASTTypeKind::Generic(generic) => write!(
+                f,
+                "{}<{}>",
+                generic.identifier(),
+                join(&generic.arguments, ", ")
+            ),
+            ASTTypeKind::Trusted(trusted) => write!(f, "trusted {}", trusted.inner),
+            ASTTypeKind::Object(object) => {
+                let properties: Vec<String> = object
+                    .properties
+                    .iter()
+                    .map(|property| match property.optional {
+                        true => format!("{}?: {}", property.identifier(), property.ty),
+                        false => format!("{}: {}", property.identifier(), property.ty),
+                    })
+                    .collect();
+                match properties.is_empty() {
+                    true => write!(f, "{{}}"),
+                    false => write!(f, "{{ {} }}", properties.join(", ")),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ASTUnionType<'a> {
+    pub(crate) members: Vec<ASTType<'a>>,
+}
+
+impl<'a> ASTUnionType<'a> {
+    pub fn members(&self) -> &[ASTType<'a>] {
+        &self.members
+    }
+}
+
+#[derive(Debug)]
+pub struct ASTOptionalType<'a> {
+    pub(crate) inner: Box<'a, ASTType<'a>>,
+}
+
+impl<'a> ASTOptionalType<'a> {
+    pub fn inner(&self) -> &ASTType<'a> {
+        &self.inner
+    }
+}
+
+#[derive(Debug)]
+pub struct ASTArrayType<'a> {
+    pub(crate) element: Box<'a, ASTType<'a>>,
+}
+
+impl<'a> ASTArrayType<'a> {
+    pub fn element(&self) -> &ASTType<'a> {
+        &self.element
+    }
+}
+
+#[derive(Debug)]
+pub struct ASTFunctionType<'a> {
+    pub(crate) parameters: Vec<ASTParameter<'a>>,
+    pub(crate) return_type: Box<'a, ASTType<'a>>,
+}
+
+impl<'a> ASTFunctionType<'a> {
+    pub fn parameters(&self) -> &[ASTParameter<'a>] {
+        &self.parameters
+    }
+
+    pub fn return_type(&self) -> &ASTType<'a> {
+        &self.return_type
+    }
+}
+
+#[derive(Debug)]
+pub struct ASTTrustedType<'a> {
+    pub(crate) inner: Box<'a, ASTType<'a>>,
+}
+
+impl<'a> ASTTrustedType<'a> {
+    pub fn inner(&self) -> &ASTType<'a> {
+        &self.inner
+    }
+}
+
+#[derive(Debug)]
+pub struct ASTObjectType<'a> {
+    pub(crate) properties: Vec<ASTPropertyType<'a>>,
+}
+
+impl<'a> ASTObjectType<'a> {
+    pub fn properties(&self) -> &[ASTPropertyType<'a>] {
+        &self.properties
+    }
+}
+
+/// A property of an object type, e.g. `age?: number`.
+#[derive(Debug)]
+pub struct ASTPropertyType<'a> {
+    pub(crate) identifier: Token,
+    pub(crate) optional: bool,
+    pub(crate) ty: ASTType<'a>,
+}
+
+impl<'a> ASTPropertyType<'a> {
+    pub fn new(identifier: Token, optional: bool, ty: ASTType<'a>) -> Self {
+        Self {
+            identifier,
+            optional,
+            ty,
+        }
+    }
+
+    pub fn identifier(&self) -> &str {
+        &self.identifier.text
+    }
+
+    /// Whether the property may be left out, as it is marked with `?`.
+    pub fn is_optional(&self) -> bool {
+        self.optional
+    }
+
+    pub fn ty(&self) -> &ASTType<'a> {
+        &self.ty
+    }
+}
+
+#[derive(Debug)]
+pub struct ASTGenericType<'a> {
+    pub(crate) identifier: Token,
+    pub(crate) arguments: Vec<ASTType<'a>>,
+}
+
+impl<'a> ASTGenericType<'a> {
+    pub fn identifier(&self) -> &str {
+        &self.identifier.text
+    }
+
+    pub fn arguments(&self) -> &[ASTType<'a>] {
+        &self.arguments
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ASTGenericConstraintKind {
+    /// `T extends U`, the argument must be assignable to `U`.
+    Extends,
+    /// `T excludes U`, the argument must not be assignable to `U`.
+    Excludes,
+}
+
+#[derive(Debug)]
+pub struct ASTGenericConstraint<'a> {
+    pub(crate) kind: ASTGenericConstraintKind,
+    pub(crate) ty: ASTType<'a>,
+}
+
+impl<'a> ASTGenericConstraint<'a> {
+    pub fn kind(&self) -> &ASTGenericConstraintKind {
+        &self.kind
+    }
+
+    pub fn ty(&self) -> &ASTType<'a> {
+        &self.ty
+    }
+}
+
+/// A generic parameter declaration, e.g. `T` or `T excludes string`.
+#[derive(Debug)]
+pub struct ASTGenericParameter<'a> {
+    pub(crate) identifier: Token,
+    pub(crate) constraint: Option<ASTGenericConstraint<'a>>,
+    pub(crate) span: TextSpan,
+}
+
+impl<'a> ASTGenericParameter<'a> {
+    pub fn new(
+        identifier: Token,
+        constraint: Option<ASTGenericConstraint<'a>>,
+        span: TextSpan,
+    ) -> Self {
+        Self {
+            identifier,
+            constraint,
+            span,
+        }
+    }
+
+    pub fn span(&self) -> &TextSpan {
+        &self.span
+    }
+
+    pub fn identifier(&self) -> &str {
+        &self.identifier.text
+    }
+
+    pub fn constraint(&self) -> Option<&ASTGenericConstraint<'a>> {
+        self.constraint.as_ref()
+    }
+}
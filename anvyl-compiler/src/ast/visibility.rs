@@ -0,0 +1,470 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::ast::symbols::SymbolChecker;
+use crate::ast::typechecker::{Property, TypeChecker};
+use crate::config::Config;
+use crate::prelude::*;
+
+/// The top-level declarations of a file, along with how far each can be imported from.
+#[derive(Debug, Default)]
+pub struct ModuleExports {
+    declarations: HashMap<String, Visibility>,
+    /// The name of the `pub default` declaration, if the file has one.
+    default: Option<String>,
+    /// The props of each component of the file, once it has been type checked.
+    components: HashMap<String, Vec<Property>>,
+}
+
+impl ModuleExports {
+    pub fn new(ast: &AST) -> Self {
+        let mut exports = Self::default();
+        for statement in &ast.statements {
+            let (names, visibility): (Vec<&str>, _) = match statement.kind() {
+                ASTStatementKind::Component(component) => {
+                    (vec![component.identifier()], &component.visibility)
+                }
+                ASTStatementKind::LetStatement(let_statement) => (
+                    let_statement
+                        .pattern
+                        .identifiers()
+                        .iter()
+                        .map(|identifier| identifier.text())
+                        .collect(),
+                    &let_statement.visibility,
+                ),
+                ASTStatementKind::TypeAlias(type_alias) => {
+                    (vec![type_alias.identifier()], &type_alias.visibility)
+                }
+                _ => continue,
+            };
+
+            if visibility.is_default()
+                && let Some(name) = names.first()
+            {
+                exports.default.get_or_insert(name.to_string());
+            }
+            for name in names {
+                exports
+                    .declarations
+                    .insert(name.to_string(), visibility.visibility());
+            }
+        }
+        exports
+    }
+
+    /// Like `new`, but also type checking the file to know the props of its components.
+    /// Errors in the file are reported when compiling that file, rather than here.
+    pub fn checked(ast: &AST) -> Self {
+        let diagnostics = DiagnosticsBag::new_ref_cell();
+        let mut symbol_checker = SymbolChecker::new(Rc::clone(&diagnostics));
+        symbol_checker.check(ast);
+        let mut type_checker = TypeChecker::new(symbol_checker.model(), diagnostics);
+        ast.visit(&mut type_checker);
+
+        Self {
+            components: type_checker.into_components(),
+            ..Self::new(ast)
+        }
+    }
+
+    /// The declaration a file is imported as when importing the item named `name`: the
+    /// declaration named after it, or otherwise the default export of the file.
+    fn item(&self, name: &str) -> Option<&str> {
+        self.declared(name).or(self.default.as_deref())
+    }
+
+    /// The declaration named `name`, if the file has one.
+    fn declared(&self, name: &str) -> Option<&str> {
+        self.declarations
+            .get_key_value(name)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// The visibility of the declaration named `name`.
+    fn visibility(&self, name: &str) -> Option<Visibility> {
+        self.declarations.get(name).copied()
+    }
+}
+
+/// A file of the project which may be imported from.
+#[derive(Debug)]
+pub enum ProjectFile {
+    /// An Anvyl file, whose exports are known.
+    Anvyl(ModuleExports),
+    /// Any other file, e.g. a TypeScript one, whose exports aren't checked.
+    Foreign,
+}
+
+/// The files of a project which a file's imports may reach into, keyed by module path,
+/// e.g. `["context", "AuthContext"]` for `src/context/AuthContext.avyl`.
+#[derive(Debug, Default)]
+pub struct ProjectModules {
+    files: HashMap<Vec<String>, ProjectFile>,
+}
+
+impl ProjectModules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, module: Vec<String>, file: ProjectFile) {
+        self.files.insert(module, file);
+    }
+
+    pub fn get(&self, module: &[String]) -> Option<&ProjectFile> {
+        self.files.get(module)
+    }
+
+    /// The file an item named `name` is imported from, when imported from the project folder
+    /// or file at `path`: the file named after the item, or otherwise the file at `path`,
+    /// which has the item as a named export. Gives the module of the file and the file.
+    fn source(&self, path: &[String], name: &str) -> Option<(Vec<String>, &ProjectFile)> {
+        let mut item = path.to_vec();
+        item.push(name.to_string());
+        match self.files.get(&item) {
+            Some(file) => Some((item, file)),
+            None => self.files.get(path).map(|file| (path.to_vec(), file)),
+        }
+    }
+
+    /// The declaration `name` refers to when imported from `path`, along with the module and
+    /// exports of the Anvyl file declaring it, if it is one.
+    fn declaration(
+        &self,
+        path: &[String],
+        name: &str,
+    ) -> Option<(Vec<String>, &ModuleExports, Option<&str>)> {
+        let (module, ProjectFile::Anvyl(exports)) = self.source(path, name)? else {
+            return None;
+        };
+        let declaration = match module.len() > path.len() {
+            true => exports.item(name),
+            false => exports.declared(name),
+        };
+        Some((module, exports, declaration))
+    }
+
+    /// The props of the components a file imports from Anvyl files of the project, keyed by
+    /// the name they are imported as.
+    pub fn imported_components(
+        &self,
+        ast: &AST,
+        model: &SemanticModel,
+    ) -> HashMap<String, Vec<Property>> {
+        let mut components = HashMap::new();
+        for item in imported_items(ast, model) {
+            if let Some((_, exports, Some(declaration))) = self.declaration(&item.path, item.name)
+                && let Some(props) = exports.components.get(declaration)
+            {
+                components.insert(item.name.to_string(), props.clone());
+            }
+        }
+        components
+    }
+
+    /// Whether `name`, imported from the project folder or file at `path`, is a named export
+    /// of the file at `path` rather than the file named after it, e.g. `MAX_LENGTH` in
+    /// `import components::chat::Message::{MAX_LENGTH}`.
+    pub(crate) fn is_named_export(&self, path: &[&str], name: &str) -> bool {
+        let file: Vec<String> = path.iter().map(|segment| segment.to_string()).collect();
+        let mut item = file.clone();
+        item.push(name.to_string());
+        !self.files.contains_key(&item) && self.files.contains_key(&file)
+    }
+}
+
+/// An item imported from a folder or file of the project.
+struct ImportedItem<'t> {
+    /// The token importing the item, which is the `*` of a glob import.
+    token: &'t Token,
+    name: &'t str,
+    /// The module path the item is imported from, e.g. `["context"]` for
+    /// `import context::AuthContext`.
+    path: Vec<String>,
+    glob: bool,
+}
+
+impl ImportedItem<'_> {
+    /// The module of the file named after the item.
+    fn module(&self) -> Vec<String> {
+        let mut module = self.path.clone();
+        module.push(self.name.to_string());
+        module
+    }
+}
+
+/// Each item imported by the import statements of a file. The names a `*` import provides
+/// are the ones the file uses.
+fn imported_items<'t>(ast: &'t AST, model: &'t SemanticModel) -> Vec<ImportedItem<'t>> {
+    let mut items = Vec::new();
+    for statement in &ast.statements {
+        let ASTStatementKind::Import(import) = statement.kind() else {
+            continue;
+        };
+        let path: Vec<String> = import.path().map(str::to_string).collect();
+        match &import.items {
+            ASTImportItems::Named(names) => {
+                for token in names {
+                    items.push(ImportedItem {
+                        token,
+                        name: token.text(),
+                        path: path.clone(),
+                        glob: false,
+                    });
+                }
+            }
+            // The names a `*` import provides aren't written out, so they are reported
+            // at the `*` itself.
+            ASTImportItems::Glob(glob) => {
+                for name in model.glob_references() {
+                    items.push(ImportedItem {
+                        token: glob,
+                        name,
+                        path: path.clone(),
+                        glob: true,
+                    });
+                }
+            }
+        }
+    }
+    items
+}
+
+/// Checks that the items a file imports from other files of the project are visible to it.
+///
+/// The files of the folders aliased in the `anvyl.toml` are modules named after the item
+/// they export, so `import context::AuthContext` imports the `context::AuthContext` module,
+/// and a name used through `import components::*` the `components::<name>` module. Other
+/// declarations of a file are imported by name from its module, e.g.
+/// `import components::chat::Message::{MAX_LENGTH}`.
+pub struct VisibilityChecker<'a> {
+    config: &'a Config,
+    /// The module path of the folder holding the file being checked, e.g. `components`.
+    folder: Vec<String>,
+    diagnostics: DiagnosticsBagCell,
+}
+
+impl<'a> VisibilityChecker<'a> {
+    pub fn new(config: &'a Config, folder: Vec<String>, diagnostics: DiagnosticsBagCell) -> Self {
+        Self {
+            config,
+            folder,
+            diagnostics,
+        }
+    }
+
+    /// The modules the imports of a file may reach into, e.g. `["context", "AuthContext"]`,
+    /// which `check` needs to know of when they exist: the file named after each item, and
+    /// the file it would otherwise be a named export of.
+    pub fn dependencies(&self, ast: &AST, model: &SemanticModel) -> Vec<Vec<String>> {
+        let mut modules: Vec<Vec<String>> = Vec::new();
+        for item in self.imported_items(ast, model) {
+            for module in [item.module(), item.path] {
+                if !modules.contains(&module) {
+                    modules.push(module);
+                }
+            }
+        }
+        modules
+    }
+
+    /// Reports the imported items the file can't see, given the files of the project.
+    /// Items of files other than Anvyl ones, e.g. TypeScript files, aren't checked.
+    pub fn check(&self, ast: &AST, model: &SemanticModel, modules: &ProjectModules) {
+        let mut diagnostics = self.diagnostics.borrow_mut();
+        for item in self.imported_items(ast, model) {
+            let (file, visibility) = match modules.source(&item.path, item.name) {
+                Some(_) => match modules.declaration(&item.path, item.name) {
+                    Some((file, exports, declaration)) => {
+                        (file, declaration.and_then(|name| exports.visibility(name)))
+                    }
+                    // Items of files other than Anvyl ones aren't checked.
+                    None => continue,
+                },
+                // The names a `*` import provides may come from another `*` import.
+                None if item.glob => continue,
+                None => {
+                    diagnostics.report_missing_module(item.token, &item.module().join("::"));
+                    continue;
+                }
+            };
+            let path = file.join("::");
+            let Some(visibility) = visibility else {
+                diagnostics.report_missing_export(item.token, item.name, &path);
+                continue;
+            };
+
+            let folder = &file[..file.len() - 1];
+            let visible_from = match visibility {
+                Visibility::Public => continue,
+                Visibility::Private => {
+                    diagnostics.report_private_import(item.token, item.name, &path);
+                    continue;
+                }
+                Visibility::Directory => folder,
+                Visibility::Super => &folder[..folder.len().saturating_sub(1)],
+            };
+            if !self.folder.starts_with(visible_from) {
+                diagnostics.report_inaccessible_import(
+                    item.token,
+                    item.name,
+                    &visible_from.join("::"),
+                );
+            }
+        }
+    }
+
+    /// The items imported from folders and files of the project.
+    fn imported_items<'t>(&self, ast: &'t AST, model: &'t SemanticModel) -> Vec<ImportedItem<'t>> {
+        imported_items(ast, model)
+            .into_iter()
+            .filter(|item| {
+                let path: Vec<&str> = item.path.iter().map(String::as_str).collect();
+                self.config.alias(&path).is_some()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::ast::symbols::SymbolChecker;
+    use crate::testing::{compile_with, messages, parse};
+
+    const MESSAGE: &str = "
+        pub default component Message(text: string) { return <p>{text}</p> }
+        pub(dir) const MAX_LENGTH = 280
+        pub const MIN_LENGTH = 1
+        const PAGE_SIZE = 20
+    ";
+
+    fn project() -> (Config, ProjectModules) {
+        let config = Config::parse("[aliases]\ncomponents = \"@components\"\n").unwrap();
+        let mut modules = ProjectModules::new();
+        modules.insert(
+            vec!["components".into(), "chat".into(), "Message".into()],
+            ProjectFile::Anvyl(ModuleExports::checked(&parse(MESSAGE))),
+        );
+        modules.insert(
+            vec!["components".into(), "chat".into(), "format".into()],
+            ProjectFile::Foreign,
+        );
+        (config, modules)
+    }
+
+    /// The diagnostics of checking the imports of a file in the `folder` of the project.
+    fn check(folder: &[&str], source: &str) -> Vec<String> {
+        let (config, modules) = project();
+        let ast = parse(source);
+        let diagnostics = DiagnosticsBag::new_ref_cell();
+        let mut symbol_checker = SymbolChecker::new(Rc::clone(&diagnostics));
+        symbol_checker.check(&ast);
+        diagnostics.borrow_mut().diagnostics.clear();
+
+        let folder = folder.iter().map(|segment| segment.to_string()).collect();
+        VisibilityChecker::new(&config, folder, Rc::clone(&diagnostics)).check(
+            &ast,
+            symbol_checker.model(),
+            &modules,
+        );
+        messages(&diagnostics)
+    }
+
+    #[test]
+    fn checks_named_exports_of_a_file() {
+        let source = "import components::chat::Message::{MAX_LENGTH, MIN_LENGTH, PAGE_SIZE}";
+        assert_eq!(
+            check(&["components", "chat"], source),
+            [
+                "'PAGE_SIZE' is private to 'components::chat::Message', declare it 'pub' to import it"
+            ]
+        );
+        assert_eq!(
+            check(&["pages"], source),
+            [
+                "'MAX_LENGTH' is only visible within 'components::chat'",
+                "'PAGE_SIZE' is private to 'components::chat::Message', declare it 'pub' to import it",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_missing_modules() {
+        assert_eq!(
+            check(&["pages"], "import components::chat::{Mesage}"),
+            ["There is no module 'components::chat::Mesage' to import"]
+        );
+        assert_eq!(
+            check(&["pages"], "import components::chat::Mesage::{MAX_LENGTH}"),
+            ["There is no module 'components::chat::Mesage::MAX_LENGTH' to import"]
+        );
+    }
+
+    #[test]
+    fn skips_files_other_than_anvyl_ones() {
+        assert_eq!(
+            check(&["pages"], "import components::chat::format::{formatDate}"),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn imports_named_exports_by_name() {
+        let (config, modules) = project();
+        let compilation = compile_with(
+            "import components::chat::{Message}
+            import components::chat::Message::{MIN_LENGTH}
+            import components::chat::format::{formatDate}
+            pub default component Chat() { return <Message text={formatDate(MIN_LENGTH)} /> }",
+            &config,
+            &modules,
+        );
+        assert_eq!(compilation.diagnostics, Vec::<String>::new());
+        assert!(compilation.tsx.unwrap().starts_with(
+            "import Message, { MIN_LENGTH } from \"@components/chat/Message\";\n\
+             import { formatDate } from \"@components/chat/format\";\n"
+        ));
+    }
+
+    #[test]
+    fn omits_only_the_props_overriding_those_of_imported_components() {
+        let (config, modules) = project();
+        let compilation = compile_with(
+            "import components::chat::{Message}
+            extend_props(Message)
+            pub default component Quote(text: string, author: string) {
+                return <Message text={text} />
+            }",
+            &config,
+            &modules,
+        );
+        assert_eq!(
+            compilation.diagnostics,
+            [
+                "Prop 'text' overrides the prop it inherits from 'Message' with a compatible type, \
+              which wouldn't need omitting"
+            ]
+        );
+        assert!(compilation.tsx.unwrap().contains(
+            "export interface QuoteProps extends Omit<ComponentProps<typeof Message>, 'text'> {"
+        ));
+
+        let compilation = compile_with(
+            "import components::chat::{Message}
+            extend_props(Message)
+            pub default component Signature(author: string) { return <Message text={author} /> }",
+            &config,
+            &modules,
+        );
+        assert_eq!(compilation.diagnostics, Vec::<String>::new());
+        assert!(
+            compilation.tsx.unwrap().contains(
+                "export interface SignatureProps extends ComponentProps<typeof Message> {"
+            )
+        );
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use super::sourcemap::Mapping;
+use crate::ast::visibility::ProjectModules;
+use crate::config::Config;
+use crate::prelude::*;
+
+/// The module React's hooks are imported from.
+const REACT: &str = "react";
+
+/// A TypeScript import declaration, e.g. `import AuthContext from "@contexts/AuthContext"`.
+#[derive(Debug, Clone)]
+pub(crate) struct Import {
+    pub(crate) default: Option<String>,
+    pub(crate) named: Vec<String>,
+    pub(crate) specifier: String,
+    /// Where the `import` statement this was resolved from starts, `None` for imports
+    /// which the generated code needs by itself.
+    pub(crate) origin: Option<usize>,
+}
+
+impl Display for Import {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let named = (!self.named.is_empty()).then(|| format!("{{ {} }}", self.named.join(", ")));
+        let bindings: Vec<String> = self.default.iter().cloned().chain(named).collect();
+        // An import for its side effects only, e.g. a stylesheet.
+        if bindings.is_empty() {
+            return write!(f, "import \"{}\"", self.specifier);
+        }
+        write!(
+            f,
+            "import {} from \"{}\"",
+            bindings.join(", "),
+            self.specifier
+        )
+    }
+}
+
+/// Every import of a generated file, merged into a single declaration per module specifier.
+///
+/// Declarations are kept in the order their specifier was first imported, and names in the
+/// order they were first imported, so the same file always gets the same imports.
+#[derive(Debug, Default)]
+pub(crate) struct ImportCollection {
+    imports: Vec<Import>,
+}
+
+impl ImportCollection {
+    /// Adds an import, merging it into an earlier one from the same module, unless both
+    /// have a default import under a different name.
+    pub(crate) fn add(&mut self, import: Import) {
+        let existing = self.imports.iter_mut().find(|existing| {
+            existing.specifier == import.specifier
+                && (existing.default.is_none()
+                    || import.default.is_none()
+                    || existing.default == import.default)
+        });
+        let Some(existing) = existing else {
+            self.imports.push(import);
+            return;
+        };
+
+        existing.default = existing.default.take().or(import.default);
+        existing.origin = existing.origin.or(import.origin);
+        for name in import.named {
+            if !existing.named.contains(&name) {
+                existing.named.push(name);
+            }
+        }
+    }
+
+    /// Imports a named export of React, e.g. a hook which `stateful` was lowered to.
+    pub(crate) fn add_react(&mut self, name: &str) {
+        self.add(Import {
+            default: None,
+            named: vec![name.to_string()],
+            specifier: REACT.to_string(),
+            origin: None,
+        });
+    }
+
+    /// Writes each import on a line of its own, followed by a blank line, along with
+    /// mappings back to the `import` statements they were resolved from.
+    pub(crate) fn render(&self) -> (String, Vec<Mapping>) {
+        let mut result = String::new();
+        let mut mappings = Vec::new();
+        for import in &self.imports {
+            if let Some(origin) = import.origin {
+                mappings.push(Mapping {
+                    generated: result.len(),
+                    original: origin,
+                });
+            }
+            result.push_str(&format!("{};\n", import));
+        }
+        if !result.is_empty() {
+            result.push('\n');
+        }
+        (result, mappings)
+    }
+}
+
+/// Where the items of an `import` statement come from.
+struct Module {
+    specifier: String,
+    /// Whether the module is a folder of the project, aliased in the `anvyl.toml`, rather
+    /// than a package.
+    local: bool,
+    /// The module path, e.g. `react::dom`.
+    path: String,
+}
+
+/// Resolves `import` statements to TypeScript imports.
+///
+/// Module paths starting with an alias from the `anvyl.toml` are folders of the project,
+/// where each file default exports the item it is named after, so
+/// `import context::AuthContext` becomes `import AuthContext from "@contexts/AuthContext"`.
+/// Items of a project file which no file is named after are its named exports, so
+/// `import context::AuthContext::{useAuth}` becomes
+/// `import { useAuth } from "@contexts/AuthContext"`.
+/// Any other path names a package, whose items are named exports, so `import react::use`
+/// becomes `import { use } from "react"`.
+///
+/// `*` imports are narrowed down to the names the file actually uses, and are left out
+/// entirely when it uses none of them.
+pub(crate) struct ModuleResolver<'a> {
+    config: &'a Config,
+    modules: &'a ProjectModules,
+}
+
+impl<'a> ModuleResolver<'a> {
+    pub(crate) fn new(config: &'a Config, modules: &'a ProjectModules) -> Self {
+        Self { config, modules }
+    }
+
+    /// The TypeScript imports of every `import` statement of a file.
+    pub(crate) fn resolve(&self, ast: &AST, model: &SemanticModel) -> ImportCollection {
+        let statements: Vec<&ASTImportStatement> = ast
+            .statements
+            .iter()
+            .filter_map(|statement| match statement.kind() {
+                ASTStatementKind::Import(import) => Some(import.as_ref()),
+                _ => None,
+            })
+            .collect();
+
+        let globs: Vec<(&TextSpan, Module)> = statements
+            .iter()
+            .filter(|import| matches!(import.items, ASTImportItems::Glob(_)))
+            .map(|import| {
+                let path: Vec<&str> = import.path().collect();
+                (&import.keyword.span, self.module(&path))
+            })
+            .collect();
+        let mut glob_names: HashMap<&TextSpan, Vec<String>> = HashMap::new();
+        for name in model.glob_references() {
+            if let Some((keyword, _)) = self.glob_for(&globs, name) {
+                glob_names.entry(keyword).or_default().push(name.clone());
+            }
+        }
+
+        let mut imports = ImportCollection::default();
+        for import in statements {
+            let path: Vec<&str> = import.path().collect();
+            let origin = Some(import.keyword.span.start());
+            let resolved = match &import.items {
+                ASTImportItems::Glob(_) => match glob_names.remove(&import.keyword.span) {
+                    Some(named) => vec![Import {
+                        default: None,
+                        named,
+                        specifier: self.module(&path).specifier,
+                        origin,
+                    }],
+                    None => continue,
+                },
+                ASTImportItems::Named(items) => self.named_imports(&path, items, origin),
+            };
+            for import in resolved {
+                imports.add(import);
+            }
+        }
+        imports
+    }
+
+    fn module(&self, path: &[&str]) -> Module {
+        let (specifier, local) = match self.config.alias(path) {
+            Some((alias, rest)) => {
+                let segments = std::iter::once(alias).chain(rest.iter().copied());
+                (segments.collect::<Vec<_>>().join("/"), true)
+            }
+            None => (path.join("/"), false),
+        };
+        Module {
+            specifier,
+            local,
+            path: path.join("::"),
+        }
+    }
+
+    fn named_imports(&self, path: &[&str], items: &[Token], origin: Option<usize>) -> Vec<Import> {
+        let items = items.iter().map(|item| item.text());
+        let module = self.module(path);
+        // `import react;` imports a package as a whole.
+        if path.is_empty() || module.local {
+            return items
+                .map(|item| match self.modules.is_named_export(path, item) {
+                    true => Import {
+                        default: None,
+                        named: vec![item.to_string()],
+                        specifier: module.specifier.clone(),
+                        origin,
+                    },
+                    false => {
+                        let path: Vec<&str> = path.iter().copied().chain([item]).collect();
+                        Import {
+                            default: Some(item.to_string()),
+                            named: Vec::new(),
+                            specifier: self.module(&path).specifier,
+                            origin,
+                        }
+                    }
+                })
+                .collect();
+        }
+
+        vec![Import {
+            default: None,
+            named: items.map(str::to_string).collect(),
+            specifier: module.specifier,
+            origin,
+        }]
+    }
+
+    /// The `*` import providing `name`: the first package known to export it, or otherwise
+    /// the first module whose exports aren't known, such as a folder of the project.
+    fn glob_for<'g>(
+        &self,
+        globs: &'g [(&'g TextSpan, Module)],
+        name: &str,
+    ) -> Option<&'g (&'g TextSpan, Module)> {
+        let exports = |module: &Module| match module.local {
+            true => None,
+            false => self.config.exports(&module.path),
+        };
+        globs
+            .iter()
+            .find(|(_, module)| {
+                exports(module).is_some_and(|exports| exports.iter().any(|export| export == name))
+            })
+            .or_else(|| globs.iter().find(|(_, module)| exports(module).is_none()))
+    }
+}
@@ -0,0 +1,638 @@
+use std::collections::HashSet;
+
+use super::TSXGenerator;
+use crate::ast::iterators::{IteratorChain, IteratorConsumer, IteratorStage};
+use crate::prelude::*;
+
+/// An item going through the loop an iterator chain is fused into.
+enum Item {
+    /// An item held by a variable of the loop.
+    Named(String),
+    /// The position and item `enumerate()` pairs up, held by variables of their own.
+    Pair(String, String),
+}
+
+impl Item {
+    fn expression(&self) -> String {
+        match self {
+            Item::Named(name) => name.clone(),
+            Item::Pair(index, value) => format!("[{}, {}]", index, value),
+        }
+    }
+}
+
+/// The names declared by the loop an iterator chain is fused into, which must not shadow
+/// anything the chain refers to.
+struct LoopNames {
+    /// Every name the chain refers to or declares, along with the symbol it stands for.
+    referenced: Vec<(String, Option<SymbolId>)>,
+    declared: HashSet<String>,
+}
+
+impl LoopNames {
+    /// Declares `preferred`, or the first free name among `preferred2`, `preferred3`...,
+    /// which can't collide with anything in the source as Anvyl identifiers have no digits.
+    ///
+    /// `symbol` is the closure parameter the name stands for, which may reuse its own name.
+    fn declare(&mut self, preferred: &str, symbol: Option<SymbolId>) -> String {
+        let free = |name: &str| {
+            !self.declared.contains(name)
+                && self.referenced.iter().all(|(referenced, resolved)| {
+                    referenced != name || (symbol.is_some() && *resolved == symbol)
+                })
+        };
+        let name = std::iter::once(preferred.to_string())
+            .chain((2..).map(|suffix| format!("{}{}", preferred, suffix)))
+            .find(|name| free(name))
+            .expect("there are infinitely many suffixed names");
+        self.declared.insert(name.clone());
+        name
+    }
+}
+
+/// Collects the names an iterator chain refers to or declares, as they'll be written.
+struct References<'a, 'g, 'ast> {
+    generator: &'a TSXGenerator<'g, 'ast>,
+    found: Vec<(String, Option<SymbolId>)>,
+}
+
+impl ASTVisitor for References<'_, '_, '_> {
+    fn visit_expression(&mut self, expression: &ASTExpression) {
+        if let ASTExpressionKind::Variable(variable) = expression.kind() {
+            let symbol = self.generator.model.resolved.get(expression.id()).copied();
+            let name = symbol
+                .and_then(|symbol| self.generator.renamed.get(&symbol))
+                .map_or(variable.identifier(), String::as_str);
+            self.found.push((name.to_string(), symbol));
+        }
+        self.default_visit_expression(expression);
+    }
+
+    fn visit_closure_expression(&mut self, expression: &ASTClosureExpression) {
+        for identifier in expression.identifiers() {
+            let symbol = self.generator.parameter_symbol(expression, identifier);
+            self.found.push((identifier.text().to_string(), symbol));
+        }
+        self.visit_expression(&expression.body);
+    }
+}
+
+/// The next closure an item reaches, whose parameter the variable holding it is named after,
+/// unless `enumerate()` pairs it up first.
+fn next_closure<'a, 'ast>(
+    stages: &[IteratorStage<'a, 'ast>],
+    consumer: &IteratorConsumer<'a, 'ast>,
+) -> Option<&'a ASTClosureExpression<'ast>> {
+    for stage in stages {
+        match stage {
+            IteratorStage::Map(closure) | IteratorStage::Filter(closure) => return Some(closure),
+            IteratorStage::Take(_) => {}
+            IteratorStage::Enumerate => return None,
+        }
+    }
+    consumer.closure()
+}
+
+impl TSXGenerator<'_, '_> {
+    /// Writes an iterator chain as a single loop over its source, wrapped in a function which
+    /// is called right away so the chain stays an expression, e.g.
+    /// `items.iter().map(|item| item.price).sum()` becomes:
+    ///
+    /// ```ts
+    /// (() => {
+    ///   let sum = 0;
+    ///   for (const item of items) {
+    ///     const price = item.price;
+    ///     sum += price;
+    ///   }
+    ///   return sum;
+    /// })()
+    /// ```
+    ///
+    /// Closures are inlined into the loop, their parameters renamed to the variables
+    /// holding the items they're called with.
+    pub(super) fn push_iterator_chain(&mut self, call: &ASTCallExpression, chain: &IteratorChain) {
+        let mut references = References {
+            generator: self,
+            found: Vec::new(),
+        };
+        references.visit_call_expression(call);
+        // Parameters of the inlined closures are renamed to whatever holds their items.
+        let inlined: HashSet<SymbolId> = chain
+            .closures()
+            .flat_map(|closure| self.model.declared_symbols(closure.body().id()))
+            .copied()
+            .collect();
+        let mut names = LoopNames {
+            referenced: references
+                .found
+                .into_iter()
+                .filter(|(_, symbol)| !symbol.is_some_and(|symbol| inlined.contains(&symbol)))
+                .collect(),
+            declared: HashSet::new(),
+        };
+
+        self.push("(() => {");
+        self.push_newline();
+        self.indent += 1;
+
+        // The counter of each `take` and `enumerate`, along with the limit of each `take`.
+        let mut counters: Vec<Option<(String, Option<String>)>> = Vec::new();
+        for stage in &chain.stages {
+            let counter = match stage {
+                IteratorStage::Take(count) => {
+                    let limit = match count.kind() {
+                        ASTExpressionKind::Number(number) => number.number().to_string(),
+                        _ => {
+                            let limit = names.declare("limit", None);
+                            self.push_line(|generator| {
+                                generator.push(format!("const {} = ", limit));
+                                generator.visit_expression(count);
+                                generator.push(";");
+                            });
+                            limit
+                        }
+                    };
+                    Some((names.declare("taken", None), Some(limit)))
+                }
+                IteratorStage::Enumerate => Some((names.declare("index", None), None)),
+                IteratorStage::Map(_) | IteratorStage::Filter(_) => None,
+            };
+            if let Some((counter, _)) = &counter {
+                self.push_line(|generator| generator.push(format!("let {} = 0;", counter)));
+            }
+            counters.push(counter);
+        }
+        // What the chain returns once every item went through.
+        let result = match chain.consumer {
+            IteratorConsumer::Sum => {
+                let sum = names.declare("sum", None);
+                self.push_line(|generator| generator.push(format!("let {} = 0;", sum)));
+                sum
+            }
+            IteratorConsumer::Collect => {
+                let collected = names.declare("collected", None);
+                self.push_line(|generator| generator.push(format!("const {} = [];", collected)));
+                collected
+            }
+            IteratorConsumer::Find(_) => "undefined".to_string(),
+            IteratorConsumer::Any(_) => "false".to_string(),
+            IteratorConsumer::All(_) => "true".to_string(),
+        };
+
+        let first = next_closure(&chain.stages, &chain.consumer);
+        let mut item = Item::Named(self.declare_parameter(&mut names, first, "item"));
+        self.push_indent();
+        self.push(format!("for (const {} of ", item.expression()));
+        self.visit_expression(chain.source);
+        self.push(") {");
+        self.push_newline();
+        self.indent += 1;
+
+        // A `take` which is done stops the loop before the next item goes through anything.
+        for (counter, limit) in counters.iter().flatten() {
+            if let Some(limit) = limit {
+                self.push_line(|generator| {
+                    generator.push(format!("if ({} >= {}) break;", counter, limit));
+                });
+            }
+        }
+        for (index, stage) in chain.stages.iter().enumerate() {
+            match stage {
+                IteratorStage::Map(closure) => {
+                    self.bind_parameter(&mut names, closure, &item);
+                    let next = next_closure(&chain.stages[index + 1..], &chain.consumer);
+                    let fallback = match closure.body().kind() {
+                        ASTExpressionKind::Member(member) => member.property(),
+                        _ => "mapped",
+                    };
+                    let mapped = self.declare_parameter(&mut names, next, fallback);
+                    self.push_line(|generator| {
+                        generator.push(format!("const {} = ", mapped));
+                        generator.visit_expression(closure.body());
+                        generator.push(";");
+                    });
+                    item = Item::Named(mapped);
+                }
+                IteratorStage::Filter(closure) => {
+                    self.bind_parameter(&mut names, closure, &item);
+                    self.push_line(|generator| {
+                        generator.push("if (");
+                        generator.push_negated(closure.body());
+                        generator.push(") continue;");
+                    });
+                }
+                IteratorStage::Take(_) => {
+                    if let Some((counter, _)) = &counters[index] {
+                        self.push_line(|generator| generator.push(format!("{}++;", counter)));
+                    }
+                }
+                IteratorStage::Enumerate => {
+                    let value = match item {
+                        Item::Named(name) => name,
+                        pair @ Item::Pair(..) => {
+                            let name = names.declare("pair", None);
+                            self.push_line(|generator| {
+                                generator.push(format!("const {} = {};", name, pair.expression()));
+                            });
+                            name
+                        }
+                    };
+                    let next = next_closure(&chain.stages[index + 1..], &chain.consumer);
+                    let position = next.and_then(|closure| match closure.parameters().first() {
+                        Some(ASTClosureParameter::Tuple(items)) => Some((closure, items.first()?)),
+                        _ => None,
+                    });
+                    let position = match position {
+                        Some((closure, identifier)) => {
+                            let symbol = self.parameter_symbol(closure, identifier);
+                            names.declare(identifier.text(), symbol)
+                        }
+                        None => names.declare("position", None),
+                    };
+                    if let Some((counter, _)) = &counters[index] {
+                        self.push_line(|generator| {
+                            generator.push(format!("const {} = {}++;", position, counter));
+                        });
+                    }
+                    item = Item::Pair(position, value);
+                }
+            }
+        }
+
+        match &chain.consumer {
+            IteratorConsumer::Sum => self.push_line(|generator| {
+                generator.push(format!("{} += {};", result, item.expression()));
+            }),
+            IteratorConsumer::Collect => self.push_line(|generator| {
+                generator.push(format!("{}.push({});", result, item.expression()));
+            }),
+            IteratorConsumer::Find(closure) => {
+                self.bind_parameter(&mut names, closure, &item);
+                self.push_line(|generator| {
+                    generator.push("if (");
+                    generator.visit_expression(closure.body());
+                    generator.push(format!(") return {};", item.expression()));
+                });
+            }
+            IteratorConsumer::Any(closure) => {
+                self.bind_parameter(&mut names, closure, &item);
+                self.push_line(|generator| {
+                    generator.push("if (");
+                    generator.visit_expression(closure.body());
+                    generator.push(") return true;");
+                });
+            }
+            IteratorConsumer::All(closure) => {
+                self.bind_parameter(&mut names, closure, &item);
+                self.push_line(|generator| {
+                    generator.push("if (");
+                    generator.push_negated(closure.body());
+                    generator.push(") return false;");
+                });
+            }
+        }
+
+        self.indent -= 1;
+        self.push_line(|generator| generator.push("}"));
+        self.push_line(|generator| generator.push(format!("return {};", result)));
+        self.indent -= 1;
+        self.push_indent();
+        self.push("})()");
+    }
+
+    /// Writes a line of its own at the current indentation.
+    fn push_line(&mut self, push: impl FnOnce(&mut Self)) {
+        self.push_indent();
+        push(self);
+        self.push_newline();
+    }
+
+    /// Writes the negation of a closure's test, e.g. `!message.read` or `!(count - 1)`.
+    fn push_negated(&mut self, test: &ASTExpression) {
+        match test.kind() {
+            ASTExpressionKind::Binary(_)
+            | ASTExpressionKind::Assignment(_)
+            | ASTExpressionKind::Closure(_) => {
+                self.push("!(");
+                self.visit_expression(test);
+                self.push(")");
+            }
+            _ => {
+                self.push("!");
+                self.visit_expression(test);
+            }
+        }
+    }
+
+    /// Declares the variable holding the items a closure is about to be called with, named
+    /// after its parameter when possible.
+    fn declare_parameter(
+        &mut self,
+        names: &mut LoopNames,
+        closure: Option<&ASTClosureExpression>,
+        fallback: &str,
+    ) -> String {
+        let parameter = closure.and_then(|closure| match closure.parameters().first() {
+            Some(ASTClosureParameter::Binding(parameter)) => Some((closure, parameter)),
+            _ => None,
+        });
+        match parameter {
+            Some((closure, parameter)) => {
+                let symbol = self.parameter_symbol(closure, &parameter.identifier);
+                names.declare(parameter.identifier(), symbol)
+            }
+            None => names.declare(fallback, None),
+        }
+    }
+
+    /// Makes the parameter of an inlined closure refer to `item`, destructuring it or pairing
+    /// it up first when needed.
+    fn bind_parameter(
+        &mut self,
+        names: &mut LoopNames,
+        closure: &ASTClosureExpression,
+        item: &Item,
+    ) {
+        let Some(parameter) = closure.parameters().first() else {
+            return;
+        };
+
+        match (parameter, item) {
+            (ASTClosureParameter::Binding(parameter), Item::Named(name)) => {
+                self.rename(closure, &parameter.identifier, name);
+            }
+            (ASTClosureParameter::Binding(parameter), Item::Pair(..)) => {
+                let symbol = self.parameter_symbol(closure, &parameter.identifier);
+                let pair = names.declare(parameter.identifier(), symbol);
+                self.rename(closure, &parameter.identifier, &pair);
+                self.push_line(|generator| {
+                    generator.push(format!("const {} = {};", pair, item.expression()));
+                });
+            }
+            (ASTClosureParameter::Tuple(items), Item::Pair(index, value)) => {
+                let values = [index.as_str(), value.as_str()];
+                for (position, identifier) in items.iter().enumerate() {
+                    let value = values.get(position).unwrap_or(&"undefined");
+                    self.rename(closure, identifier, value);
+                }
+            }
+            (ASTClosureParameter::Tuple(items), Item::Named(name)) => {
+                let declared: Vec<String> = items
+                    .iter()
+                    .map(|identifier| {
+                        let symbol = self.parameter_symbol(closure, identifier);
+                        let declared = names.declare(identifier.text(), symbol);
+                        self.rename(closure, identifier, &declared);
+                        declared
+                    })
+                    .collect();
+                self.push_line(|generator| {
+                    generator.push(format!("const [{}] = {};", declared.join(", "), name));
+                });
+            }
+        }
+    }
+
+    /// The symbol declared by the parameter `identifier` of a closure.
+    fn parameter_symbol(
+        &self,
+        closure: &ASTClosureExpression,
+        identifier: &Token,
+    ) -> Option<SymbolId> {
+        self.model
+            .declared_symbol(closure.body().id(), identifier.text())
+    }
+
+    /// Writes references to a parameter of a closure as `name`.
+    fn rename(&mut self, closure: &ASTClosureExpression, parameter: &Token, name: &str) {
+        if let Some(symbol) = self.parameter_symbol(closure, parameter) {
+            self.renamed.insert(symbol, name.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::evaluator::Value;
+    use crate::testing::{compile, evaluate};
+
+    /// Checks the loop `let r = <chain>` is fused into, and the value the chain gives when
+    /// evaluated.
+    fn check(declarations: &str, chain: &str, expected_loop: &str, expected_value: Value) {
+        let source = format!("{}\nlet r = {};\nr", declarations, chain);
+        let tsx = compile(&source).tsx.unwrap();
+        let fused = format!("let r = {};\n", expected_loop);
+        assert!(tsx.contains(&fused), "expected\n{}\nin\n{}", fused, tsx);
+        assert_eq!(evaluate(&source), expected_value);
+    }
+
+    fn numbers(numbers: &[i64]) -> Value {
+        Value::Array(numbers.iter().copied().map(Value::Number).collect())
+    }
+
+    #[test]
+    fn maps_and_sums() {
+        check(
+            "let xs = [1, 2, 3];",
+            "xs.iter().map(|x| x * 2).sum()",
+            "(() => {
+  let sum = 0;
+  for (const x of xs) {
+    const mapped = x * 2;
+    sum += mapped;
+  }
+  return sum;
+})()",
+            Value::Number(12),
+        );
+    }
+
+    #[test]
+    fn filters_and_collects() {
+        check(
+            "let xs = [1, 2, 3];",
+            "xs.iter().filter(|x| x - 2).collect()",
+            "(() => {
+  const collected = [];
+  for (const x of xs) {
+    if (!(x - 2)) continue;
+    collected.push(x);
+  }
+  return collected;
+})()",
+            numbers(&[1, 3]),
+        );
+    }
+
+    #[test]
+    fn takes_before_filtering() {
+        check(
+            "let xs = [1, 2, 3, 4];",
+            "xs.iter().take(3).filter(|x| x - 2).collect()",
+            "(() => {
+  let taken = 0;
+  const collected = [];
+  for (const x of xs) {
+    if (taken >= 3) break;
+    taken++;
+    if (!(x - 2)) continue;
+    collected.push(x);
+  }
+  return collected;
+})()",
+            numbers(&[1, 3]),
+        );
+    }
+
+    #[test]
+    fn takes_after_filtering() {
+        check(
+            "let xs = [1, 2, 3, 4];",
+            "xs.iter().filter(|x| x - 2).take(3).collect()",
+            "(() => {
+  let taken = 0;
+  const collected = [];
+  for (const x of xs) {
+    if (taken >= 3) break;
+    if (!(x - 2)) continue;
+    taken++;
+    collected.push(x);
+  }
+  return collected;
+})()",
+            numbers(&[1, 3, 4]),
+        );
+    }
+
+    #[test]
+    fn enumerates_the_items_left() {
+        check(
+            "let xs = [5, 0, 7];",
+            "xs.iter().filter(|x| x).enumerate().map(|(i, x)| x * i).collect()",
+            "(() => {
+  let index = 0;
+  const collected = [];
+  for (const x of xs) {
+    if (!x) continue;
+    const i = index++;
+    const mapped = x * i;
+    collected.push(mapped);
+  }
+  return collected;
+})()",
+            numbers(&[0, 7]),
+        );
+    }
+
+    #[test]
+    fn finds_the_first_match() {
+        let expected_loop = |condition: &str| {
+            format!(
+                "(() => {{
+  for (const x of xs) {{
+    if ({}) return x;
+  }}
+  return undefined;
+}})()",
+                condition
+            )
+        };
+        check(
+            "let xs = [1, 2, 3];",
+            "xs.iter().find(|x| x - 1)",
+            &expected_loop("x - 1"),
+            Value::Number(2),
+        );
+        check(
+            "let xs = [1, 2, 3];",
+            "xs.iter().find(|x| x - x)",
+            &expected_loop("x - x"),
+            Value::Undefined,
+        );
+    }
+
+    #[test]
+    fn checks_any_and_all_items() {
+        check(
+            "let xs = [1, 2, 3];",
+            "xs.iter().any(|x| x - 1)",
+            "(() => {
+  for (const x of xs) {
+    if (x - 1) return true;
+  }
+  return false;
+})()",
+            Value::Boolean(true),
+        );
+        check(
+            "let xs = [1, 2, 3];",
+            "xs.iter().all(|x| x - 2)",
+            "(() => {
+  for (const x of xs) {
+    if (!(x - 2)) return false;
+  }
+  return true;
+})()",
+            Value::Boolean(false),
+        );
+    }
+
+    #[test]
+    fn fuses_nested_chains_separately() {
+        check(
+            "let rows = [[1, 2], [3, 4]];",
+            "rows.iter().map(|row| row.iter().map(|x| x * 10).sum()).collect()",
+            "(() => {
+  const collected = [];
+  for (const row of rows) {
+    const mapped = (() => {
+      let sum = 0;
+      for (const x of row) {
+        const mapped = x * 10;
+        sum += mapped;
+      }
+      return sum;
+    })();
+    collected.push(mapped);
+  }
+  return collected;
+})()",
+            numbers(&[30, 70]),
+        );
+    }
+
+    #[test]
+    fn renames_loop_variables_shadowing_the_chain() {
+        check(
+            "let collected = [1, 2, 3, 4, 5, 6];\nlet taken = 1;",
+            "collected.iter().take(4).filter(|index| index - 2).map(|item| item + taken).collect()",
+            "(() => {
+  let taken2 = 0;
+  const collected2 = [];
+  for (const index of collected) {
+    if (taken2 >= 4) break;
+    taken2++;
+    if (!(index - 2)) continue;
+    const mapped = index + taken;
+    collected2.push(mapped);
+  }
+  return collected2;
+})()",
+            numbers(&[2, 4, 5]),
+        );
+        check(
+            "let xs = [1, 2, 3];\nlet sum = 10;",
+            "xs.iter().map(|mapped| mapped + sum).sum()",
+            "(() => {
+  let sum2 = 0;
+  for (const mapped of xs) {
+    const mapped2 = mapped + sum;
+    sum2 += mapped2;
+  }
+  return sum2;
+})()",
+            Value::Number(36),
+        );
+    }
+}
@@ -0,0 +1,1567 @@
+use std::collections::HashMap;
+
+use crate::ast::hooks::{ReactHooks, capitalized, change_handler_name, default_prop_name};
+use crate::ast::iterators::IteratorChain;
+use crate::ast::typechecker::Property;
+use crate::ast::visibility::ProjectModules;
+use crate::config::Config;
+use crate::prelude::*;
+use imports::{Import, ImportCollection, ModuleResolver};
+use sourcemap::Mapping;
+pub use sourcemap::SourceMap;
+use styles::Stylesheet;
+use validators::TypeGuards;
+
+mod imports;
+mod iterators;
+mod sourcemap;
+mod styles;
+mod validators;
+
+const INDENT: &str = "  ";
+
+/// A prop made controllable with `controllable(#<prop>)`.
+struct ControlledProp<'a, 'ast> {
+    /// The `#<prop>` argument of the modifier.
+    prop: &'a Token,
+    parameter: &'a ASTParameter<'ast>,
+    /// The `on<Prop>Changed` parameter, if the component declares it.
+    handler: Option<&'a ASTParameter<'ast>>,
+}
+
+impl<'a, 'ast> ControlledProp<'a, 'ast> {
+    /// The controllable props of a component. Props it doesn't declare are reported by the
+    /// `SymbolChecker`.
+    fn of(component: &'a ASTComponentStatement<'ast>) -> Vec<Self> {
+        let parameter = |name: &str| {
+            component
+                .parameters
+                .iter()
+                .find(|parameter| parameter.identifier() == name)
+        };
+        component
+            .controlled_props()
+            .into_iter()
+            .filter_map(|prop| {
+                Some(ControlledProp {
+                    prop,
+                    parameter: parameter(prop.text())?,
+                    handler: parameter(&change_handler_name(prop.text())),
+                })
+            })
+            .collect()
+    }
+
+    fn name(&self) -> &str {
+        self.parameter.identifier()
+    }
+}
+
+/// Emits TypeScript (`.tsx`) source code from the AST.
+pub struct TSXGenerator<'a, 'ast> {
+    result: String,
+    indent: usize,
+    source: &'a SourceText,
+    /// Where the previous statement of the current block ended, to carry blank lines over.
+    previous_end: Option<usize>,
+    /// Where each node was written to, in output order.
+    mappings: Vec<Mapping>,
+    model: &'a SemanticModel,
+    hooks: &'a ReactHooks,
+    /// `impl` blocks of each component, in declaration order.
+    impls: HashMap<&'a str, Vec<&'a ASTImplStatement<'ast>>>,
+    /// Components declared in the file, whose props others may extend.
+    components: HashMap<&'a str, &'a ASTComponentStatement<'ast>>,
+    /// The props of components imported from Anvyl files of the project, keyed by the name
+    /// they are imported as.
+    imported_components: HashMap<String, Vec<Property>>,
+    /// Imports of the generated code, written above it once everything else is generated.
+    imports: ImportCollection,
+    guards: TypeGuards<'a, 'ast>,
+    stylesheet: Stylesheet,
+    /// Classes scoping the styles of a component, keyed by the elements they're added to.
+    scoped_elements: SideTable<String>,
+    /// The names closure parameters are written as, once inlined into the loop of an
+    /// iterator chain.
+    renamed: HashMap<SymbolId, String>,
+}
+
+impl<'a, 'ast> TSXGenerator<'a, 'ast> {
+    pub fn new(
+        ast: &'a AST<'ast>,
+        source: &'a SourceText,
+        model: &'a SemanticModel,
+        hooks: &'a ReactHooks,
+    ) -> Self {
+        Self::with_config(ast, source, model, hooks, &Config::default())
+    }
+
+    /// Like `new`, but resolving imports with the path aliases of a project's `anvyl.toml`.
+    pub fn with_config(
+        ast: &'a AST<'ast>,
+        source: &'a SourceText,
+        model: &'a SemanticModel,
+        hooks: &'a ReactHooks,
+        config: &Config,
+    ) -> Self {
+        Self::with_project(ast, source, model, hooks, config, &ProjectModules::new())
+    }
+
+    /// Like `with_config`, but also knowing the project files the file imports from, so
+    /// named exports of a file can be imported from it, and the props of imported
+    /// components are known.
+    pub fn with_project(
+        ast: &'a AST<'ast>,
+        source: &'a SourceText,
+        model: &'a SemanticModel,
+        hooks: &'a ReactHooks,
+        config: &Config,
+        modules: &ProjectModules,
+    ) -> Self {
+        let mut impls: HashMap<&str, Vec<&ASTImplStatement>> = HashMap::new();
+        let mut components = HashMap::new();
+        for statement in &ast.statements {
+            match statement.kind() {
+                ASTStatementKind::Impl(impl_statement) => impls
+                    .entry(impl_statement.identifier())
+                    .or_default()
+                    .push(impl_statement.as_ref()),
+                ASTStatementKind::Component(component) => {
+                    components.insert(component.identifier(), component.as_ref());
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            result: String::new(),
+            indent: 0,
+            source,
+            previous_end: None,
+            mappings: Vec::new(),
+            model,
+            hooks,
+            impls,
+            components,
+            imported_components: modules.imported_components(ast, model),
+            imports: ModuleResolver::new(config, modules).resolve(ast, model),
+            guards: TypeGuards::new(ast),
+            stylesheet: Stylesheet::default(),
+            scoped_elements: SideTable::new(),
+            renamed: HashMap::new(),
+        }
+    }
+
+    /// The scoped styles of every component, to be written next to the generated file, or
+    /// `None` when no component has a `style` block.
+    pub fn stylesheet(&self) -> Option<&str> {
+        self.stylesheet.css()
+    }
+
+    /// Imports the file the `stylesheet` is written to, e.g. `./ChatRoom.css`.
+    pub fn import_stylesheet(&mut self, specifier: &str) {
+        self.imports.add(Import {
+            default: None,
+            named: Vec::new(),
+            specifier: specifier.to_string(),
+            origin: None,
+        });
+    }
+
+    /// The generated source, between its imports and the type guards used by
+    /// `validate<T>(value)` calls.
+    pub fn result(&self) -> String {
+        let (imports, _) = self.imports.render();
+        format!("{}{}{}", imports, self.result, self.guards.functions())
+    }
+
+    /// Maps the generated code about to be written back to the node at `span`.
+    fn push_mapping(&mut self, span: &TextSpan) {
+        self.mappings.push(Mapping {
+            generated: self.result.len(),
+            original: span.start,
+        });
+    }
+
+    /// Maps the generated code back to the source it was emitted from.
+    ///
+    /// `file` names the generated file and `source` the path to the `.avyl` file from it.
+    pub fn source_map(&self, file: &str, source: &str) -> SourceMap {
+        // Everything else is written below the imports.
+        let (imports, mut mappings) = self.imports.render();
+        mappings.extend(self.mappings.iter().map(|mapping| Mapping {
+            generated: imports.len() + mapping.generated,
+            original: mapping.original,
+        }));
+        SourceMap::new(
+            file,
+            source,
+            self.source.as_str(),
+            &self.result(),
+            &mappings,
+        )
+    }
+
+    fn push(&mut self, text: impl AsRef<str>) {
+        self.result.push_str(text.as_ref());
+    }
+
+    fn push_newline(&mut self) {
+        self.result.push('\n');
+    }
+
+    fn push_indent(&mut self) {
+        self.result.push_str(&INDENT.repeat(self.indent));
+    }
+
+    /// Runs `push` against an empty result, returning what it wrote.
+    ///
+    /// Mappings of the rendered nodes are dropped, as their final position isn't known yet.
+    fn render(&mut self, push: impl FnOnce(&mut Self)) -> String {
+        let (result, mappings) = (std::mem::take(&mut self.result), self.mappings.len());
+        push(self);
+        self.mappings.truncate(mappings);
+        std::mem::replace(&mut self.result, result)
+    }
+
+    fn push_separated<T>(&mut self, items: &[T], mut push_item: impl FnMut(&mut Self, &T)) {
+        for (index, item) in items.iter().enumerate() {
+            if index > 0 {
+                self.push(", ");
+            }
+            push_item(self, item);
+        }
+    }
+
+    fn push_block(&mut self, statements: &[ASTStatement]) {
+        if statements.is_empty() {
+            self.push("{}");
+            return;
+        }
+
+        self.push_block_with(|_| {}, statements);
+    }
+
+    /// Like `push_block`, with `prologue` writing statements of its own before the
+    /// block's statements.
+    fn push_block_with(&mut self, prologue: impl FnOnce(&mut Self), statements: &[ASTStatement]) {
+        self.push("{");
+        self.push_newline();
+        self.indent += 1;
+        prologue(self);
+        let previous_end = self.previous_end.take();
+        for statement in statements {
+            self.visit_statement(statement);
+        }
+        self.previous_end = previous_end;
+        self.indent -= 1;
+        self.push_indent();
+        self.push("}");
+    }
+
+    fn push_parameters(&mut self, parameters: &[ASTParameter]) {
+        self.push("(");
+        self.push_separated(parameters, |generator, parameter| {
+            generator.push(parameter.identifier());
+            // TypeScript doesn't allow both, and a default makes a parameter optional anyway.
+            if parameter.optional && parameter.default.is_none() {
+                generator.push("?");
+            }
+            if let Some(ty) = &parameter.ty {
+                generator.push(": ");
+                generator.visit_type(ty);
+            }
+            if let Some(default) = &parameter.default {
+                generator.push(" = ");
+                generator.visit_expression(default);
+            }
+        });
+        self.push(")");
+    }
+
+    /// Writes generic parameters, e.g. `<T, U extends string>`.
+    ///
+    /// `excludes` constraints have no TypeScript equivalent, so they are enforced on the
+    /// props instead, see `push_props`.
+    fn push_generics(&mut self, generics: &[ASTGenericParameter]) {
+        if generics.is_empty() {
+            return;
+        }
+
+        self.push("<");
+        self.push_separated(generics, |generator, generic| {
+            generator.push(generic.identifier());
+            if let Some(constraint) = &generic.constraint
+                && constraint.kind == ASTGenericConstraintKind::Extends
+            {
+                generator.push(" extends ");
+                generator.visit_type(&constraint.ty);
+            }
+        });
+        self.push(">");
+    }
+
+    /// Writes the names of generic parameters as type arguments, e.g. `<T, U>`.
+    fn push_generic_arguments(&mut self, generics: &[ASTGenericParameter]) {
+        if generics.is_empty() {
+            return;
+        }
+
+        self.push("<");
+        self.push_separated(generics, |generator, generic| {
+            generator.push(generic.identifier());
+        });
+        self.push(">");
+    }
+
+    /// Writes `export ` or `export default ` before a declaration that is `pub`, whatever
+    /// folders it is restricted to, since TypeScript can't tell them apart.
+    fn push_export(&mut self, visibility: &ASTVisibility) {
+        match (visibility.is_exported(), visibility.is_default()) {
+            (true, true) => self.push("export default "),
+            (true, false) => self.push("export "),
+            (false, _) => {}
+        }
+    }
+
+    /// Writes the props of a component as an interface, e.g.
+    ///
+    /// ```ts
+    /// interface ChatRoomProps {
+    ///   room: string;
+    ///   limit?: number;
+    /// }
+    /// ```
+    ///
+    /// Props without an annotation take the type of their default.
+    fn push_props_interface(&mut self, name: &str, component: &ASTComponentStatement) {
+        // The props of an exported component are exported along with it.
+        if component.visibility.is_exported() {
+            self.push("export ");
+        }
+        self.push(format!("interface {}", name));
+        self.push_generics(&component.generics);
+        if let Some(base) = component.extended_component() {
+            self.push_extended_props(component, base);
+        }
+        self.push(" {");
+        self.indent += 1;
+        let controlled = ControlledProp::of(component);
+        for parameter in &component.parameters {
+            let control = controlled
+                .iter()
+                .find(|control| std::ptr::eq(control.parameter, parameter));
+            let handled = controlled.iter().find(|control| {
+                control
+                    .handler
+                    .is_some_and(|handler| std::ptr::eq(handler, parameter))
+            });
+            // Nullable props may be left out, as the type checker allows.
+            let nullable = parameter
+                .ty
+                .as_ref()
+                .is_some_and(|ty| matches!(ty.kind(), ASTTypeKind::Optional(_)));
+            let optional =
+                parameter.is_optional() || nullable || control.is_some() || handled.is_some();
+            self.push_prop(parameter.identifier(), optional, |generator| {
+                match (handled, &parameter.ty) {
+                    (Some(control), None) => generator.push_handler_type(control),
+                    _ => generator.push_prop_type(parameter),
+                }
+            });
+
+            if let Some(control) = control {
+                self.push_prop(&default_prop_name(control.name()), true, |generator| {
+                    generator.push_prop_type(parameter);
+                });
+                if control.handler.is_none() {
+                    self.push_prop(&change_handler_name(control.name()), true, |generator| {
+                        generator.push_handler_type(control);
+                    });
+                }
+            }
+        }
+        self.indent -= 1;
+        if !component.parameters.is_empty() {
+            self.push_newline();
+            self.push_indent();
+        }
+        self.push("}");
+        self.push_newline();
+        self.push_newline();
+        self.push_indent();
+    }
+
+    /// Adds the `style` blocks of a component to the stylesheet, scoping them to the
+    /// elements it renders.
+    fn scope_styles(&mut self, component: &ASTComponentStatement) {
+        let css: Vec<&str> = component
+            .body
+            .iter()
+            .filter_map(|statement| match statement.kind() {
+                ASTStatementKind::Style(style) => Some(style.css()),
+                _ => None,
+            })
+            .collect();
+        if css.is_empty() {
+            return;
+        }
+
+        let class = self.stylesheet.add(component.identifier(), &css.join("\n"));
+        let mut roots = Vec::new();
+        Self::root_elements(&component.body, &mut roots);
+        for root in roots {
+            self.scoped_elements.insert(root, class.clone());
+        }
+    }
+
+    /// Writes a callback, wrapped in `useCallback` when it's declared in a component.
+    fn push_callback(&mut self, id: NodeId, callback: &ASTCallbackStatement) {
+        let dependencies = self.hooks.callback_dependencies(id);
+
+        self.push(format!("const {} = ", callback.identifier()));
+        if dependencies.is_some() {
+            self.imports.add_react("useCallback");
+            self.push("useCallback(");
+        }
+
+        self.push_parameters(&callback.parameters);
+        self.push(" => ");
+        match callback.body.as_slice() {
+            [statement] => match statement.kind() {
+                ASTStatementKind::Expression(expression) => self.visit_expression(expression),
+                _ => self.push_block(&callback.body),
+            },
+            _ => self.push_block(&callback.body),
+        }
+
+        if let Some(dependencies) = dependencies {
+            self.push(format!(", [{}])", dependencies.join(", ")));
+        }
+    }
+
+    /// Writes a JSX element, adding the class scoping its component's styles to it.
+    fn push_element(&mut self, id: NodeId, expression: &ASTElementExpression) {
+        self.push(format!("<{}", expression.name()));
+        let scope = self.scoped_elements.get(id).cloned();
+        let class_name = expression
+            .attributes
+            .iter()
+            .find(|attribute| attribute.name() == "className");
+        if let (Some(scope), None) = (&scope, class_name) {
+            self.push(format!(" className={{\"{}\"}}", scope));
+        }
+        for attribute in &expression.attributes {
+            self.push(format!(" {}={{", attribute.name()));
+            match &scope {
+                // The scoping class is added to the element's own classes.
+                Some(scope) if attribute.name() == "className" => {
+                    self.push(format!("`{} ${{", scope));
+                    self.visit_expression(&attribute.value);
+                    self.push("}`");
+                }
+                _ => self.visit_expression(&attribute.value),
+            }
+            self.push("}");
+        }
+
+        let children = match expression.children() {
+            None => return self.push(" />"),
+            Some([]) => return self.push(format!("></{}>", expression.name())),
+            Some(children) => children,
+        };
+        self.push(">");
+        // Text is written on the line of the tags around it, as whitespace between text and
+        // the tags is part of the text.
+        if children
+            .iter()
+            .any(|child| matches!(child.kind(), ASTExpressionKind::Text(_)))
+        {
+            for child in children {
+                self.push_child(child);
+            }
+            return self.push(format!("</{}>", expression.name()));
+        }
+        self.indent += 1;
+        for child in children {
+            self.push_newline();
+            self.push_indent();
+            self.push_child(child);
+        }
+        self.indent -= 1;
+        self.push_newline();
+        self.push_indent();
+        self.push(format!("</{}>", expression.name()));
+    }
+
+    /// Writes a child of an element, wrapping expressions other than elements and text in
+    /// braces.
+    fn push_child(&mut self, child: &ASTExpression) {
+        match child.kind() {
+            ASTExpressionKind::Element(_) | ASTExpressionKind::Text(_) => {
+                self.visit_expression(child)
+            }
+            _ => {
+                self.push("{");
+                self.visit_expression(child);
+                self.push("}");
+            }
+        }
+    }
+
+    /// Finds the outermost HTML elements returned by a block, which the class scoping a
+    /// component's styles is added to.
+    ///
+    /// Components don't necessarily pass a `className` on, so the elements within them are
+    /// searched instead, and callbacks render nothing of the component's.
+    fn root_elements(statements: &[ASTStatement], roots: &mut Vec<NodeId>) {
+        fn visit(expression: &ASTExpression, roots: &mut Vec<NodeId>) {
+            match expression.kind() {
+                ASTExpressionKind::Parenthesized(parenthesized) => {
+                    visit(&parenthesized.inner, roots)
+                }
+                ASTExpressionKind::Element(element)
+                    if element.name().starts_with(char::is_lowercase) =>
+                {
+                    roots.push(expression.id())
+                }
+                ASTExpressionKind::Element(element) => {
+                    for child in element.children().unwrap_or_default() {
+                        visit(child, roots);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for statement in statements {
+            match statement.kind() {
+                ASTStatementKind::Return(return_statement) => {
+                    if let Some(value) = return_statement.value() {
+                        visit(value, roots);
+                    }
+                }
+                ASTStatementKind::For(for_statement) => {
+                    Self::root_elements(&for_statement.body, roots)
+                }
+                ASTStatementKind::While(while_statement) => {
+                    Self::root_elements(&while_statement.body, roots)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Writes a member of a props interface on a line of its own, e.g. `limit?: number;`.
+    fn push_prop(&mut self, name: &str, optional: bool, push_type: impl FnOnce(&mut Self)) {
+        self.push_newline();
+        self.push_indent();
+        self.push(name);
+        if optional {
+            self.push("?");
+        }
+        self.push(": ");
+        push_type(self);
+        self.push(";");
+    }
+
+    /// Writes the type of a prop, which is that of its default when it has no annotation.
+    fn push_prop_type(&mut self, parameter: &ASTParameter) {
+        match (&parameter.ty, &parameter.default) {
+            (Some(ty), _) => self.visit_type(ty),
+            (None, Some(default)) => match self.model.inferred_type(default.id()) {
+                Some(InferredType::Number) => self.push("number"),
+                Some(InferredType::String) => self.push("string"),
+                _ => self.push("any"),
+            },
+            (None, None) => self.push("any"),
+        }
+    }
+
+    /// Writes the type of the `on<Prop>Changed` prop of a controllable prop, e.g.
+    /// `(value: string) => void`.
+    fn push_handler_type(&mut self, control: &ControlledProp) {
+        self.push(format!("({}: ", control.name()));
+        self.push_prop_type(control.parameter);
+        self.push(") => void");
+    }
+
+    /// Writes the state behind a controllable prop, which follows the prop while it is
+    /// controlled, and otherwise starts out as its `default<Prop>`, e.g.
+    ///
+    /// ```ts
+    /// const [uncontrolledValue, setUncontrolledValue] = useState(defaultValue);
+    /// const value = controlledValue !== undefined ? controlledValue : uncontrolledValue;
+    /// const onValueChanged = useCallback((value: string) => {
+    ///   setUncontrolledValue(value);
+    ///   onValueChangedProp?.(value);
+    /// }, [onValueChangedProp]);
+    /// ```
+    ///
+    /// Assignments to the prop are lowered to calls of `onValueChanged`, see `HookLowering`.
+    fn push_controlled_state(&mut self, control: &ControlledProp) {
+        let name = control.name();
+        let uncontrolled = format!("uncontrolled{}", capitalized(name));
+        let setter = format!("setUncontrolled{}", capitalized(name));
+        let handler = change_handler_name(name);
+        self.imports.add_react("useState");
+        self.imports.add_react("useCallback");
+
+        self.push_indent();
+        self.push_mapping(&control.prop.span);
+        self.push(format!(
+            "const [{}, {}] = useState({});",
+            uncontrolled,
+            setter,
+            default_prop_name(name)
+        ));
+        self.push_newline();
+        self.push_indent();
+        self.push(format!(
+            "const {} = controlled{} !== undefined ? controlled{} : {};",
+            name,
+            capitalized(name),
+            capitalized(name),
+            uncontrolled
+        ));
+        self.push_newline();
+        self.push_indent();
+        self.push(format!("const {} = useCallback(({}: ", handler, name));
+        self.push_prop_type(control.parameter);
+        self.push(") => {");
+        self.push_newline();
+        self.indent += 1;
+        self.push_indent();
+        self.push(format!("{}({});", setter, name));
+        self.push_newline();
+        self.push_indent();
+        self.push(format!("{}Prop?.({});", handler, name));
+        self.push_newline();
+        self.indent -= 1;
+        self.push_indent();
+        self.push(format!("}}, [{}Prop]);", handler));
+        self.push_newline();
+    }
+
+    /// Writes the `extends` clause of a props interface, e.g.
+    /// `extends Omit<ButtonProps, 'size'>`, leaving out the props the component redeclares
+    /// so they can be given a different type.
+    ///
+    /// The props of an imported component aren't known, so every prop the component
+    /// declares is left out of them, which TypeScript allows even when they don't exist.
+    fn push_extended_props(&mut self, component: &ASTComponentStatement, base: &Token) {
+        let (props, overlapping): (String, Vec<&ASTParameter>) =
+            match self.components.get(base.text()) {
+                Some(base) if !base.has_props() => return,
+                Some(base) => (
+                    base.props_name(),
+                    component
+                        .parameters
+                        .iter()
+                        .filter(|parameter| {
+                            base.parameters
+                                .iter()
+                                .any(|inherited| inherited.identifier() == parameter.identifier())
+                        })
+                        .collect(),
+                ),
+                None => {
+                    // Without knowing the props of the base, every declared prop may conflict.
+                    let overlapping = match self.imported_components.get(base.text()) {
+                        Some(base) if base.is_empty() => return,
+                        Some(base) => component
+                            .parameters
+                            .iter()
+                            .filter(|parameter| {
+                                base.iter()
+                                    .any(|inherited| inherited.name == parameter.identifier())
+                            })
+                            .collect(),
+                        None => component.parameters.iter().collect(),
+                    };
+                    self.imports.add_react("ComponentProps");
+                    (
+                        format!("ComponentProps<typeof {}>", base.text()),
+                        overlapping,
+                    )
+                }
+            };
+
+        self.push(" extends ");
+        if overlapping.is_empty() {
+            self.push(props);
+            return;
+        }
+        let keys: Vec<String> = overlapping
+            .iter()
+            .map(|parameter| format!("'{}'", parameter.identifier()))
+            .collect();
+        self.push(format!("Omit<{}, {}>", props, keys.join(" | ")));
+    }
+
+    /// Writes component parameters as a single destructured props object, along with their
+    /// defaults, e.g. `({ room, limit = 50 }: ChatRoomProps)`.
+    fn push_props(&mut self, component: &ASTComponentStatement) {
+        if !component.has_props() {
+            self.push("()");
+            return;
+        }
+        // Props which are only inherited still have to be accepted.
+        if component.parameters.is_empty() {
+            self.push(format!("(_props: {}", component.props_name()));
+            self.push_generic_arguments(&component.generics);
+            self.push(")");
+            return;
+        }
+
+        // Controllable props and their handlers are renamed, as the names are taken by the
+        // state behind them, see `push_controlled_state`.
+        let controlled = ControlledProp::of(component);
+        self.push("({ ");
+        self.push_separated(&component.parameters, |generator, parameter| {
+            let name = parameter.identifier();
+            let control = controlled
+                .iter()
+                .find(|control| std::ptr::eq(control.parameter, parameter));
+            let handled = controlled.iter().any(|control| {
+                control
+                    .handler
+                    .is_some_and(|handler| std::ptr::eq(handler, parameter))
+            });
+            match control {
+                Some(_) => generator.push(format!("{}: controlled{}", name, capitalized(name))),
+                None if handled => generator.push(format!("{}: {}Prop", name, name)),
+                None => generator.push(name),
+            }
+            if let Some(default) = &parameter.default
+                && control.is_none()
+            {
+                generator.push(" = ");
+                generator.visit_expression(default);
+            }
+
+            let Some(control) = control else {
+                return;
+            };
+            generator.push(format!(", {}", default_prop_name(name)));
+            if let Some(default) = &parameter.default {
+                generator.push(" = ");
+                generator.visit_expression(default);
+            }
+            if control.handler.is_none() {
+                let handler = change_handler_name(name);
+                generator.push(format!(", {}: {}Prop", handler, handler));
+            }
+        });
+        self.push(" }: ");
+        self.push(component.props_name());
+        self.push_generic_arguments(&component.generics);
+
+        // `T excludes string` rejects `T = string` by making the props impossible to satisfy.
+        for generic in &component.generics {
+            if let Some(constraint) = &generic.constraint
+                && constraint.kind == ASTGenericConstraintKind::Excludes
+            {
+                self.push(format!(" & ([{}] extends [", generic.identifier()));
+                self.visit_type(&constraint.ty);
+                self.push("] ? never : unknown)");
+            }
+        }
+        self.push(")");
+    }
+
+    /// Writes the props types of a component with `impl` blocks, e.g.
+    ///
+    /// ```ts
+    /// interface AutocompleteBaseProps<T> {
+    ///   getOptionLabel: (e: T) => string;
+    /// }
+    ///
+    /// type AutocompleteProps<T> = [T] extends [string]
+    ///   ? AutocompleteBaseProps<T>
+    ///   : Omit<AutocompleteBaseProps<T>, 'getOptionLabel'> & { getOptionLabel?: (e: T) => string };
+    /// ```
+    ///
+    /// Each `impl` block replaces the props it redeclares whenever its constraints hold,
+    /// with later blocks taking precedence over earlier ones.
+    fn push_impl_props(&mut self, component: &ASTComponentStatement, impls: &[&ASTImplStatement]) {
+        let arguments = self.render(|generator| {
+            generator.push_generic_arguments(&component.generics);
+        });
+        let generics = self.render(|generator| generator.push_generics(&component.generics));
+
+        self.push_props_interface(&format!("{}BaseProps", component.identifier()), component);
+
+        let mut props = format!("{}BaseProps{}", component.identifier(), arguments);
+        for impl_statement in impls {
+            let replaced = self.render(|generator| {
+                let keys: Vec<String> = impl_statement
+                    .members
+                    .iter()
+                    .map(|member| format!("'{}'", member.identifier()))
+                    .collect();
+                generator.push(format!("Omit<{}, {}> & {{ ", props, keys.join(" | ")));
+                for (index, member) in impl_statement.members.iter().enumerate() {
+                    if index > 0 {
+                        generator.push("; ");
+                    }
+                    generator.push(member.identifier());
+                    if member.is_optional() {
+                        generator.push("?");
+                    }
+                    generator.push(": ");
+                    generator.visit_type(&member.ty);
+                }
+                generator.push(" }");
+            });
+            props = self.render(|generator| {
+                generator.push_conditional_props(&impl_statement.generics, &props, &replaced);
+            });
+        }
+
+        if component.visibility.is_exported() {
+            self.push("export ");
+        }
+        self.push(format!(
+            "type {}{} = {};",
+            component.props_name(),
+            generics,
+            props
+        ));
+        self.push_newline();
+        self.push_newline();
+        self.push_indent();
+    }
+
+    /// Writes `replaced` when every constraint of `generics` holds, otherwise `props`.
+    fn push_conditional_props(
+        &mut self,
+        generics: &[ASTGenericParameter],
+        props: &str,
+        replaced: &str,
+    ) {
+        let Some((generic, rest)) = generics.split_first() else {
+            self.push(replaced);
+            return;
+        };
+        let Some(constraint) = &generic.constraint else {
+            self.push_conditional_props(rest, props, replaced);
+            return;
+        };
+
+        self.push(format!("[{}] extends [", generic.identifier()));
+        self.visit_type(&constraint.ty);
+        self.push("] ? ");
+        match constraint.kind {
+            ASTGenericConstraintKind::Extends => {
+                self.push_conditional_props(rest, props, replaced);
+                self.push(format!(" : {}", props));
+            }
+            ASTGenericConstraintKind::Excludes => {
+                self.push(format!("{} : ", props));
+                self.push_conditional_props(rest, props, replaced);
+            }
+        }
+    }
+
+    /// Writes a type nested in a union, optional or array type, parenthesising it if needed.
+    fn push_nested_type(&mut self, ty: &ASTType) {
+        if ty.needs_parentheses() {
+            self.push("(");
+            self.visit_type(ty);
+            self.push(")");
+        } else {
+            self.visit_type(ty);
+        }
+    }
+
+    /// Writes `const [<identifier>, <setter>] = useState<<type>>(<initial value>)`.
+    fn push_stateful_binding(
+        &mut self,
+        let_statement: &ASTLetStatement,
+        identifier: &Token,
+        setter: &str,
+    ) {
+        self.imports.add_react("useState");
+        self.push(format!(
+            "const [{}, {}] = useState",
+            identifier.text(),
+            setter
+        ));
+        if let Some(ty) = &let_statement.type_annotation {
+            self.push("<");
+            self.visit_type(ty);
+            self.push(">");
+        }
+        self.push("(");
+        if let Some(call) = let_statement.stateful_initializer() {
+            self.push_separated(call.arguments(), |generator, argument| {
+                generator.visit_expression(argument);
+            });
+        }
+        self.push(")");
+    }
+
+    /// Writes the literal members of a type as an array, e.g. `['a', 'b']`.
+    fn push_materialized_type(&mut self, members: &[Token]) {
+        let members: Vec<&str> = members.iter().map(|member| member.text()).collect();
+        self.push(format!("[{}]", members.join(", ")));
+    }
+}
+
+impl ASTVisitor for TSXGenerator<'_, '_> {
+    fn visit_statement(&mut self, statement: &ASTStatement) {
+        // `impl` blocks are folded into the props type of their component, and imports are
+        // all written at the top.
+        if let ASTStatementKind::Impl(_)
+        | ASTStatementKind::Import(_)
+        | ASTStatementKind::Style(_) = statement.kind()
+        {
+            return;
+        }
+
+        if let Some(previous_end) = self.previous_end
+            && self
+                .source
+                .has_blank_line(previous_end, statement.span().start)
+        {
+            self.push_newline();
+        }
+        self.previous_end = Some(statement.span().end);
+
+        self.push_indent();
+        self.push_mapping(statement.span());
+        match statement.kind() {
+            ASTStatementKind::Callback(callback) => self.push_callback(statement.id(), callback),
+            _ => self.default_visit_statement(statement),
+        }
+
+        let block_statement = matches!(
+            statement.kind(),
+            ASTStatementKind::Component(_) | ASTStatementKind::For(_) | ASTStatementKind::While(_)
+        );
+        if !block_statement {
+            self.push(";");
+        }
+        self.push_newline();
+
+        // Only functions can be declared as the default export, so other declarations
+        // export their name after it.
+        let default = match statement.kind() {
+            ASTStatementKind::LetStatement(let_statement)
+                if let_statement.visibility.is_default() =>
+            {
+                let_statement
+                    .identifier()
+                    .map(|identifier| identifier.text())
+            }
+            ASTStatementKind::TypeAlias(type_alias) if type_alias.visibility.is_default() => {
+                Some(type_alias.identifier())
+            }
+            _ => None,
+        };
+        if let Some(name) = default {
+            self.push_indent();
+            self.push(format!("export default {};", name));
+            self.push_newline();
+        }
+    }
+
+    fn visit_let_statement(&mut self, let_statement: &ASTLetStatement) {
+        if let Some(identifier) = let_statement.identifier()
+            && let Some(setter) = self.hooks.stateful_setter(let_statement.initializer.id())
+        {
+            self.push_stateful_binding(let_statement, identifier, setter);
+            return;
+        }
+
+        if let_statement.visibility.is_exported() && !let_statement.visibility.is_default() {
+            self.push("export ");
+        }
+        self.push(format!("{} ", let_statement.keyword.text()));
+        match &let_statement.pattern {
+            ASTPattern::Identifier(identifier) => self.push(identifier.text()),
+            ASTPattern::Object(properties) => {
+                self.push("{ ");
+                self.push_separated(properties, |generator, property| {
+                    generator.push(property.text());
+                });
+                self.push(" }");
+            }
+        }
+        if let Some(ty) = &let_statement.type_annotation {
+            self.push(": ");
+            self.visit_type(ty);
+        }
+        self.push(" = ");
+        self.visit_expression(&let_statement.initializer);
+    }
+
+    fn visit_component_statement(&mut self, component: &ASTComponentStatement) {
+        self.scope_styles(component);
+        let impls = self
+            .impls
+            .get(component.identifier())
+            .cloned()
+            .unwrap_or_default();
+        if !impls.is_empty() {
+            self.push_impl_props(component, &impls);
+        } else if component.has_props() {
+            self.push_props_interface(&component.props_name(), component);
+        }
+
+        self.push_export(&component.visibility);
+        self.push(format!("function {}", component.identifier()));
+        self.push_generics(&component.generics);
+        self.push_props(component);
+        self.push(" ");
+        let controlled = ControlledProp::of(component);
+        match controlled.is_empty() {
+            true => self.push_block(&component.body),
+            false => self.push_block_with(
+                |generator| {
+                    for control in &controlled {
+                        generator.push_controlled_state(control);
+                    }
+                    generator.push_newline();
+                },
+                &component.body,
+            ),
+        }
+    }
+
+    fn visit_for_statement(&mut self, for_statement: &ASTForStatement) {
+        if let Some(label) = for_statement.label() {
+            self.push(format!("{}: ", label));
+        }
+
+        let keyword = match for_statement.is_mutable() {
+            true => "let",
+            false => "const",
+        };
+        let kind = match for_statement.kind {
+            ASTForKind::Of => "of",
+            ASTForKind::In => "in",
+        };
+        self.push(format!(
+            "for ({} {} {} ",
+            keyword,
+            for_statement.identifier(),
+            kind
+        ));
+        self.visit_expression(&for_statement.iterable);
+        self.push(") ");
+        self.push_block(&for_statement.body);
+    }
+
+    fn visit_while_statement(&mut self, while_statement: &ASTWhileStatement) {
+        if let Some(label) = while_statement.label() {
+            self.push(format!("{}: ", label));
+        }
+
+        self.push("while (");
+        self.visit_expression(&while_statement.condition);
+        self.push(") ");
+        self.push_block(&while_statement.body);
+    }
+
+    fn visit_break_statement(&mut self, break_statement: &ASTBreakStatement) {
+        match break_statement.label() {
+            Some(label) => self.push(format!("break {}", label)),
+            None => self.push("break"),
+        }
+    }
+
+    fn visit_continue_statement(&mut self, continue_statement: &ASTContinueStatement) {
+        match continue_statement.label() {
+            Some(label) => self.push(format!("continue {}", label)),
+            None => self.push("continue"),
+        }
+    }
+
+    fn visit_return_statement(&mut self, return_statement: &ASTReturnStatement) {
+        let Some(value) = &return_statement.value else {
+            self.push("return");
+            return;
+        };
+
+        let mut inner = value;
+        while let ASTExpressionKind::Parenthesized(parenthesized) = inner.kind() {
+            inner = &parenthesized.inner;
+        }
+        // Elements with children span several lines, which need parentheses after `return`.
+        let multiline = matches!(
+            inner.kind(),
+            ASTExpressionKind::Element(ASTElementExpression {
+                children: Some(_),
+                ..
+            })
+        );
+        if !multiline {
+            self.push("return ");
+            self.visit_expression(value);
+            return;
+        }
+
+        self.push("return (");
+        self.push_newline();
+        self.indent += 1;
+        self.push_indent();
+        self.visit_expression(inner);
+        self.indent -= 1;
+        self.push_newline();
+        self.push_indent();
+        self.push(")");
+    }
+
+    fn visit_type_alias_statement(&mut self, type_alias: &ASTTypeAliasStatement) {
+        if type_alias.visibility.is_exported() && !type_alias.visibility.is_default() {
+            self.push("export ");
+        }
+        self.push(format!("type {} = ", type_alias.identifier()));
+        self.visit_type(&type_alias.ty);
+    }
+
+    fn visit_error_expression(&mut self, span: &TextSpan) {
+        self.push(self.source.slice(*span));
+    }
+
+    fn visit_number_expression(&mut self, expression: &ASTNumberExpression) {
+        self.push(expression.number().to_string());
+    }
+
+    fn visit_string_expression(&mut self, expression: &ASTStringExpression) {
+        self.push(expression.literal.text());
+    }
+
+    fn visit_text_expression(&mut self, expression: &ASTTextExpression) {
+        // JSX doesn't allow `>` and `}` in text, unlike Anvyl.
+        let mut text = String::new();
+        for character in expression.value().chars() {
+            match character {
+                '>' | '}' => text.push_str(&format!("{{\"{}\"}}", character)),
+                _ => text.push(character),
+            }
+        }
+        self.push(text);
+    }
+
+    fn visit_array_expression(&mut self, expression: &ASTArrayExpression) {
+        self.push("[");
+        self.push_separated(&expression.elements, |generator, element| {
+            generator.visit_expression(element);
+        });
+        self.push("]");
+    }
+
+    fn visit_binary_expression(&mut self, expression: &ASTBinaryExpression) {
+        self.visit_expression(&expression.left);
+        self.push(format!(" {} ", expression.operator.token.text()));
+        self.visit_expression(&expression.right);
+    }
+
+    fn visit_parenthesized_expression(&mut self, expression: &ASTParenthesizedExpression) {
+        self.push("(");
+        self.visit_expression(&expression.inner);
+        self.push(")");
+    }
+
+    fn visit_expression(&mut self, expression: &ASTExpression) {
+        self.push_mapping(expression.span());
+        if let Some(name) = self
+            .model
+            .resolved
+            .get(expression.id())
+            .and_then(|symbol| self.renamed.get(symbol))
+        {
+            self.push(name.clone());
+            return;
+        }
+        match (
+            self.model.materialized_type(expression.id()),
+            expression.kind(),
+        ) {
+            (Some(members), _) => self.push_materialized_type(members),
+            (None, ASTExpressionKind::Element(element)) => {
+                self.push_element(expression.id(), element)
+            }
+            (None, _) => self.default_visit_expression(expression),
+        }
+    }
+
+    fn visit_variable_expression(&mut self, expression: &ASTVariableExpression) {
+        self.push(expression.identifier());
+    }
+
+    fn visit_call_expression(&mut self, expression: &ASTCallExpression) {
+        if let Some(chain) = IteratorChain::of(expression) {
+            self.push_iterator_chain(expression, &chain);
+            return;
+        }
+
+        self.visit_expression(&expression.callee);
+        if let Some("validate") = expression.callee_identifier()
+            && let ([ty], [value]) = (&expression.type_arguments[..], &expression.arguments[..])
+        {
+            // `validate<T>(value)` becomes `validate(value, isT)`.
+            let annotation = self.render(|generator| generator.visit_type(ty));
+            let guard = self.guards.guard(ty, &annotation);
+            self.push("(");
+            self.visit_expression(value);
+            self.push(format!(", {})", guard));
+            return;
+        }
+
+        if !expression.type_arguments.is_empty() {
+            self.push("<");
+            self.push_separated(&expression.type_arguments, |generator, ty| {
+                generator.visit_type(ty);
+            });
+            self.push(">");
+        }
+        self.push("(");
+        self.push_separated(&expression.arguments, |generator, argument| {
+            generator.visit_expression(argument);
+        });
+        self.push(")");
+    }
+
+    fn visit_member_expression(&mut self, expression: &ASTMemberExpression) {
+        self.visit_expression(&expression.object);
+        self.push(format!(".{}", expression.property()));
+    }
+
+    fn visit_index_expression(&mut self, expression: &ASTIndexExpression) {
+        self.visit_expression(&expression.object);
+        self.push("[");
+        self.visit_expression(&expression.index);
+        self.push("]");
+    }
+
+    fn visit_assignment_expression(&mut self, expression: &ASTAssignmentExpression) {
+        let setter = self.hooks.assignment_setter(expression.target.id());
+
+        let Some(setter) = setter else {
+            self.visit_expression(&expression.target);
+            self.push(format!(" {} ", expression.operator.token.text()));
+            self.visit_expression(&expression.value);
+            return;
+        };
+
+        self.push(format!("{}(", setter.name()));
+        if setter.is_functional() {
+            // Updates build on the latest state, which successive updates in a single event
+            // would otherwise overwrite, e.g. `setCount(count => count + 1)`.
+            self.visit_expression(&expression.target);
+            self.push(" => ");
+        }
+        if expression.operator.is_compound() {
+            // `value += "!"` becomes `onValueChanged(value + "!")`.
+            let operator = expression.operator.token.text().trim_end_matches('=');
+            self.visit_expression(&expression.target);
+            self.push(format!(" {} ", operator));
+
+            let parenthesize = matches!(
+                expression.value.kind(),
+                ASTExpressionKind::Binary(_) | ASTExpressionKind::Assignment(_)
+            );
+            if parenthesize {
+                self.push("(");
+            }
+            self.visit_expression(&expression.value);
+            if parenthesize {
+                self.push(")");
+            }
+        } else {
+            self.visit_expression(&expression.value);
+        }
+        self.push(")");
+    }
+
+    /// Writes an element with each child on a line of its own.
+    ///
+    /// Attribute values are always wrapped in braces, e.g. `gap=2` becomes `gap={2}`.
+    fn visit_closure_expression(&mut self, expression: &ASTClosureExpression) {
+        self.push("(");
+        self.push_separated(
+            &expression.parameters,
+            |generator, parameter| match parameter {
+                ASTClosureParameter::Binding(parameter) => {
+                    generator.push(parameter.identifier());
+                    if let Some(ty) = &parameter.ty {
+                        generator.push(": ");
+                        generator.visit_type(ty);
+                    }
+                }
+                ASTClosureParameter::Tuple(items) => {
+                    let items: Vec<&str> = items.iter().map(|item| item.text()).collect();
+                    generator.push(format!("[{}]", items.join(", ")));
+                }
+            },
+        );
+        self.push(") => ");
+        self.visit_expression(&expression.body);
+    }
+
+    fn visit_type(&mut self, ty: &ASTType) {
+        self.push_mapping(ty.span());
+        self.default_visit_type(ty);
+    }
+
+    fn visit_error_type(&mut self, span: &TextSpan) {
+        self.push(self.source.slice(*span));
+    }
+
+    fn visit_named_type(&mut self, identifier: &Token) {
+        self.push(identifier.text());
+    }
+
+    fn visit_literal_type(&mut self, literal: &Token) {
+        self.push(literal.text());
+    }
+
+    fn visit_union_type(&mut self, union: &ASTUnionType) {
+        for (index, member) in union.members().iter().enumerate() {
+            if index > 0 {
+                self.push(" | ");
+            }
+            self.push_nested_type(member);
+        }
+    }
+
+    /// TypeScript has no notion of trust, so only the underlying type is emitted.
+    fn visit_trusted_type(&mut self, trusted: &ASTTrustedType) {
+        self.visit_type(&trusted.inner);
+    }
+
+    fn visit_optional_type(&mut self, optional: &ASTOptionalType) {
+        self.push_nested_type(&optional.inner);
+        self.push(" | undefined");
+    }
+
+    fn visit_array_type(&mut self, array: &ASTArrayType) {
+        self.push_nested_type(&array.element);
+        self.push("[]");
+    }
+
+    fn visit_object_type(&mut self, object: &ASTObjectType) {
+        if object.properties().is_empty() {
+            self.push("{}");
+            return;
+        }
+        self.push("{ ");
+        for (index, property) in object.properties().iter().enumerate() {
+            if index > 0 {
+                self.push("; ");
+            }
+            self.push(property.identifier());
+            if property.is_optional() {
+                self.push("?");
+            }
+            self.push(": ");
+            self.visit_type(property.ty());
+        }
+        self.push(" }");
+    }
+
+    fn visit_function_type(&mut self, function: &ASTFunctionType) {
+        self.push_parameters(&function.parameters);
+        self.push(" => ");
+        self.visit_type(&function.return_type);
+    }
+
+    fn visit_generic_type(&mut self, generic: &ASTGenericType) {
+        self.push(format!("{}<", generic.identifier()));
+        self.push_separated(&generic.arguments, |generator, argument| {
+            generator.visit_type(argument);
+        });
+        self.push(">");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::compile;
+
+    fn tsx(source: &str) -> String {
+        let compilation = compile(source);
+        assert_eq!(compilation.diagnostics, Vec::<String>::new());
+        compilation.tsx.unwrap()
+    }
+
+    #[test]
+    fn writes_text_children_inline() {
+        let tsx = tsx("pub default component Greeting(name: string) {
+                return <div>
+                    <p>Hello world</p>
+                    <p>Hello {name}!</p>
+                    <p>
+                        Don't stop,
+                        {name} > everyone }
+                    </p>
+                </div>
+            }");
+        assert!(tsx.contains(
+            "    <div>\n      <p>Hello world</p>\n      <p>Hello {name}!</p>\n      \
+             <p>Don't stop,{name} {\">\"} everyone {\"}\"}</p>\n    </div>\n"
+        ));
+    }
+
+    #[test]
+    fn writes_fragments() {
+        let tsx = tsx("pub default component Pair() {
+                return <><p>One</p><p>Two</p></>
+            }");
+        assert!(tsx.contains("    <>\n      <p>One</p>\n      <p>Two</p>\n    </>\n"));
+    }
+
+    #[test]
+    fn writes_dashed_attributes() {
+        let tsx = tsx("component Item(id: string) {
+                return <li data-id={id} aria-label=\"Item\"></li>
+            }
+            pub default component List() {
+                return <Item id=\"a\" data-testid=\"item\" />
+            }");
+        assert!(tsx.contains("<li data-id={id} aria-label={\"Item\"}></li>"));
+        assert!(tsx.contains("<Item id={\"a\"} data-testid={\"item\"} />"));
+    }
+
+    const CONTROLLED_VALUE: &str = "import { useState, useCallback } from \"react\";
+
+export interface TextFieldProps {
+  value?: string;
+  defaultValue?: string;
+  onValueChanged?: (value: string) => void;
+}
+
+export default function TextField({ value: controlledValue, defaultValue, onValueChanged: onValueChangedProp }: TextFieldProps) {
+  const [uncontrolledValue, setUncontrolledValue] = useState(defaultValue);
+  const value = controlledValue !== undefined ? controlledValue : uncontrolledValue;
+  const onValueChanged = useCallback((value: string) => {
+    setUncontrolledValue(value);
+    onValueChangedProp?.(value);
+  }, [onValueChangedProp]);
+
+";
+
+    #[test]
+    fn makes_props_controllable_with_their_own_change_prop() {
+        let tsx = tsx("controllable(#value)
+pub default component TextField(value: string, onValueChanged) {
+    return <input #value onChange={onValueChanged} />
+}");
+        assert_eq!(
+            tsx,
+            format!(
+                "{}  return <input value={{value}} onChange={{onValueChanged}} />;\n}}\n",
+                CONTROLLED_VALUE
+            )
+        );
+    }
+
+    #[test]
+    fn makes_props_controllable_adding_their_change_prop() {
+        let tsx = tsx("controllable(#value)
+pub default component TextField(value: string) {
+    return <input #value />
+}");
+        assert_eq!(
+            tsx,
+            format!(
+                "{}  return <input value={{value}} />;\n}}\n",
+                CONTROLLED_VALUE
+            )
+        );
+    }
+
+    #[test]
+    fn starts_controllable_props_at_their_default() {
+        let tsx = tsx("controllable(#count)
+pub default component Counter(count: number = 0) {
+    return <p>{count}</p>
+}");
+        assert_eq!(
+            tsx,
+            "import { useState, useCallback } from \"react\";
+
+export interface CounterProps {
+  count?: number;
+  defaultCount?: number;
+  onCountChanged?: (count: number) => void;
+}
+
+export default function Counter({ count: controlledCount, defaultCount = 0, onCountChanged: onCountChangedProp }: CounterProps) {
+  const [uncontrolledCount, setUncontrolledCount] = useState(defaultCount);
+  const count = controlledCount !== undefined ? controlledCount : uncontrolledCount;
+  const onCountChanged = useCallback((count: number) => {
+    setUncontrolledCount(count);
+    onCountChangedProp?.(count);
+  }, [onCountChangedProp]);
+
+  return (
+    <p>
+      {count}
+    </p>
+  );
+}
+"
+        );
+    }
+
+    #[test]
+    fn notifies_assignments_to_controllable_props() {
+        let tsx = tsx("controllable(#value)
+pub default component TextField(value: string) {
+    callback clear() {
+        value = \"\"
+    }
+    return <input #value onBlur={|| value += \"!\"} onFocus={clear} />
+}");
+        assert_eq!(
+            tsx,
+            format!(
+                "{}  const clear = useCallback(() => onValueChanged(\"\"), [onValueChanged]);\n  \
+                 return <input value={{value}} onBlur={{() => onValueChanged(value + \"!\")}} \
+                 onFocus={{clear}} />;\n}}\n",
+                CONTROLLED_VALUE
+            )
+        );
+    }
+
+    #[test]
+    fn makes_props_conditional_on_the_constraints_of_impl_blocks() {
+        let tsx = tsx("pub component Autocomplete<T>(
+    options: T[],
+    value: T[],
+    onChange: (values: T[]) => unknown,
+    getOptionLabel: (e: T) => string
+) {
+    return <ul>{options}</ul>
+}
+
+impl Autocomplete<T excludes string> {
+    getOptionLabel?: (e: T) => string
+}");
+        assert_eq!(
+            tsx,
+            "export interface AutocompleteBaseProps<T> {
+  options: T[];
+  value: T[];
+  onChange: (values: T[]) => unknown;
+  getOptionLabel: (e: T) => string;
+}
+
+export type AutocompleteProps<T> = [T] extends [string] ? AutocompleteBaseProps<T> : Omit<AutocompleteBaseProps<T>, 'getOptionLabel'> & { getOptionLabel?: (e: T) => string };
+
+export function Autocomplete<T>({ options, value, onChange, getOptionLabel }: AutocompleteProps<T>) {
+  return (
+    <ul>
+      {options}
+    </ul>
+  );
+}
+"
+        );
+    }
+}
@@ -0,0 +1,248 @@
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Links a position in the generated output to the source position it was emitted from,
+/// both as byte offsets.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Mapping {
+    pub(crate) generated: usize,
+    pub(crate) original: usize,
+}
+
+/// A Source Map v3 from generated `.tsx` back to its `.avyl` source.
+#[derive(Debug)]
+pub struct SourceMap {
+    /// Name of the generated file, e.g. `ChatRoom.tsx`.
+    file: String,
+    /// Path of the source file, relative to the map.
+    source: String,
+    content: String,
+    mappings: String,
+}
+
+impl SourceMap {
+    /// Encodes `mappings`, which must be ordered by their generated offset.
+    pub(crate) fn new(
+        file: &str,
+        source: &str,
+        content: &str,
+        generated: &str,
+        mappings: &[Mapping],
+    ) -> Self {
+        Self {
+            file: file.to_string(),
+            source: source.to_string(),
+            content: content.to_string(),
+            mappings: Self::encode(generated, content, mappings),
+        }
+    }
+
+    /// The VLQ-encoded `mappings` field.
+    pub fn mappings(&self) -> &str {
+        &self.mappings
+    }
+
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"version\":3,\"file\":{},\"sources\":[{}],\"sourcesContent\":[{}],\"names\":[],\"mappings\":{}}}",
+            json_string(&self.file),
+            json_string(&self.source),
+            json_string(&self.content),
+            json_string(&self.mappings)
+        )
+    }
+
+    /// A comment pointing at the map written next to the generated file, as `<file>.map`.
+    pub fn url_comment(&self) -> String {
+        format!("//# sourceMappingURL={}.map", self.file)
+    }
+
+    /// A comment embedding the whole map as a base64 data URL.
+    pub fn inline_comment(&self) -> String {
+        format!(
+            "//# sourceMappingURL=data:application/json;charset=utf-8;base64,{}",
+            base64(self.to_json().as_bytes())
+        )
+    }
+
+    /// Writes one segment per mapping, each relative to the one before it.
+    ///
+    /// Columns are counted in UTF-16 code units, as JavaScript tooling expects.
+    fn encode(generated: &str, content: &str, mappings: &[Mapping]) -> String {
+        let generated_lines = LineIndex::new(generated);
+        let source_lines = LineIndex::new(content);
+
+        let mut result = String::new();
+        let (mut line, mut column) = (0, 0);
+        let (mut source_line, mut source_column) = (0, 0);
+        for (index, mapping) in mappings.iter().enumerate() {
+            // Only the innermost node starting at a position is kept.
+            if mappings
+                .get(index + 1)
+                .is_some_and(|next| next.generated == mapping.generated)
+            {
+                continue;
+            }
+
+            let (generated_line, generated_column) = generated_lines.position(mapping.generated);
+            if generated_line > line {
+                result.push_str(&";".repeat(generated_line - line));
+                (line, column) = (generated_line, 0);
+            } else if !result.is_empty() && !result.ends_with(';') {
+                result.push(',');
+            }
+
+            let (original_line, original_column) = source_lines.position(mapping.original);
+            vlq(generated_column as i64 - column as i64, &mut result);
+            vlq(0, &mut result);
+            vlq(original_line as i64 - source_line as i64, &mut result);
+            vlq(original_column as i64 - source_column as i64, &mut result);
+
+            column = generated_column;
+            (source_line, source_column) = (original_line, original_column);
+        }
+        result
+    }
+}
+
+/// Converts byte offsets into zero-based lines and UTF-16 columns.
+struct LineIndex<'a> {
+    text: &'a str,
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    fn new(text: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        Self { text, starts }
+    }
+
+    fn position(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.text.len());
+        let line = self.starts.partition_point(|start| *start <= offset) - 1;
+        let column = self.text[self.starts[line]..offset].encode_utf16().count();
+        (line, column)
+    }
+}
+
+/// Appends a base64 VLQ: sign in the lowest bit, then 5 bits per digit, least significant
+/// first, with the 6th bit marking a continuation.
+fn vlq(value: i64, result: &mut String) {
+    let mut value = match value < 0 {
+        true => ((-value) << 1) | 1,
+        false => value << 1,
+    };
+    loop {
+        let mut digit = value & 0b11111;
+        value >>= 5;
+        if value > 0 {
+            digit |= 0b100000;
+        }
+        result.push(BASE64[digit as usize] as char);
+        if value == 0 {
+            break;
+        }
+    }
+}
+
+fn base64(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (index, byte)| {
+            group | (*byte as u32) << (16 - 8 * index)
+        });
+        for index in 0..4 {
+            match index <= chunk.len() {
+                true => {
+                    result.push(BASE64[(group >> (18 - 6 * index) & 0b111111) as usize] as char)
+                }
+                false => result.push('='),
+            }
+        }
+    }
+    result
+}
+
+fn json_string(text: &str) -> String {
+    let mut result = String::with_capacity(text.len() + 2);
+    result.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            character if (character as u32) < 0x20 => {
+                result.push_str(&format!("\\u{:04x}", character as u32))
+            }
+            character => result.push(character),
+        }
+    }
+    result.push('"');
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encoded(value: i64) -> String {
+        let mut result = String::new();
+        vlq(value, &mut result);
+        result
+    }
+
+    #[test]
+    fn encodes_vlq_values() {
+        assert_eq!(encoded(0), "A");
+        assert_eq!(encoded(1), "C");
+        assert_eq!(encoded(-1), "D");
+        assert_eq!(encoded(15), "e");
+        // 16 no longer fits in the first digit, which leaves 4 bits after the sign.
+        assert_eq!(encoded(16), "gB");
+        assert_eq!(encoded(-16), "hB");
+        assert_eq!(encoded(1000), "w+B");
+    }
+
+    #[test]
+    fn pads_base64_to_whole_groups() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foob"), "Zm9vYg==");
+    }
+
+    #[test]
+    fn counts_columns_in_utf16_code_units() {
+        let lines = LineIndex::new("a\né😀b");
+        assert_eq!(lines.position(0), (0, 0));
+        assert_eq!(lines.position(2), (1, 0));
+        // `é` is 2 bytes and 1 unit, `😀` is 4 bytes and 2 units.
+        assert_eq!(lines.position(4), (1, 1));
+        assert_eq!(lines.position(8), (1, 3));
+    }
+
+    #[test]
+    fn keeps_the_last_mapping_of_a_generated_offset() {
+        let mappings = [
+            Mapping {
+                generated: 0,
+                original: 0,
+            },
+            Mapping {
+                generated: 0,
+                original: 1,
+            },
+            Mapping {
+                generated: 3,
+                original: 3,
+            },
+        ];
+        let map = SourceMap::new("a.tsx", "a.avyl", "ab\ncd", "ab\ncd", &mappings);
+        // The second line starts over at generated column 0, one line and column back.
+        assert_eq!(map.mappings(), "AAAC;AACD");
+    }
+}
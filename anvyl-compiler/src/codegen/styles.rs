@@ -0,0 +1,322 @@
+/// At-rules whose blocks hold style rules, which are scoped like top-level ones.
+const GROUPING_RULES: &[&str] = &["@media", "@supports", "@container", "@layer"];
+
+/// The stylesheet of a generated file, made of the `style { ... }` blocks of its components.
+///
+/// Every rule of a component is scoped to the elements it renders: the root elements get a
+/// class derived from the component, which each selector is scoped to, so `.title { ... }`
+/// becomes `.ChatRoom-3f9a1c.title, .ChatRoom-3f9a1c .title { ... }`, matching root elements
+/// with the class as well as their descendants. The root elements themselves are selected
+/// with `:scope`.
+#[derive(Debug, Default)]
+pub(crate) struct Stylesheet {
+    css: String,
+}
+
+impl Stylesheet {
+    /// Adds the rules of a component's style block, returning the class scoping them.
+    pub(crate) fn add(&mut self, component: &str, css: &str) -> String {
+        let class = format!("{}-{:06x}", component, hash(component, css) & 0xff_ffff);
+        if !self.css.is_empty() {
+            self.css.push('\n');
+        }
+        CssScoper::new(css, &class).rules(0, &mut self.css);
+        class
+    }
+
+    /// The stylesheet, or `None` when no component has a style block.
+    pub(crate) fn css(&self) -> Option<&str> {
+        (!self.css.is_empty()).then_some(self.css.as_str())
+    }
+}
+
+/// FNV-1a, so a component gets the same class for as long as its styles don't change.
+fn hash(component: &str, css: &str) -> u32 {
+    component
+        .bytes()
+        .chain([0])
+        .chain(css.bytes())
+        .fold(0x811c_9dc5, |hash, byte| {
+            (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+        })
+}
+
+/// Rewrites the rules of a style block, nesting every selector under a class.
+///
+/// Only the structure of the stylesheet is read, so declarations are copied as written.
+struct CssScoper<'a> {
+    css: &'a str,
+    /// Byte offset of the next character.
+    position: usize,
+    class: &'a str,
+}
+
+impl<'a> CssScoper<'a> {
+    fn new(css: &'a str, class: &'a str) -> Self {
+        Self {
+            css,
+            position: 0,
+            class,
+        }
+    }
+
+    /// Writes the rules up to the end of the enclosing block, indented by `depth` levels.
+    fn rules(&mut self, depth: usize, result: &mut String) {
+        let indent = "  ".repeat(depth);
+        loop {
+            self.skip_trivia();
+            if self.position >= self.css.len() || self.rest().starts_with('}') {
+                return;
+            }
+
+            let prelude = self.read_until(&['{', ';', '}']);
+            let prelude = prelude.trim().to_string();
+            if !self.rest().starts_with('{') {
+                // A statement at-rule, e.g. `@import url("fonts.css");`.
+                self.position += self.rest().len().min(1);
+                result.push_str(&format!("{}{};\n", indent, prelude));
+                continue;
+            }
+            self.position += 1;
+
+            let keyword = prelude.split_whitespace().next().unwrap_or_default();
+            if GROUPING_RULES.contains(&keyword) {
+                result.push_str(&format!("{}{} {{\n", indent, prelude));
+                self.rules(depth + 1, result);
+            } else {
+                // Other at-rules, e.g. `@keyframes`, hold no selectors to scope.
+                let selector = match prelude.starts_with('@') {
+                    true => prelude,
+                    false => self.scope_selectors(&prelude),
+                };
+                result.push_str(&format!("{}{} {{\n", indent, selector));
+                self.declarations(depth + 1, result);
+            }
+            self.position += self.rest().len().min(1);
+            result.push_str(&format!("{}}}\n", indent));
+        }
+    }
+
+    /// Writes the declarations of a block on a line each, up to its closing brace.
+    fn declarations(&mut self, depth: usize, result: &mut String) {
+        let indent = "  ".repeat(depth);
+        loop {
+            self.skip_trivia();
+            if self.position >= self.css.len() || self.rest().starts_with('}') {
+                return;
+            }
+            if self.rest().starts_with('{') {
+                // A nested block, e.g. a keyframe of `@keyframes`.
+                self.position += 1;
+                self.declarations(depth + 1, result);
+                self.position += self.rest().len().min(1);
+                continue;
+            }
+
+            let declaration = self.read_until(&[';', '{', '}']);
+            let declaration = declaration.trim();
+            if self.rest().starts_with('{') {
+                // The selector of a nested block, e.g. `from` or `50%`.
+                self.position += 1;
+                result.push_str(&format!("{}{} {{\n", indent, declaration));
+                self.declarations(depth + 1, result);
+                self.position += self.rest().len().min(1);
+                result.push_str(&format!("{}}}\n", indent));
+                continue;
+            }
+            if self.rest().starts_with(';') {
+                self.position += 1;
+            }
+            if !declaration.is_empty() {
+                result.push_str(&format!("{}{};\n", indent, declaration));
+            }
+        }
+    }
+
+    /// Scopes each selector of a list to the scoping class, replacing `:scope` with it.
+    ///
+    /// Other selectors match on the root elements as well as within them, e.g. `p.note`
+    /// becomes `p.Card-3f9a1c.note, .Card-3f9a1c p.note`, unless they start with a
+    /// combinator, e.g. `> p` becomes `.Card-3f9a1c > p`.
+    fn scope_selectors(&self, selectors: &str) -> String {
+        let scope = format!(".{}", self.class);
+        split_top_level(selectors, ',')
+            .iter()
+            .map(|selector| selector.trim())
+            .filter(|selector| !selector.is_empty())
+            .map(|selector| {
+                if selector.contains(":scope") {
+                    return selector.replace(":scope", &scope);
+                }
+                if selector.starts_with(['>', '+', '~']) {
+                    return format!("{} {}", scope, selector);
+                }
+                // A type selector must come first in its compound selector.
+                let type_end = selector
+                    .find(|c: char| !(c.is_alphanumeric() || matches!(c, '-' | '_' | '*')))
+                    .unwrap_or(selector.len());
+                let (element, rest) = selector.split_at(type_end);
+                format!("{}{}{}, {} {}", element, scope, rest, scope, selector)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.css[self.position.min(self.css.len())..]
+    }
+
+    /// Skips whitespace and `/* ... */` comments.
+    fn skip_trivia(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.position += rest.len() - trimmed.len();
+            if !trimmed.starts_with("/*") {
+                return;
+            }
+            self.position += match trimmed[2..].find("*/") {
+                Some(end) => end + 4,
+                None => trimmed.len(),
+            };
+        }
+    }
+
+    /// Reads up to the first of `ends` outside of strings and parentheses.
+    fn read_until(&mut self, ends: &[char]) -> String {
+        let start = self.position;
+        let mut quote = None;
+        let mut escaped = false;
+        let mut depth = 0;
+        for (offset, c) in self.rest().char_indices() {
+            if std::mem::take(&mut escaped) {
+                continue;
+            }
+            match (quote, c) {
+                (Some(_), '\\') => escaped = true,
+                (Some(open), c) if c == open => quote = None,
+                (Some(_), _) => {}
+                (None, '"' | '\'') => quote = Some(c),
+                (None, '(' | '[') => depth += 1,
+                (None, ')' | ']') => depth -= 1,
+                (None, c) if depth == 0 && ends.contains(&c) => {
+                    self.position = start + offset;
+                    return self.css[start..self.position].to_string();
+                }
+                _ => {}
+            }
+        }
+        self.position = self.css.len();
+        self.css[start..].to_string()
+    }
+}
+
+/// Splits `text` at each `separator` outside of parentheses and brackets, e.g. the
+/// selectors of `a:is(b, c), d`.
+fn split_top_level(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, c) in text.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            c if c == separator && depth == 0 => {
+                parts.push(&text[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scoped(css: &str) -> String {
+        let mut result = String::new();
+        CssScoper::new(css, "Card-1").rules(0, &mut result);
+        result
+    }
+
+    #[test]
+    fn scopes_selectors_to_the_root_elements_and_within_them() {
+        assert_eq!(
+            scoped(".card { color: red } p, a:is(.x, .y) { margin: 0 } * { gap: 0 }"),
+            ".Card-1.card, .Card-1 .card {
+  color: red;
+}
+p.Card-1, .Card-1 p, a.Card-1:is(.x, .y), .Card-1 a:is(.x, .y) {
+  margin: 0;
+}
+*.Card-1, .Card-1 * {
+  gap: 0;
+}
+"
+        );
+    }
+
+    #[test]
+    fn replaces_scope_and_keeps_leading_combinators_within() {
+        assert_eq!(
+            scoped(":scope > .title { gap: 1px } > p { gap: 2px }"),
+            ".Card-1 > .title {
+  gap: 1px;
+}
+.Card-1 > p {
+  gap: 2px;
+}
+"
+        );
+    }
+
+    #[test]
+    fn scopes_the_rules_of_grouping_at_rules_only() {
+        assert_eq!(
+            scoped(
+                "@media (min-width: 600px) { .card { gap: 2px; } }
+@keyframes fade { from { opacity: 0; } to { opacity: 1; } }
+@import url(\"a.css\");"
+            ),
+            "@media (min-width: 600px) {
+  .Card-1.card, .Card-1 .card {
+    gap: 2px;
+  }
+}
+@keyframes fade {
+  from {
+    opacity: 0;
+  }
+  to {
+    opacity: 1;
+  }
+}
+@import url(\"a.css\");
+"
+        );
+    }
+
+    #[test]
+    fn skips_comments_and_braces_in_strings() {
+        assert_eq!(
+            scoped(".a::after { content: \"}\"; } /* .b { } */ .c {}"),
+            ".Card-1.a::after, .Card-1 .a::after {
+  content: \"}\";
+}
+.Card-1.c, .Card-1 .c {
+}
+"
+        );
+    }
+
+    #[test]
+    fn derives_the_class_from_the_component_and_its_styles() {
+        let class = |css: &str| Stylesheet::default().add("Card", css);
+        assert_eq!(class(".a {}"), class(".a {}"));
+        assert_ne!(class(".a {}"), class(".b {}"));
+        assert!(class(".a {}").starts_with("Card-"));
+    }
+}
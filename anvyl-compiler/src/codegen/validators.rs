@@ -0,0 +1,217 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::prelude::*;
+
+/// Throws unless `guard` accepts the value, narrowing it to the guarded type.
+const VALIDATE_HELPER: &str =
+    "function validate<T>(value: unknown, guard: (value: unknown) => value is T): T {
+  if (!guard(value)) {
+    throw new TypeError(\"Value does not match its expected type\");
+  }
+  return value;
+}
+";
+
+/// Builds the runtime type guards behind the `validate<T>(value)` intrinsic.
+///
+/// Each type alias a guard refers to gets its own `isAlias` function, so recursive
+/// aliases are checked by recursive calls rather than expanding forever.
+pub(crate) struct TypeGuards<'a, 'ast> {
+    aliases: HashMap<&'a str, &'a ASTType<'ast>>,
+    /// Aliases which need a guard function, in the order they were first referenced.
+    referenced: RefCell<Vec<String>>,
+    validated: bool,
+}
+
+impl<'a, 'ast> TypeGuards<'a, 'ast> {
+    pub(crate) fn new(ast: &'a AST<'ast>) -> Self {
+        let aliases = ast
+            .statements
+            .iter()
+            .filter_map(|statement| match statement.kind() {
+                ASTStatementKind::TypeAlias(type_alias) => {
+                    Some((type_alias.identifier(), &type_alias.ty))
+                }
+                _ => None,
+            })
+            .collect();
+
+        Self {
+            aliases,
+            referenced: RefCell::new(Vec::new()),
+            validated: false,
+        }
+    }
+
+    /// Returns the guard to pass to `validate`: a named guard for an alias, or an inline
+    /// arrow function for any other type, whose TypeScript form is `annotation`.
+    pub(crate) fn guard(&mut self, ty: &ASTType, annotation: &str) -> String {
+        self.validated = true;
+        match self.alias(ty) {
+            Some(alias) => self.reference(alias),
+            None => format!(
+                "(value: unknown): value is {} => {}",
+                annotation,
+                self.condition(ty, "value")
+            ),
+        }
+    }
+
+    /// The `validate` helper and every referenced alias guard, or nothing when
+    /// `validate` is never called.
+    pub(crate) fn functions(&self) -> String {
+        if !self.validated {
+            return String::new();
+        }
+
+        let mut result = format!("\n{}", VALIDATE_HELPER);
+        // Guards may reference further aliases, which are appended as they are found.
+        let mut index = 0;
+        loop {
+            let Some(alias) = self.referenced.borrow().get(index).cloned() else {
+                break;
+            };
+            let condition = self.condition(self.aliases[alias.as_str()], "value");
+            result.push_str(&format!(
+                "\nfunction {}(value: unknown): value is {} {{\n  return {};\n}}\n",
+                Self::guard_name(&alias),
+                alias,
+                condition
+            ));
+            index += 1;
+        }
+        result
+    }
+
+    fn alias(&self, ty: &ASTType) -> Option<&'a str> {
+        match ty.kind() {
+            ASTTypeKind::Named(identifier) => self
+                .aliases
+                .get_key_value(identifier.text())
+                .map(|(alias, _)| *alias),
+            ASTTypeKind::Trusted(trusted) => self.alias(trusted.inner()),
+            _ => None,
+        }
+    }
+
+    fn reference(&self, alias: &str) -> String {
+        let mut referenced = self.referenced.borrow_mut();
+        if !referenced.iter().any(|existing| existing == alias) {
+            referenced.push(alias.to_string());
+        }
+        Self::guard_name(alias)
+    }
+
+    /// Derives the guard name of an alias, e.g. `message` -> `isMessage`.
+    fn guard_name(alias: &str) -> String {
+        let mut characters = alias.chars();
+        match characters.next() {
+            Some(first) => format!("is{}{}", first.to_uppercase(), characters.as_str()),
+            None => "is".to_string(),
+        }
+    }
+
+    /// A boolean expression checking that `value` holds a value of type `ty`.
+    ///
+    /// Types without a runtime representation, which the `TypeChecker` reports, are accepted
+    /// as is.
+    fn condition(&self, ty: &ASTType, value: &str) -> String {
+        match ty.kind() {
+            ASTTypeKind::Error(_) => "true".to_string(),
+            ASTTypeKind::Named(identifier) => match identifier.text() {
+                name @ ("number" | "string" | "boolean") => {
+                    format!("typeof {} === \"{}\"", value, name)
+                }
+                "undefined" | "void" => format!("{} === undefined", value),
+                "null" => format!("{} === null", value),
+                "never" => "false".to_string(),
+                name if self.aliases.contains_key(name) => {
+                    format!("{}({})", self.reference(name), value)
+                }
+                _ => "true".to_string(),
+            },
+            ASTTypeKind::Literal(literal) => {
+                format!("{} === {}", value, literal.text())
+            }
+            ASTTypeKind::Union(union) => {
+                let members: Vec<String> = union
+                    .members()
+                    .iter()
+                    .map(|member| self.condition(member, value))
+                    .collect();
+                format!("({})", members.join(" || "))
+            }
+            ASTTypeKind::Optional(optional) => format!(
+                "({} === undefined || {})",
+                value,
+                self.condition(optional.inner(), value)
+            ),
+            ASTTypeKind::Array(array) => self.array_condition(array.element(), value),
+            ASTTypeKind::Function(_) => format!("typeof {} === \"function\"", value),
+            ASTTypeKind::Object(object) => self.object_condition(object, value),
+            ASTTypeKind::Trusted(trusted) => self.condition(trusted.inner(), value),
+            ASTTypeKind::Generic(generic) => match (generic.identifier(), generic.arguments()) {
+                ("Array", [element]) => self.array_condition(element, value),
+                _ => "true".to_string(),
+            },
+        }
+    }
+
+    /// Checks each property of an object, after `in` checks which let TypeScript narrow
+    /// `value` to an object having the property.
+    fn object_condition(&self, object: &ASTObjectType, value: &str) -> String {
+        let mut conditions = vec![
+            format!("typeof {} === \"object\"", value),
+            format!("{} !== null", value),
+        ];
+        for property in object.properties() {
+            let name = property.identifier();
+            let member = format!("{}.{}", value, name);
+            let condition = self.condition(property.ty(), &member);
+            conditions.push(match property.is_optional() {
+                true => format!(
+                    "(!(\"{}\" in {}) || {} === undefined || {})",
+                    name, value, member, condition
+                ),
+                false => format!("\"{}\" in {} && {}", name, value, condition),
+            });
+        }
+        format!("({})", conditions.join(" && "))
+    }
+
+    fn array_condition(&self, element: &ASTType, value: &str) -> String {
+        format!(
+            "Array.isArray({}) && {}.every((element) => {})",
+            value,
+            value,
+            self.condition(element, "element")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::compile;
+
+    #[test]
+    fn checks_each_property_of_objects() {
+        let tsx = compile(
+            "type User = { name: string, age?: number, friends: User[] }
+            pub default component Profile(data: unknown) {
+                const user = validate<User>(data)
+                return <p>{user.name}</p>
+            }",
+        )
+        .tsx
+        .unwrap();
+        assert!(tsx.contains(
+            "function isUser(value: unknown): value is User {\n  return (typeof value === \"object\" \
+             && value !== null \
+             && \"name\" in value && typeof value.name === \"string\" \
+             && (!(\"age\" in value) || value.age === undefined || typeof value.age === \"number\") \
+             && \"friends\" in value && Array.isArray(value.friends) \
+             && value.friends.every((element) => isUser(element)));\n}\n"
+        ));
+    }
+}
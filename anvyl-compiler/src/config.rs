@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use toml::de::{DeTable, DeValue};
+
+/// Named exports of well known packages, so names used through `import react::*` can be
+/// told apart from those of other `*` imports.
+const BUILTIN_EXPORTS: &[(&str, &[&str])] = &[(
+    "react",
+    &[
+        "use",
+        "useActionState",
+        "useCallback",
+        "useContext",
+        "useDebugValue",
+        "useDeferredValue",
+        "useEffect",
+        "useId",
+        "useImperativeHandle",
+        "useInsertionEffect",
+        "useLayoutEffect",
+        "useMemo",
+        "useOptimistic",
+        "useReducer",
+        "useRef",
+        "useState",
+        "useSyncExternalStore",
+        "useTransition",
+        "createContext",
+        "forwardRef",
+        "memo",
+        "lazy",
+        "startTransition",
+        "Fragment",
+        "StrictMode",
+        "Suspense",
+    ],
+)];
+
+/// Project settings, read from the `anvyl.toml` at the root of a project.
+///
+/// ```toml
+/// [aliases]
+/// components = "@components"
+/// context = "@contexts"
+///
+/// [exports]
+/// "date-fns" = ["format", "parseISO"]
+/// ```
+#[derive(Debug)]
+pub struct Config {
+    /// Module paths of project folders, mapped to the TypeScript specifier they are
+    /// imported from, e.g. `context` -> `@contexts`.
+    aliases: Vec<(String, String)>,
+    /// The named exports of packages, keyed by their module path.
+    exports: HashMap<String, Vec<String>>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let exports = BUILTIN_EXPORTS
+            .iter()
+            .map(|(module, names)| {
+                let names = names.iter().map(|name| name.to_string()).collect();
+                (module.to_string(), names)
+            })
+            .collect();
+
+        Self {
+            aliases: Vec::new(),
+            exports,
+        }
+    }
+}
+
+impl Config {
+    /// Reads the settings from the text of an `anvyl.toml`.
+    ///
+    /// Tables other than `[aliases]` and `[exports]` may hold anything, as they don't affect
+    /// compilation, e.g. the project name or version.
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let line = |offset: usize| text[..offset].matches('\n').count() + 1;
+        let document = DeTable::parse(text).map_err(|error| ConfigError {
+            line: error.span().map_or(1, |span| line(span.start)),
+            message: error.message().to_string(),
+        })?;
+
+        let mut config = Config::default();
+        for (table, entries) in document.get_ref() {
+            let table = table.get_ref().as_ref();
+            if !matches!(table, "aliases" | "exports") {
+                continue;
+            }
+            let DeValue::Table(entries) = entries.get_ref() else {
+                return Err(ConfigError {
+                    line: line(entries.span().start),
+                    message: format!("'{}' must be a table", table),
+                });
+            };
+
+            for (key, value) in entries {
+                let key = key.get_ref().to_string();
+                let error = |expected: &str| ConfigError {
+                    line: line(value.span().start),
+                    message: format!("'{}.{}' must be {}", table, key, expected),
+                };
+                match (table, value.get_ref()) {
+                    ("aliases", DeValue::String(specifier)) => {
+                        config.aliases.push((key, specifier.to_string()))
+                    }
+                    ("aliases", _) => return Err(error("a string")),
+                    (_, DeValue::Array(names)) => {
+                        let names = names
+                            .iter()
+                            .map(|name| match name.get_ref() {
+                                DeValue::String(name) => Ok(name.to_string()),
+                                _ => Err(error("an array of strings")),
+                            })
+                            .collect::<Result<_, _>>()?;
+                        config.exports.insert(key, names);
+                    }
+                    _ => return Err(error("an array of strings")),
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    /// The alias of the longest prefix of a module `path`, along with the segments
+    /// following that prefix.
+    pub(crate) fn alias<'p>(&self, path: &'p [&'p str]) -> Option<(&str, &'p [&'p str])> {
+        self.aliases
+            .iter()
+            .filter_map(|(module, specifier)| {
+                let segments: Vec<&str> = module.split("::").collect();
+                path.starts_with(&segments)
+                    .then(|| (specifier.as_str(), &path[segments.len()..]))
+            })
+            .min_by_key(|(_, rest)| rest.len())
+    }
+
+    /// The named exports of the package at a module `path`, e.g. `react`, if known.
+    pub(crate) fn exports(&self, path: &str) -> Option<&[String]> {
+        self.exports.get(path).map(Vec::as_slice)
+    }
+}
+
+#[derive(Debug)]
+pub struct ConfigError {
+    line: usize,
+    message: String,
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_aliases_and_exports() {
+        let config = Config::parse(
+            r#"
+            [aliases]
+            components = "@components"
+            "ui::forms" = "@forms"
+
+            [exports]
+            "date-fns" = [
+                "format", # Multi-line arrays may hold comments.
+                "parseISO",
+            ]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.alias(&["ui", "forms", "Input"]),
+            Some(("@forms", &["Input"][..]))
+        );
+        assert_eq!(
+            config.alias(&["components", "Card"]),
+            Some(("@components", &["Card"][..]))
+        );
+        assert_eq!(
+            config.exports("date-fns"),
+            Some(&["format".to_string(), "parseISO".to_string()][..])
+        );
+        assert!(config.exports("react").is_some());
+    }
+
+    #[test]
+    fn ignores_settings_not_affecting_compilation() {
+        let config = Config::parse(
+            r#"
+            name = "Chat example"
+            x = 1.5
+            released = 2024-01-01
+            anvyl-general = { version = "1.0" }
+            notes = """
+            multi-line
+            """
+
+            [[bin]]
+            name = "chat"
+
+            [aliases]
+            components = "@components"
+            "#,
+        )
+        .unwrap();
+
+        assert!(config.alias(&["components"]).is_some());
+    }
+
+    #[test]
+    fn reports_invalid_settings_with_their_line() {
+        let error = Config::parse("[aliases]\ncomponents = 1\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2: 'aliases.components' must be a string"
+        );
+
+        let error = Config::parse("[exports]\nreact = [\"use\", 2]\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2: 'exports.react' must be an array of strings"
+        );
+
+        let error = Config::parse("name = \n").unwrap_err();
+        assert!(error.to_string().starts_with("line 1: "));
+    }
+}
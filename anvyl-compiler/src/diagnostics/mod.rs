@@ -37,6 +37,12 @@ pub struct DiagnosticsBag {
     pub diagnostics: Vec<Diagnostic>,
 }
 
+impl Default for DiagnosticsBag {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DiagnosticsBag {
     pub fn new() -> Self {
         Self {
@@ -52,6 +58,13 @@ impl DiagnosticsBag {
         self.diagnostics.is_empty()
     }
 
+    /// Whether any of the diagnostics is an error, rather than only warnings.
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|diagnostic| matches!(diagnostic.kind, DiagnosticKind::Error))
+    }
+
     pub fn report_error(&mut self, message: String, span: TextSpan) {
         let error = Diagnostic::new(DiagnosticKind::Error, message, span);
         self.diagnostics.push(error);
@@ -64,7 +77,7 @@ impl DiagnosticsBag {
 
     pub fn report_unexpected_token(&mut self, expected: &TokenKind, found: &Token) {
         let message = format!("Expected {}, found {}", expected, found.kind);
-        self.report_error(message, found.span.clone());
+        self.report_error(message, found.span);
     }
 
     pub fn report_undeclared_variable(&mut self, identifier: String, span: TextSpan) {
@@ -72,6 +85,396 @@ impl DiagnosticsBag {
         self.report_error(message, span);
     }
 
+    pub fn report_used_before_declaration(&mut self, identifier: String, span: TextSpan) {
+        let message = format!("Variable '{}' is used before its declaration", identifier);
+        self.report_error(message, span);
+    }
+
+    pub fn report_duplicate_declaration(&mut self, identifier: String, span: TextSpan) {
+        let message = format!("'{}' is already declared in this scope", identifier);
+        self.report_error(message, span);
+    }
+
+    pub fn report_unused_variable(&mut self, identifier: String, span: TextSpan) {
+        let message = format!("Unused variable '{}'", identifier);
+        self.report_warning(message, span);
+    }
+
+    pub fn report_type_mismatch(&mut self, expected: String, found: String, span: TextSpan) {
+        let message = format!("Type '{}' is not assignable to type '{}'", found, expected);
+        self.report_error(message, span);
+    }
+
+    pub fn report_invalid_operands(&mut self, operator: &Token, left: String, right: String) {
+        let message = format!(
+            "Operator '{}' cannot be applied to types '{}' and '{}'",
+            operator.kind, left, right
+        );
+        self.report_error(message, operator.span);
+    }
+
+    pub fn report_not_callable(&mut self, ty: String, span: TextSpan) {
+        let message = format!("Type '{}' is not callable", ty);
+        self.report_error(message, span);
+    }
+
+    pub fn report_argument_count_mismatch(
+        &mut self,
+        expected: usize,
+        found: usize,
+        span: TextSpan,
+    ) {
+        let message = format!("Expected {} arguments, found {}", expected, found);
+        self.report_error(message, span);
+    }
+
+    pub fn report_possibly_undefined(&mut self, ty: String, span: TextSpan) {
+        let message = format!("Value of type '{}' is possibly undefined", ty);
+        self.report_error(message, span);
+    }
+
+    pub fn report_not_iterable(&mut self, ty: String, span: TextSpan) {
+        let message = format!("Type '{}' is not iterable", ty);
+        self.report_error(message, span);
+    }
+
+    pub fn report_unknown_iterator_method(&mut self, method: &Token) {
+        let message = format!(
+            "Iterators have no '{}' method, expected one of map, filter, take, enumerate, sum, collect, find, any or all",
+            method.text()
+        );
+        self.report_error(message, method.span);
+    }
+
+    pub fn report_expected_item_closure(&mut self, method: &Token, span: TextSpan) {
+        let message = format!(
+            "'{}' expects a closure taking a single item, e.g. '|item| ...'",
+            method.text()
+        );
+        self.report_error(message, span);
+    }
+
+    pub fn report_unconsumed_iterator(&mut self, span: TextSpan) {
+        let message =
+            "Iterators are lazy and do nothing unless consumed, e.g. with 'collect()'".to_string();
+        self.report_error(message, span);
+    }
+
+    pub fn report_unknown_property(&mut self, ty: String, property: &Token) {
+        let message = format!(
+            "Property '{}' does not exist on type '{}'",
+            property.text(),
+            ty
+        );
+        self.report_error(message, property.span);
+    }
+
+    pub fn report_untrusted_value(&mut self, span: TextSpan) {
+        let message = "This value is untrusted, but a trusted value is required here".to_string();
+        self.report_error(message, span);
+    }
+
+    pub fn report_invalid_validate(&mut self, span: TextSpan) {
+        let message =
+            "`validate<T>(value)` expects exactly 1 type argument and 1 argument".to_string();
+        self.report_error(message, span);
+    }
+
+    pub fn report_unguarded_type(&mut self, ty: &ASTType) {
+        let message = format!(
+            "No runtime check can be generated for type '{}', only for primitives, literals, functions, and the arrays, unions, objects and type aliases of them",
+            ty
+        );
+        self.report_error(message, *ty.span());
+    }
+
+    pub fn report_undeclared_type(&mut self, identifier: String, span: TextSpan) {
+        let message = format!("Undeclared type '{}'", identifier);
+        self.report_error(message, span);
+    }
+
+    pub fn report_type_used_as_value(&mut self, identifier: String, span: TextSpan) {
+        let message = format!(
+            "Type '{}' cannot be used as a value, only finite unions of literal types can",
+            identifier
+        );
+        self.report_error(message, span);
+    }
+
+    pub fn report_assignment_to_constant(
+        &mut self,
+        identifier: String,
+        span: TextSpan,
+        suggest_stateful: bool,
+    ) {
+        let message = match suggest_stateful {
+            true => format!(
+                "Cannot assign to constant '{}', declare it with `stateful(...)` to make it assignable",
+                identifier
+            ),
+            false => format!("Cannot assign to constant '{}'", identifier),
+        };
+        self.report_error(message, span);
+    }
+
+    pub fn report_invalid_assignment_target(&mut self, operator: &Token, target: TextSpan) {
+        let message = format!(
+            "Invalid left-hand side of '{}', expected a variable, member or index",
+            operator.kind
+        );
+        self.report_error(message, target);
+    }
+
+    pub fn report_unreachable_code(&mut self, span: TextSpan) {
+        let message = "Unreachable code".to_string();
+        self.report_warning(message, span);
+    }
+
+    pub fn report_misplaced_stateful(&mut self, span: TextSpan) {
+        let message =
+            "`stateful(...)` can only initialise a `const` binding inside a component".to_string();
+        self.report_error(message, span);
+    }
+
+    pub fn report_stateful_arguments(&mut self, count: usize, span: TextSpan) {
+        let message = format!(
+            "`stateful(...)` expects at most 1 argument, found {}",
+            count
+        );
+        self.report_error(message, span);
+    }
+
+    pub fn report_stateful_mutation(&mut self, identifier: String, span: TextSpan) {
+        let message = format!(
+            "Mutating stateful binding '{}' won't re-render the component, assign a new value instead",
+            identifier
+        );
+        self.report_warning(message, span);
+    }
+
+    pub fn report_callback_outside_component(&mut self, span: TextSpan) {
+        let message = "Callbacks can only be declared inside a component".to_string();
+        self.report_error(message, span);
+    }
+
+    pub fn report_nested_stateful(&mut self, span: TextSpan) {
+        let message = "`stateful(...)` can't be used inside a loop or callback, as React needs hooks called in the same order on every render".to_string();
+        self.report_error(message, span);
+    }
+
+    pub fn report_nested_callback(&mut self, span: TextSpan) {
+        let message = "Callbacks can't be declared inside a loop or callback, as React needs hooks called in the same order on every render".to_string();
+        self.report_error(message, span);
+    }
+
+    pub fn report_misplaced_label(&mut self, label: &Token) {
+        let message = format!(
+            "Label '{}' must be followed by a `for` or `while` loop",
+            label.text()
+        );
+        self.report_error(message, label.span);
+    }
+
+    pub fn report_jump_outside_loop(&mut self, keyword: &Token) {
+        let message = format!("'{}' can only be used inside a loop", keyword.kind);
+        self.report_error(message, keyword.span);
+    }
+
+    pub fn report_return_outside_function(&mut self, keyword: &Token) {
+        let message = "'return' can only be used inside a component or callback".to_string();
+        self.report_error(message, keyword.span);
+    }
+
+    pub fn report_misplaced_import(&mut self, keyword: &Token) {
+        let message = "Imports are only allowed at the top level of a file".to_string();
+        self.report_error(message, keyword.span);
+    }
+
+    pub fn report_unknown_modifier(&mut self, modifier: &Token, known: &[&str]) {
+        let message = format!(
+            "Unknown modifier '{}', expected one of: {}",
+            modifier.text(),
+            known.join(", ")
+        );
+        self.report_error(message, modifier.span);
+    }
+
+    pub fn report_invalid_modifier_arguments(&mut self, modifier: &Token, expected: &str) {
+        let message = format!("Modifier '{}' expects {}", modifier.text(), expected);
+        self.report_error(message, modifier.span);
+    }
+
+    pub fn report_duplicate_modifier(&mut self, modifier: &Token) {
+        let message = format!("Modifier '{}' can only be applied once", modifier.text());
+        self.report_error(message, modifier.span);
+    }
+
+    pub fn report_duplicate_props_name(&mut self, name: &str, span: TextSpan) {
+        let message = format!("Props interface '{}' is already declared", name);
+        self.report_error(message, span);
+    }
+
+    pub fn report_duplicate_type(&mut self, identifier: &Token) {
+        let message = format!("Type '{}' is already declared", identifier.text());
+        self.report_error(message, identifier.span);
+    }
+
+    pub fn report_misplaced_style(&mut self, keyword: &Token) {
+        let message = "Style blocks are only allowed directly inside a component".to_string();
+        self.report_error(message, keyword.span);
+    }
+
+    pub fn report_invalid_visibility_target(&mut self, keyword: &Token) {
+        let message =
+            "Only components, variables, constants and types can be declared 'pub'".to_string();
+        self.report_error(message, keyword.span);
+    }
+
+    pub fn report_unknown_visibility_scope(&mut self, scope: &Token) {
+        let message = format!(
+            "Unknown visibility 'pub({})', expected 'pub(dir)' or 'pub(super)'",
+            scope.text()
+        );
+        self.report_error(message, scope.span);
+    }
+
+    pub fn report_misplaced_visibility(&mut self, keyword: &Token) {
+        let message = "Only top-level declarations can be declared 'pub'".to_string();
+        self.report_error(message, keyword.span);
+    }
+
+    pub fn report_duplicate_default_export(&mut self, default: &Token) {
+        let message = "A file can only have one default export".to_string();
+        self.report_error(message, default.span);
+    }
+
+    pub fn report_destructured_default_export(&mut self, default: &Token) {
+        let message = "A destructuring declaration can't be a default export".to_string();
+        self.report_error(message, default.span);
+    }
+
+    pub fn report_private_import(&mut self, item: &Token, name: &str, module: &str) {
+        let message = format!(
+            "'{}' is private to '{}', declare it 'pub' to import it",
+            name, module
+        );
+        self.report_error(message, item.span);
+    }
+
+    pub fn report_inaccessible_import(&mut self, item: &Token, name: &str, folder: &str) {
+        let message = format!("'{}' is only visible within '{}'", name, folder);
+        self.report_error(message, item.span);
+    }
+
+    pub fn report_missing_export(&mut self, item: &Token, name: &str, module: &str) {
+        let message = format!("'{}' doesn't declare '{}'", module, name);
+        self.report_error(message, item.span);
+    }
+
+    pub fn report_missing_module(&mut self, item: &Token, module: &str) {
+        let message = format!("There is no module '{}' to import", module);
+        self.report_error(message, item.span);
+    }
+
+    pub fn report_undeclared_label(&mut self, label: &Token) {
+        let message = format!("Undeclared loop label '{}'", label.text());
+        self.report_error(message, label.span);
+    }
+
+    pub fn report_duplicate_label(&mut self, label: &Token) {
+        let message = format!(
+            "Label '{}' is already used by an enclosing loop",
+            label.text()
+        );
+        self.report_error(message, label.span);
+    }
+
+    pub fn report_undeclared_component(&mut self, identifier: &Token) {
+        let message = format!("Undeclared component '{}'", identifier.text());
+        self.report_error(message, identifier.span);
+    }
+
+    pub fn report_generic_base_component(&mut self, identifier: &Token) {
+        let message = format!(
+            "Component '{}' has generic parameters, so its props can't be extended",
+            identifier.text()
+        );
+        self.report_error(message, identifier.span);
+    }
+
+    pub fn report_compatible_prop_override(&mut self, prop: &Token, base: &str) {
+        let message = format!(
+            "Prop '{}' overrides the prop it inherits from '{}' with a compatible type, which wouldn't need omitting",
+            prop.text(),
+            base
+        );
+        self.report_warning(message, prop.span);
+    }
+
+    pub fn report_missing_prop(&mut self, element: &Token, prop: &str) {
+        let message = format!(
+            "Component '{}' requires the prop '{}'",
+            element.text(),
+            prop
+        );
+        self.report_error(message, element.span);
+    }
+
+    pub fn report_generic_count_mismatch(
+        &mut self,
+        component: &Token,
+        expected: usize,
+        found: usize,
+    ) {
+        let message = format!(
+            "Component '{}' takes {} generic parameter(s), but {} were given",
+            component.text(),
+            expected,
+            found
+        );
+        self.report_error(message, component.span);
+    }
+
+    pub fn report_generic_name_mismatch(&mut self, expected: &str, found: &Token) {
+        let message = format!(
+            "Expected generic parameter '{}', found '{}'",
+            expected,
+            found.text()
+        );
+        self.report_error(message, found.span);
+    }
+
+    pub fn report_unknown_prop(&mut self, component: &str, prop: &Token) {
+        let message = format!(
+            "Component '{}' has no prop named '{}'",
+            component,
+            prop.text()
+        );
+        self.report_error(message, prop.span);
+    }
+
+    pub fn report_ambiguous_line_continuation(&mut self, token: &Token) {
+        let message = format!(
+            "A line starting with '{}' continues the previous statement, add a ';' before it if that isn't intended",
+            token.kind
+        );
+        self.report_warning(message, token.span);
+    }
+
+    pub fn report_mismatched_closing_tag(&mut self, opening: &Token, closing: &Token) {
+        let message = format!(
+            "Expected closing tag </{}>, found </{}>",
+            opening.text(),
+            closing.text()
+        );
+        self.report_error(message, closing.span);
+    }
+
+    pub fn report_expected_type(&mut self, found: &Token) {
+        let message = format!("Expected type, found '{}'", found.kind);
+        self.report_error(message, found.span);
+    }
+
     pub fn report_expected_expression(&mut self, found: &Token) {
         let message = match found.kind.is_keyword() {
             true => format!(
@@ -81,7 +484,7 @@ impl DiagnosticsBag {
             false => format!("Expected expression, found '{}'", found.kind),
         };
 
-        self.report_error(message, found.span.clone());
+        self.report_error(message, found.span);
     }
 }
 
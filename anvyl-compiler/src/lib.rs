@@ -1,4 +1,9 @@
 pub mod ast;
+pub mod codegen;
+pub mod config;
 pub mod diagnostics;
 pub mod prelude;
 pub mod text;
+
+#[cfg(test)]
+mod testing;
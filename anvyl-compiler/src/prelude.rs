@@ -5,12 +5,16 @@ pub use crate::ast::expressions::*;
 pub use crate::ast::lexer::*;
 pub use crate::ast::parser::*;
 pub use crate::ast::printer::*;
+pub use crate::ast::semantics::*;
 pub use crate::ast::statements::*;
+pub use crate::ast::transform::*;
+pub use crate::ast::types::*;
 
 pub use crate::diagnostics::*;
 pub use crate::text::*;
 
 pub use crate::ast::AST;
+pub use crate::ast::ASTArena;
 pub use crate::ast::ASTVisitor;
 
 pub type DiagnosticsBagCell = Rc<RefCell<DiagnosticsBag>>;
@@ -0,0 +1,131 @@
+//! Helpers running the compiler pipeline over source text, for tests.
+
+use std::rc::Rc;
+
+use crate::ast::evaluator::{ASTEvaluator, Value};
+use crate::ast::hooks::HookLowering;
+use crate::ast::symbols::SymbolChecker;
+use crate::ast::typechecker::TypeChecker;
+use crate::ast::visibility::ProjectModules;
+use crate::codegen::TSXGenerator;
+use crate::config::Config;
+use crate::prelude::*;
+
+/// Parses `source`, panicking on syntax errors.
+pub(crate) fn parse(source: &str) -> AST<'static> {
+    parse_with_builder(source).0
+}
+
+/// Parses `source` like `parse`, along with a builder creating nodes for rewriting the tree.
+///
+/// The tree's arena is leaked, so tests can hold on to the tree however they like.
+pub(crate) fn parse_with_builder(source: &str) -> (AST<'static>, ASTBuilder<'static>) {
+    let (ast, builder, errors) = parse_recovering(source);
+    assert_eq!(errors, Vec::<String>::new());
+    (ast, builder)
+}
+
+/// Parses `source`, recovering from syntax errors, along with the messages of the errors.
+pub(crate) fn parse_with_errors(source: &str) -> (AST<'static>, Vec<String>) {
+    let (ast, _, errors) = parse_recovering(source);
+    (ast, errors)
+}
+
+fn parse_recovering(source: &str) -> (AST<'static>, ASTBuilder<'static>, Vec<String>) {
+    let arena: &'static ASTArena = Box::leak(Box::default());
+    let diagnostics = DiagnosticsBag::new_ref_cell();
+    let mut lexer = Lexer::new(source);
+    let mut tokens = Vec::new();
+    while let Some(token) = lexer.next_token() {
+        tokens.push(token);
+    }
+
+    let mut parser = Parser::new(
+        tokens,
+        &SourceText::new(source),
+        arena,
+        Rc::clone(&diagnostics),
+    );
+    let mut ast = AST::new();
+    while let Some(statement) = parser.next_statement() {
+        ast.add_statement(statement);
+    }
+    let builder = ASTBuilder::new(parser.next_node_id(), arena);
+    (ast, builder, messages(&diagnostics))
+}
+
+/// The messages of the reported diagnostics, in the order they were reported.
+pub(crate) fn messages(diagnostics: &DiagnosticsBagCell) -> Vec<String> {
+    diagnostics
+        .borrow()
+        .diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.message.clone())
+        .collect()
+}
+
+/// The outcome of compiling a file.
+pub(crate) struct Compilation {
+    /// The messages of the errors and warnings reported while checking the file.
+    pub(crate) diagnostics: Vec<String>,
+    /// The generated TSX, if checking the file reported no errors.
+    pub(crate) tsx: Option<String>,
+}
+
+/// Checks `source` and generates its TSX, as the driver does for a file outside of a project.
+pub(crate) fn compile(source: &str) -> Compilation {
+    compile_with(source, &Config::default(), &ProjectModules::new())
+}
+
+/// Like `compile`, but for a file of a project with the given settings and files.
+pub(crate) fn compile_with(source: &str, config: &Config, modules: &ProjectModules) -> Compilation {
+    generate(&parse(source), source, config, modules)
+}
+
+/// Like `compile`, but rewriting the tree with `transform` before checking it.
+pub(crate) fn compile_transformed(
+    source: &str,
+    transform: impl FnOnce(&mut AST<'static>, ASTBuilder<'static>),
+) -> Compilation {
+    let (mut ast, builder) = parse_with_builder(source);
+    transform(&mut ast, builder);
+    generate(&ast, source, &Config::default(), &ProjectModules::new())
+}
+
+/// Checks the tree of `source`, generating its TSX unless errors are reported.
+fn generate(ast: &AST, source: &str, config: &Config, modules: &ProjectModules) -> Compilation {
+    let text = SourceText::new(source);
+    let diagnostics = DiagnosticsBag::new_ref_cell();
+
+    let mut symbol_checker = SymbolChecker::new(Rc::clone(&diagnostics));
+    symbol_checker.check(ast);
+    let mut type_checker = TypeChecker::new(symbol_checker.model(), Rc::clone(&diagnostics));
+    type_checker.import_components(modules.imported_components(ast, symbol_checker.model()));
+    ast.visit(&mut type_checker);
+    let mut hook_lowering = HookLowering::new(symbol_checker.model(), Rc::clone(&diagnostics));
+    ast.visit(&mut hook_lowering);
+
+    let tsx = (!diagnostics.borrow().has_errors()).then(|| {
+        let mut generator = TSXGenerator::with_project(
+            ast,
+            &text,
+            symbol_checker.model(),
+            hook_lowering.hooks(),
+            config,
+            modules,
+        );
+        ast.visit(&mut generator);
+        generator.result()
+    });
+    Compilation {
+        diagnostics: messages(&diagnostics),
+        tsx,
+    }
+}
+
+/// Runs `source`, giving the value of its last expression.
+pub(crate) fn evaluate(source: &str) -> Value {
+    let mut evaluator = ASTEvaluator::new();
+    parse(source).visit(&mut evaluator);
+    evaluator.last_value.unwrap_or(Value::Undefined)
+}
@@ -1,22 +1,49 @@
+use std::rc::Rc;
+
+use crate::ast::lexer::TextSpan;
+
+/// The text of a file, which spans are read from. Clones share the text.
+#[derive(Debug, Clone)]
 pub struct SourceText {
-    text: String,
+    text: Rc<str>,
 }
 
 impl SourceText {
-    pub fn new(text: impl Into<String>) -> Self {
+    pub fn new(text: impl Into<Rc<str>>) -> Self {
         Self { text: text.into() }
     }
 
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// The text covered by `span`.
+    pub fn slice(&self, span: TextSpan) -> &str {
+        &self.text[span.start..span.end]
+    }
+
     pub fn line_index(&self, position: usize) -> usize {
-        let count = self.text[..position].lines().count();
-        match count {
-            0 => 0,
-            _ => count - 1,
-        }
+        self.text[..position].matches('\n').count()
     }
 
     pub fn get_line(&self, index: usize) -> &str {
-        self.text.lines().nth(index).unwrap()
+        self.text.lines().nth(index).unwrap_or_default()
+    }
+
+    /// Whether an empty line separates the `start` and `end` positions.
+    ///
+    /// Nodes created by tree transforms share the span of the code they replace, so `start`
+    /// may come after `end`, in which case there is nothing between them.
+    pub fn has_blank_line(&self, start: usize, end: usize) -> bool {
+        let Some(text) = self.text.get(start..end) else {
+            return false;
+        };
+        match (text.find('\n'), text.rfind('\n')) {
+            (Some(first), Some(last)) if first < last => text[first + 1..last]
+                .split('\n')
+                .any(|line| line.trim().is_empty()),
+            _ => false,
+        }
     }
 
     pub fn line_start(&self, index: usize) -> usize {
@@ -1,15 +1,61 @@
+use std::path::{Component, Path, PathBuf};
 use std::rc::Rc;
 
 use anvyl_compiler::{
-    ast::{evaluator::ASTEvaluator, symbols::SymbolChecker},
+    ast::{
+        evaluator::ASTEvaluator,
+        hooks::HookLowering,
+        symbols::SymbolChecker,
+        typechecker::TypeChecker,
+        visibility::{ModuleExports, ProjectFile, ProjectModules, VisibilityChecker},
+    },
+    codegen::TSXGenerator,
+    config::Config,
     prelude::*,
 };
 
+/// `anvyl-run [<file.avyl>] [--out <file.tsx>] [--inline-source-map]`
+#[derive(Default)]
+struct Options {
+    input: Option<PathBuf>,
+    /// Where to write the generated TSX, along with its source map as `<out>.map`, and the
+    /// scoped styles of its components as a `.css` file next to it.
+    out: Option<PathBuf>,
+    /// Embeds the source map in the TSX rather than writing it to a file of its own.
+    inline_source_map: bool,
+}
+
+impl Options {
+    fn parse() -> Result<Self, ()> {
+        let mut options = Options::default();
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--out" => {
+                    let out = args
+                        .next()
+                        .ok_or_else(|| eprintln!("--out expects a path"))?;
+                    options.out = Some(PathBuf::from(out));
+                }
+                "--inline-source-map" => options.inline_source_map = true,
+                _ => options.input = Some(PathBuf::from(arg)),
+            }
+        }
+        Ok(options)
+    }
+}
+
 fn main() -> Result<(), ()> {
-    let input = "let a = 10 + 11; let b = 20; let c = (a * b) + e;";
-    let text = SourceText::new(input);
+    let options = Options::parse()?;
+    // Compiles the given file, or a small example without one.
+    let input = match &options.input {
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|error| eprintln!("Failed to read {}: {}", path.display(), error))?,
+        None => "let a = 10 + 11; let b = 20; let c = (a * b) + e;".to_string(),
+    };
+    let text = SourceText::new(input.as_str());
 
-    let mut lexer = Lexer::new(input);
+    let mut lexer = Lexer::new(&input);
     let mut tokens = Vec::new();
 
     while let Some(token) = lexer.next_token() {
@@ -20,28 +66,69 @@ fn main() -> Result<(), ()> {
     for token in &tokens {
         println!("  {:?}", token);
     }
-    println!("");
+    println!();
 
     let diagnostics: DiagnosticsBagCell = DiagnosticsBag::new_ref_cell();
+    let arena = ASTArena::new();
     let mut ast = AST::new();
-    let mut parser = Parser::new(tokens, Rc::clone(&diagnostics));
+    let mut parser = Parser::new(tokens, &text, &arena, Rc::clone(&diagnostics));
 
     println!("Parsing statements...");
     while let Some(stmt) = parser.next_statement() {
         println!("  {:?}", stmt);
         ast.add_statement(stmt);
     }
-    println!("");
+    println!();
 
-    ast.visualize();
+    ast.visualize(&text);
 
     check_diagnostics(&text, Rc::clone(&diagnostics))?;
 
+    let config = load_config(options.input.as_deref())?;
+
     let mut symbol_checker = SymbolChecker::new(Rc::clone(&diagnostics));
-    ast.visit(&mut symbol_checker);
+    symbol_checker.check(&ast);
+    let modules = match &options.input {
+        Some(input) => check_visibility(input, &config, &ast, symbol_checker.model(), &diagnostics),
+        None => ProjectModules::new(),
+    };
+
+    let mut type_checker = TypeChecker::new(symbol_checker.model(), Rc::clone(&diagnostics));
+    type_checker.import_components(modules.imported_components(&ast, symbol_checker.model()));
+    ast.visit(&mut type_checker);
+
+    let mut hook_lowering = HookLowering::new(symbol_checker.model(), Rc::clone(&diagnostics));
+    ast.visit(&mut hook_lowering);
 
     check_diagnostics(&text, Rc::clone(&diagnostics))?;
 
+    let mut generator = TSXGenerator::with_project(
+        &ast,
+        &text,
+        symbol_checker.model(),
+        hook_lowering.hooks(),
+        &config,
+        &modules,
+    );
+    ast.visit(&mut generator);
+    if generator.stylesheet().is_some()
+        && let Some(out) = &options.out
+    {
+        let stylesheet = out.with_extension("css");
+        let file = stylesheet.file_name().unwrap_or_default().to_string_lossy();
+        generator.import_stylesheet(&format!("./{}", file));
+    }
+    println!("TSX:");
+    println!("{}", generator.result());
+    if let Some(css) = generator.stylesheet() {
+        println!("CSS:");
+        println!("{}", css);
+    }
+
+    if let Some(out) = &options.out {
+        write_output(&generator, &options, out)?;
+    }
+
     let mut eval = ASTEvaluator::new();
     ast.visit(&mut eval);
 
@@ -49,15 +136,175 @@ fn main() -> Result<(), ()> {
     Ok(())
 }
 
+/// The root of the project containing `input`: the closest directory above it holding an
+/// `anvyl.toml`.
+fn project_root(input: &Path) -> Option<PathBuf> {
+    let input = std::path::absolute(input).ok()?;
+    input
+        .ancestors()
+        .skip(1)
+        .find(|directory| directory.join("anvyl.toml").is_file())
+        .map(Path::to_path_buf)
+}
+
+/// Reads the `anvyl.toml` of the project containing `input`, or the default settings when
+/// there is none.
+fn load_config(input: Option<&Path>) -> Result<Config, ()> {
+    let Some(root) = input.and_then(project_root) else {
+        return Ok(Config::default());
+    };
+    let path = root.join("anvyl.toml");
+
+    let text = std::fs::read_to_string(&path)
+        .map_err(|error| eprintln!("Failed to read {}: {}", path.display(), error))?;
+    Config::parse(&text).map_err(|error| eprintln!("Invalid {}: {}", path.display(), error))
+}
+
+/// Extensions of the files of a project which aren't written in Anvyl.
+const FOREIGN_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx"];
+
+/// Reports the items `input` imports without being allowed to, when it is a file of the
+/// `src` folder of a project, by reading the exports of the project files it imports.
+///
+/// Returns those files, which decide how the imports are written in TypeScript.
+fn check_visibility(
+    input: &Path,
+    config: &Config,
+    ast: &AST,
+    model: &SemanticModel,
+    diagnostics: &DiagnosticsBagCell,
+) -> ProjectModules {
+    let mut modules = ProjectModules::new();
+    let Some(sources) = project_root(input).map(|root| root.join("src")) else {
+        return modules;
+    };
+    let Some(relative) = std::path::absolute(input)
+        .ok()
+        .and_then(|input| input.strip_prefix(&sources).ok().map(Path::to_path_buf))
+    else {
+        return modules;
+    };
+    let folder = relative
+        .parent()
+        .into_iter()
+        .flat_map(Path::components)
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+
+    let checker = VisibilityChecker::new(config, folder, Rc::clone(diagnostics));
+    for module in checker.dependencies(ast, model) {
+        let path = module
+            .iter()
+            .fold(sources.clone(), |path, segment| path.join(segment));
+        let file = match read_exports(&path.with_extension("avyl")) {
+            Some(exports) => ProjectFile::Anvyl(exports),
+            None if FOREIGN_EXTENSIONS
+                .iter()
+                .any(|extension| path.with_extension(extension).is_file()) =>
+            {
+                ProjectFile::Foreign
+            }
+            None => continue,
+        };
+        modules.insert(module, file);
+    }
+    checker.check(ast, model, &modules);
+    modules
+}
+
+/// The exports of the Anvyl file at `path`, along with the props of its components, or
+/// `None` when there is no such file.
+fn read_exports(path: &Path) -> Option<ModuleExports> {
+    let input = std::fs::read_to_string(path).ok()?;
+    let mut lexer = Lexer::new(&input);
+    let mut tokens = Vec::new();
+    while let Some(token) = lexer.next_token() {
+        tokens.push(token);
+    }
+
+    // Errors in the imported file are reported when compiling that file.
+    let text = SourceText::new(input);
+    let arena = ASTArena::new();
+    let mut parser = Parser::new(tokens, &text, &arena, DiagnosticsBag::new_ref_cell());
+    let mut ast = AST::new();
+    while let Some(statement) = parser.next_statement() {
+        ast.add_statement(statement);
+    }
+    Some(ModuleExports::checked(&ast))
+}
+
+/// Writes the generated TSX to `out`, and its source map either inline or to `<out>.map`.
+fn write_output(generator: &TSXGenerator, options: &Options, out: &Path) -> Result<(), ()> {
+    let write = |path: &Path, contents: String| {
+        std::fs::write(path, contents)
+            .map_err(|error| eprintln!("Failed to write {}: {}", path.display(), error))
+    };
+    if let Some(parent) = out.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|error| eprintln!("Failed to create {}: {}", parent.display(), error))?;
+    }
+
+    let file = out.file_name().unwrap_or_default().to_string_lossy();
+    let source = match &options.input {
+        Some(input) => relative_path(out.parent().unwrap_or(Path::new(".")), input),
+        None => "input.avyl".to_string(),
+    };
+    let source_map = generator.source_map(&file, &source);
+
+    if let Some(css) = generator.stylesheet() {
+        write(&out.with_extension("css"), css.to_string())?;
+    }
+
+    let comment = match options.inline_source_map {
+        true => source_map.inline_comment(),
+        false => {
+            let mut map = out.as_os_str().to_owned();
+            map.push(".map");
+            write(Path::new(&map), source_map.to_json())?;
+            source_map.url_comment()
+        }
+    };
+    write(out, format!("{}\n{}\n", generator.result(), comment))
+}
+
+/// The path to `target` from the `from` directory, with `/` separators.
+fn relative_path(from: &Path, target: &Path) -> String {
+    let absolute = |path: &Path| -> Vec<String> {
+        std::path::absolute(path)
+            .map(|path| {
+                path.components()
+                    .filter(|component| *component != Component::CurDir)
+                    .map(|component| component.as_os_str().to_string_lossy().into_owned())
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let (from, target) = (absolute(from), absolute(target));
+    let common = from
+        .iter()
+        .zip(&target)
+        .take_while(|(from, target)| from == target)
+        .count();
+
+    let parents = std::iter::repeat_n("..".to_string(), from.len() - common);
+    let rest = target[common..].iter().cloned();
+    parents.chain(rest).collect::<Vec<_>>().join("/")
+}
+
+/// Prints the diagnostics reported since the last check, failing when any of them is an
+/// error. Warnings are printed without stopping the compilation.
 fn check_diagnostics(text: &SourceText, diagnostics_bag: DiagnosticsBagCell) -> Result<(), ()> {
-    let diagnostics_binding = diagnostics_bag.borrow();
+    let mut diagnostics_binding = diagnostics_bag.borrow_mut();
     if !diagnostics_binding.is_empty() {
-        println!("");
+        println!();
         println!("Diagnostics found:");
-        let printer = Diagnostic::printer(&text, &diagnostics_binding.diagnostics);
+        let printer = Diagnostic::printer(text, &diagnostics_binding.diagnostics);
 
         printer.print();
-        return Err(());
+        if diagnostics_binding.has_errors() {
+            return Err(());
+        }
+        diagnostics_binding.diagnostics.clear();
     }
 
     Ok(())
@@ -0,0 +1,37 @@
+//! Compiles the example project, comparing the output with the checked-in `examples/dist`.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Compiles `examples/src/<file>.avyl` into a temporary directory, giving the path of the
+/// generated TSX.
+fn compile(file: &str) -> PathBuf {
+    let examples = Path::new(env!("CARGO_MANIFEST_DIR")).join("../examples");
+    let out = Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join("examples")
+        .join(file)
+        .with_extension("tsx");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_anvyl-run"))
+        .arg(examples.join("src").join(file).with_extension("avyl"))
+        .arg("--out")
+        .arg(&out)
+        .output()
+        .expect("anvyl-run should start");
+    assert!(
+        output.status.success(),
+        "compiling {} failed:\n{}",
+        file,
+        String::from_utf8_lossy(&output.stdout)
+    );
+    out
+}
+
+#[test]
+fn chat_room_matches_dist() {
+    let out = compile("components/ChatRoom");
+    let dist = Path::new(env!("CARGO_MANIFEST_DIR")).join("../examples/dist/components");
+
+    let read = |path: &Path| std::fs::read_to_string(path).expect("the file should exist");
+    assert_eq!(read(&out), read(&dist.join("ChatRoom.tsx")));
+}
@@ -25,3 +25,5 @@ export default function ChatRoom({ room }: ChatViewArguments) {
     </ChatContext>
   );
 }
+
+//# sourceMappingURL=ChatRoom.tsx.map
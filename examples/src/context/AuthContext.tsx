@@ -0,0 +1,10 @@
+import { createContext } from "react";
+
+export interface User {
+  id: string;
+  name: string;
+}
+
+const AuthContext = createContext<{ user: User | undefined }>({ user: undefined });
+
+export default AuthContext;
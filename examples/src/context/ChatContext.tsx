@@ -0,0 +1,10 @@
+import { createContext } from "react";
+
+export interface ChatContextValue {
+  room: string;
+  send: (text: string) => void;
+}
+
+const ChatContext = createContext<ChatContextValue | undefined>(undefined);
+
+export default ChatContext;
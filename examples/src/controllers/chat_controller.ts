@@ -0,0 +1,25 @@
+import type { User } from "@contexts/AuthContext";
+import type { ChatContextValue } from "@contexts/ChatContext";
+
+export interface Message {
+  id: string;
+  author: string;
+  text: string;
+}
+
+/** Connects to the chat server on `port`, as `user`, to read and post the messages of `room`. */
+export default function chat_controller(port: number, room: string, user: User | undefined) {
+  const messages: Message[] = [];
+  const socket = new WebSocket(`ws://localhost:${port}/rooms/${encodeURIComponent(room)}`);
+  socket.addEventListener("message", (event) => messages.push(JSON.parse(event.data)));
+
+  const context: ChatContextValue = {
+    room,
+    send: (text) => socket.send(JSON.stringify({ author: user?.name, text })),
+  };
+
+  return {
+    context,
+    get_messages: () => messages,
+  };
+}